@@ -27,4 +27,12 @@ SECTIONS {
     . = ALIGN(4);
     *(.rtc_fast.noinit .rtc_fast.noinit.*)
   } > RTC_FAST_RWDATA
+
+ .rtc_fast.persistent (NOLOAD) :
+  {
+    . = ALIGN(4);
+    _rtc_fast_persistent_start = ABSOLUTE(.);
+    *(.rtc_fast.persistent .rtc_fast.persistent.*)
+    _rtc_fast_persistent_end = ABSOLUTE(.);
+  } > RTC_FAST_RWDATA
 }
\ No newline at end of file
@@ -27,4 +27,12 @@ SECTIONS {
     . = ALIGN(4);
     *(.rtc_slow.noinit .rtc_slow.noinit.*)
   } > rtc_slow_seg
+
+ .rtc_slow.persistent (NOLOAD) :
+  {
+    . = ALIGN(4);
+    _rtc_slow_persistent_start = ABSOLUTE(.);
+    *(.rtc_slow.persistent .rtc_slow.persistent.*)
+    _rtc_slow_persistent_end = ABSOLUTE(.);
+  } > rtc_slow_seg
 }
\ No newline at end of file
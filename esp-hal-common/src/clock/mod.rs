@@ -1,4 +1,6 @@
 //! # Clock Control
+use core::sync::atomic::{AtomicU32, Ordering};
+
 use fugit::HertzU32;
 
 use crate::{
@@ -15,6 +17,18 @@ use crate::{
 #[cfg_attr(esp32s3, path = "clocks_ll/esp32s3.rs")]
 pub(crate) mod clocks_ll;
 
+/// CPU clock frequency in Hz of the most recently frozen [`Clocks`]
+static CPU_CLOCK: AtomicU32 = AtomicU32::new(0);
+
+/// The CPU clock frequency as set up by the most recently frozen [`Clocks`].
+///
+/// Unlike a copy of [`Clocks::cpu_clock`] this stays correct if the clocks
+/// get reconfigured later on (e.g. by another crate creating a new
+/// [`Clocks`]), returns 0 Hz before any clocks were frozen.
+pub fn cpu_clock() -> HertzU32 {
+    HertzU32::Hz(CPU_CLOCK.load(Ordering::Relaxed))
+}
+
 pub trait Clock {
     fn frequency(&self) -> HertzU32;
 
@@ -86,6 +100,58 @@ impl Clock for XtalClock {
     }
 }
 
+/// Frequency of the main crystal
+///
+/// Only the crystal frequencies supported by the chip are available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XtalFrequency {
+    #[cfg(esp32)]
+    X24M,
+    #[cfg(any(esp32, esp32c2))]
+    X26M,
+    #[cfg(esp32h2)]
+    X32M,
+    #[cfg(not(esp32h2))]
+    X40M,
+}
+
+impl Clock for XtalFrequency {
+    fn frequency(&self) -> HertzU32 {
+        match self {
+            #[cfg(esp32)]
+            XtalFrequency::X24M => HertzU32::MHz(24),
+            #[cfg(any(esp32, esp32c2))]
+            XtalFrequency::X26M => HertzU32::MHz(26),
+            #[cfg(esp32h2)]
+            XtalFrequency::X32M => HertzU32::MHz(32),
+            #[cfg(not(esp32h2))]
+            XtalFrequency::X40M => HertzU32::MHz(40),
+        }
+    }
+}
+
+impl XtalFrequency {
+    const ALL: &'static [XtalFrequency] = &[
+        #[cfg(esp32)]
+        XtalFrequency::X24M,
+        #[cfg(any(esp32, esp32c2))]
+        XtalFrequency::X26M,
+        #[cfg(esp32h2)]
+        XtalFrequency::X32M,
+        #[cfg(not(esp32h2))]
+        XtalFrequency::X40M,
+    ];
+
+    /// The supported crystal frequency closest to `mhz`
+    #[allow(unused)]
+    fn closest(mhz: u32) -> XtalFrequency {
+        *Self::ALL
+            .iter()
+            .min_by_key(|xtal| xtal.mhz().abs_diff(mhz))
+            .unwrap()
+    }
+}
+
 #[allow(unused)]
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum PllClock {
@@ -143,6 +209,8 @@ impl<'d> Clocks<'d> {
         system_clock_control: PeripheralRef<'d, SystemClockControl>,
         raw_clocks: RawClocks,
     ) -> Clocks<'d> {
+        CPU_CLOCK.store(raw_clocks.cpu_clock.raw(), Ordering::Relaxed);
+
         Self {
             _private: system_clock_control,
             cpu_clock: raw_clocks.cpu_clock,
@@ -194,6 +262,43 @@ impl<'d> ClockControl<'d> {
     pub fn freeze(self) -> Clocks<'d> {
         Clocks::from_raw_clocks(self._private, self.desired_rates)
     }
+
+    /// Override the frequency of the main crystal.
+    ///
+    /// This changes the XTAL frequency reported in [`Clocks::xtal_clock`] and
+    /// the frequencies of the clocks derived from it, i.e. [`Clocks::i2c_clock`]
+    /// on all chips but the ESP32 and ESP32-S2 (where I2C is clocked from APB).
+    /// The PLL is not reconfigured, so on chips where the crystal frequency is
+    /// selected by a feature (ESP32, ESP32-C2) that feature still needs to
+    /// match the board.
+    pub fn with_xtal(mut self, xtal: XtalFrequency) -> Self {
+        self.desired_rates.xtal_clock = xtal.frequency();
+        #[cfg(not(any(esp32, esp32s2)))]
+        {
+            self.desired_rates.i2c_clock = xtal.frequency();
+        }
+        self
+    }
+
+    /// Detect the frequency of the main crystal, see
+    /// [`ClockControl::with_xtal`].
+    ///
+    /// On the ESP32 and ESP32-C2, which support more than one crystal
+    /// frequency, the crystal is calibrated against the internal 8MHz
+    /// oscillator and the measurement is rounded to the closest supported
+    /// frequency. All other chips only support a single crystal frequency,
+    /// which is used without measuring.
+    pub fn with_detected_xtal(self) -> Self {
+        #[cfg(any(esp32, esp32c2))]
+        let xtal = {
+            let mhz = crate::rtc_cntl::RtcClock::estimate_xtal_frequency();
+            XtalFrequency::closest(mhz)
+        };
+        #[cfg(not(any(esp32, esp32c2)))]
+        let xtal = XtalFrequency::ALL[0];
+
+        self.with_xtal(xtal)
+    }
 }
 
 #[cfg(esp32)]
@@ -256,7 +361,7 @@ impl<'d> ClockControl<'d> {
             desired_rates: RawClocks {
                 cpu_clock: cpu_clock_speed.frequency(),
                 apb_clock: HertzU32::MHz(80),
-                xtal_clock: HertzU32::MHz(40),
+                xtal_clock: xtal_freq.frequency(),
                 i2c_clock: HertzU32::MHz(40),
                 // The docs are unclear here. pwm_clock seems to be tied to clocks.apb_clock
                 // while simultaneously being fixed at 160 MHz.
@@ -328,7 +433,7 @@ impl<'d> ClockControl<'d> {
                 cpu_clock: cpu_clock_speed.frequency(),
                 apb_clock: apb_freq.frequency(),
                 xtal_clock: xtal_freq.frequency(),
-                i2c_clock: HertzU32::MHz(40),
+                i2c_clock: xtal_freq.frequency(),
             },
         }
     }
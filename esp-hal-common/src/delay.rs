@@ -15,8 +15,14 @@ use fugit::HertzU64;
 ///
 /// Uses the `SYSTIMER` peripheral internally for RISC-V devices, and the
 /// built-in Xtensa timer for Xtensa devices.
+///
+/// On Xtensa devices the delay counts CPU cycles, the number of cycles per
+/// microsecond is derived from the current CPU clock (see
+/// [`crate::clock::cpu_clock`]) on every call so delays stay accurate when
+/// the CPU frequency changes.
 #[derive(Clone, Copy)]
 pub struct Delay {
+    #[cfg(riscv)]
     freq: HertzU64,
 }
 
@@ -80,16 +86,70 @@ mod delay {
 
     impl Delay {
         /// Create a new `Delay` instance
-        pub fn new(clocks: &Clocks) -> Self {
-            Self {
-                freq: clocks.cpu_clock.into(),
-            }
+        pub fn new(_clocks: &Clocks) -> Self {
+            Self {}
         }
 
         /// Delay for the specified number of microseconds
         pub fn delay(&self, us: u32) {
-            let clocks = us as u64 * (self.freq / HertzU64::MHz(1));
+            let freq: HertzU64 = crate::clock::cpu_clock().into();
+            let clocks = us as u64 * (freq / HertzU64::MHz(1));
             xtensa_lx::timer::delay(clocks as u32);
         }
     }
 }
+
+/// Delay driver based on the `ets_delay_us` function of the ROM
+///
+/// Unlike [`Delay`] this doesn't need a [`Clocks`](crate::clock::Clocks), so
+/// it can be used during early boot before the clocks are frozen, e.g. to wait
+/// for a power rail to settle. The ROM busy-waits counting CPU cycles, using
+/// the CPU frequency it was last told about: the one set up by the
+/// bootloader, or the XTAL frequency if the CPU still runs from it. The delay
+/// is only accurate if the CPU frequency wasn't changed afterwards without
+/// updating the ROM, and like any busy-wait it's extended by interrupts.
+#[derive(Clone, Copy, Default)]
+pub struct RomDelay;
+
+impl RomDelay {
+    /// Create a new `RomDelay` instance
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Delay for the specified number of microseconds
+    pub fn delay(&self, us: u32) {
+        extern "C" {
+            fn ets_delay_us(us: u32);
+        }
+
+        unsafe { ets_delay_us(us) };
+    }
+}
+
+impl<T> embedded_hal::blocking::delay::DelayMs<T> for RomDelay
+where
+    T: Into<u32>,
+{
+    fn delay_ms(&mut self, ms: T) {
+        for _ in 0..ms.into() {
+            self.delay(1000u32);
+        }
+    }
+}
+
+impl<T> embedded_hal::blocking::delay::DelayUs<T> for RomDelay
+where
+    T: Into<u32>,
+{
+    fn delay_us(&mut self, us: T) {
+        self.delay(us.into());
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl embedded_hal_1::delay::DelayUs for RomDelay {
+    fn delay_us(&mut self, us: u32) {
+        self.delay(us);
+    }
+}
@@ -0,0 +1,147 @@
+//! Interrupt-mode executor
+//!
+//! An [`InterruptExecutor`] polls its tasks from one of the four software
+//! interrupts (`FROM_CPU_INTR0..3`), so they preempt the tasks of the
+//! thread-mode executor and all interrupt handlers of a lower priority.
+//!
+//! The interrupt handlers of the software interrupts are defined by this
+//! module, so they can't be defined by the application as well.
+//!
+//! ```rust,ignore
+//! static EXECUTOR: InterruptExecutor = InterruptExecutor::new(SoftwareInterrupt::SoftwareInterrupt1);
+//!
+//! let spawner = EXECUTOR.start(Priority::Priority3).unwrap();
+//! spawner.spawn(i2c_task(i2c)).unwrap();
+//! ```
+
+use core::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{AtomicBool, AtomicPtr, Ordering},
+};
+
+use embassy_executor::{raw, Pender, SendSpawner};
+
+use procmacros::interrupt;
+
+use crate::{
+    interrupt::{self, Priority},
+    system::SoftwareInterrupt,
+};
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_EXECUTOR: AtomicPtr<raw::Executor> = AtomicPtr::new(ptr::null_mut());
+
+/// The started executors, indexed by their software interrupt
+static EXECUTORS: [AtomicPtr<raw::Executor>; 4] = [NO_EXECUTOR; 4];
+
+/// An executor running its tasks in a software interrupt
+pub struct InterruptExecutor {
+    interrupt: SoftwareInterrupt,
+    started: AtomicBool,
+    executor: UnsafeCell<MaybeUninit<raw::Executor>>,
+}
+
+unsafe impl Send for InterruptExecutor {}
+unsafe impl Sync for InterruptExecutor {}
+
+impl InterruptExecutor {
+    /// Create a new executor running in the given software interrupt
+    pub const fn new(interrupt: SoftwareInterrupt) -> Self {
+        Self {
+            interrupt,
+            started: AtomicBool::new(false),
+            executor: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Start the executor at the given interrupt priority
+    ///
+    /// Tasks are spawned with the returned spawner, which can be used from
+    /// any other executor or interrupt handler. To spawn tasks from within
+    /// the executor's own tasks use their `Spawner` argument instead.
+    ///
+    /// Fails if the software interrupt can't be enabled at `priority`, in
+    /// that case the executor isn't started and `start` can be called again.
+    ///
+    /// # Panics
+    ///
+    /// If the executor was already started, or another executor was started
+    /// for the same software interrupt.
+    pub fn start(&'static self, priority: Priority) -> Result<SendSpawner, interrupt::Error> {
+        if self.started.swap(true, Ordering::Acquire) {
+            panic!("InterruptExecutor::start() called multiple times");
+        }
+
+        let executor = unsafe {
+            (*self.executor.get()).write(raw::Executor::new(Pender::new_from_callback(
+                pend,
+                self.interrupt as usize as *mut (),
+            )))
+        };
+
+        if EXECUTORS[self.interrupt as usize]
+            .compare_exchange(
+                ptr::null_mut(),
+                executor as *mut _,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_err()
+        {
+            panic!("Software interrupt already used by another InterruptExecutor");
+        }
+
+        if let Err(error) = interrupt::enable(self.interrupt.interrupt(), priority) {
+            EXECUTORS[self.interrupt as usize].store(ptr::null_mut(), Ordering::Release);
+            self.started.store(false, Ordering::Release);
+            return Err(error);
+        }
+
+        Ok(executor.spawner().make_send())
+    }
+}
+
+/// Raise the software interrupt of an executor with pending work
+fn pend(context: *mut ()) {
+    software_interrupt(context as usize).raise();
+}
+
+fn software_interrupt(index: usize) -> SoftwareInterrupt {
+    match index {
+        0 => SoftwareInterrupt::SoftwareInterrupt0,
+        1 => SoftwareInterrupt::SoftwareInterrupt1,
+        2 => SoftwareInterrupt::SoftwareInterrupt2,
+        _ => SoftwareInterrupt::SoftwareInterrupt3,
+    }
+}
+
+fn on_interrupt(index: usize) {
+    software_interrupt(index).reset();
+
+    let executor = EXECUTORS[index].load(Ordering::Acquire);
+    if !executor.is_null() {
+        unsafe { (*executor).poll() };
+    }
+}
+
+#[interrupt]
+fn FROM_CPU_INTR0() {
+    on_interrupt(0);
+}
+
+#[interrupt]
+fn FROM_CPU_INTR1() {
+    on_interrupt(1);
+}
+
+#[interrupt]
+fn FROM_CPU_INTR2() {
+    on_interrupt(2);
+}
+
+#[interrupt]
+fn FROM_CPU_INTR3() {
+    on_interrupt(3);
+}
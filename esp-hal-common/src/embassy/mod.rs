@@ -1,6 +1,7 @@
 use core::{cell::Cell, ptr};
 
 use embassy_time::driver::{AlarmHandle, Driver};
+use embedded_hal::watchdog::WatchdogDisable;
 
 #[cfg_attr(
     all(systimer, feature = "embassy-time-systick",),
@@ -12,14 +13,265 @@ use embassy_time::driver::{AlarmHandle, Driver};
 )]
 mod time_driver;
 
+#[cfg(feature = "embassy-executor-interrupt")]
+pub mod executor;
+
 use time_driver::EmbassyTimer;
 
-use crate::clock::Clocks;
+#[cfg(pdma)]
+use crate::system::Dma;
+use crate::{
+    clock::{ClockControl, Clocks},
+    gpio::IO,
+    peripherals::{GPIO, IO_MUX, TIMG0},
+    system::{
+        CpuControl,
+        PeripheralClockControl,
+        RadioClockControl,
+        SoftwareInterruptControl,
+        SystemExt,
+    },
+    timer::TimerGroup,
+    Rtc,
+};
 
 pub fn init(clocks: &Clocks, td: time_driver::TimerType) {
     EmbassyTimer::init(clocks, td)
 }
 
+#[cfg(esp32)]
+type SystemPeripheral = crate::peripherals::DPORT;
+#[cfg(any(esp32c6, esp32h2))]
+type SystemPeripheral = crate::peripherals::PCR;
+#[cfg(not(any(esp32, esp32c6, esp32h2)))]
+type SystemPeripheral = crate::peripherals::SYSTEM;
+
+#[cfg(any(esp32c6, esp32h2))]
+type RtcPeripheral = crate::peripherals::LP_CLKRST;
+#[cfg(not(any(esp32c6, esp32h2)))]
+type RtcPeripheral = crate::peripherals::RTC_CNTL;
+
+#[cfg(not(esp32c2))]
+type Timg1Peripheral = crate::peripherals::TIMG1;
+#[cfg(esp32c2)]
+type Timg1Peripheral = ();
+
+#[cfg(feature = "embassy-time-systick")]
+type SystimerPeripheral = crate::peripherals::SYSTIMER;
+#[cfg(not(feature = "embassy-time-systick"))]
+type SystimerPeripheral = ();
+
+/// The parts of the common setup done by [`init_default!`] that are still
+/// needed afterwards
+pub struct DefaultParts {
+    pub clocks: Clocks<'static>,
+    pub peripheral_clock_control: PeripheralClockControl,
+    pub cpu_control: CpuControl,
+    #[cfg(pdma)]
+    pub dma: Dma,
+    pub radio_clock_control: RadioClockControl,
+    pub software_interrupt_control: SoftwareInterruptControl,
+    pub rtc: Rtc<'static>,
+    pub io: IO,
+}
+
+/// Implementation of [`init_default!`], which picks the peripherals for the
+/// chip and the enabled time driver
+#[doc(hidden)]
+#[allow(unused_variables)]
+pub fn setup_default(
+    system: SystemPeripheral,
+    rtc: RtcPeripheral,
+    timg0: TIMG0,
+    timg1: Timg1Peripheral,
+    systimer: SystimerPeripheral,
+    gpio: GPIO,
+    io_mux: IO_MUX,
+) -> DefaultParts {
+    let system = system.split();
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+    let mut peripheral_clock_control = system.peripheral_clock_control;
+
+    let mut rtc = Rtc::new(rtc);
+    let timer_group0 = TimerGroup::new(timg0, &clocks, &mut peripheral_clock_control);
+    let mut wdt0 = timer_group0.wdt;
+    #[cfg(not(esp32c2))]
+    let mut wdt1 = TimerGroup::new(timg1, &clocks, &mut peripheral_clock_control).wdt;
+
+    // Disable watchdog timers
+    #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+    rtc.swd.disable();
+    rtc.rwdt.disable();
+    wdt0.disable();
+    #[cfg(not(esp32c2))]
+    wdt1.disable();
+
+    #[cfg(feature = "embassy-time-systick")]
+    init(&clocks, crate::systimer::SystemTimer::new(systimer));
+
+    #[cfg(feature = "embassy-time-timg0")]
+    init(&clocks, timer_group0.timer0);
+
+    DefaultParts {
+        clocks,
+        peripheral_clock_control,
+        cpu_control: system.cpu_control,
+        #[cfg(pdma)]
+        dma: system.dma,
+        radio_clock_control: system.radio_clock_control,
+        software_interrupt_control: system.software_interrupt_control,
+        rtc,
+        io: IO::new(gpio, io_mux),
+    }
+}
+
+/// Do the setup every embassy application starts with
+///
+/// Takes the peripherals it needs out of `peripherals` (a
+/// [`Peripherals`](crate::peripherals::Peripherals) binding), the remaining
+/// ones can still be used afterwards:
+///
+/// - splits the SYSTEM/DPORT/PCR peripheral and freezes the boot default
+///   clocks
+/// - disables the RTC watchdog (and super watchdog where present) and the
+///   watchdogs of TIMG0 and TIMG1
+/// - initializes the time driver selected by the `embassy-time-*` feature
+///   with SYSTIMER or timer 0 of TIMG0
+/// - creates the [`IO`] driver
+///
+/// Evaluates to [`DefaultParts`]. The other timers of TIMG0 and TIMG1 are
+/// dropped; do the setup by hand with [`init`] to use them or to configure
+/// the clocks or watchdogs differently.
+///
+/// ```rust,ignore
+/// let peripherals = Peripherals::take();
+/// let embassy::DefaultParts { clocks, io, .. } = embassy::init_default!(peripherals);
+/// ```
+pub use crate::__embassy_init_default as init_default;
+
+#[doc(hidden)]
+#[cfg(esp32)]
+#[macro_export]
+macro_rules! __embassy_init_default {
+    ($peripherals:ident) => {
+        $crate::embassy::setup_default(
+            $peripherals.DPORT,
+            $peripherals.RTC_CNTL,
+            $peripherals.TIMG0,
+            $peripherals.TIMG1,
+            (),
+            $peripherals.GPIO,
+            $peripherals.IO_MUX,
+        )
+    };
+}
+
+#[doc(hidden)]
+#[cfg(esp32s2)]
+#[macro_export]
+macro_rules! __embassy_init_default {
+    ($peripherals:ident) => {
+        $crate::embassy::setup_default(
+            $peripherals.SYSTEM,
+            $peripherals.RTC_CNTL,
+            $peripherals.TIMG0,
+            $peripherals.TIMG1,
+            $crate::__embassy_systimer!($peripherals),
+            $peripherals.GPIO,
+            $peripherals.IO_MUX,
+        )
+    };
+}
+
+#[doc(hidden)]
+#[cfg(esp32c2)]
+#[macro_export]
+macro_rules! __embassy_init_default {
+    ($peripherals:ident) => {
+        $crate::embassy::setup_default(
+            $peripherals.SYSTEM,
+            $peripherals.RTC_CNTL,
+            $peripherals.TIMG0,
+            (),
+            $crate::__embassy_systimer!($peripherals),
+            $peripherals.GPIO,
+            $peripherals.IO_MUX,
+        )
+    };
+}
+
+#[doc(hidden)]
+#[cfg(any(esp32c3, esp32s3))]
+#[macro_export]
+macro_rules! __embassy_init_default {
+    ($peripherals:ident) => {
+        $crate::embassy::setup_default(
+            $peripherals.SYSTEM,
+            $peripherals.RTC_CNTL,
+            $peripherals.TIMG0,
+            $peripherals.TIMG1,
+            $crate::__embassy_systimer!($peripherals),
+            $peripherals.GPIO,
+            $peripherals.IO_MUX,
+        )
+    };
+}
+
+#[doc(hidden)]
+#[cfg(any(esp32c6, esp32h2))]
+#[macro_export]
+macro_rules! __embassy_init_default {
+    ($peripherals:ident) => {
+        $crate::embassy::setup_default(
+            $peripherals.PCR,
+            $peripherals.LP_CLKRST,
+            $peripherals.TIMG0,
+            $peripherals.TIMG1,
+            $crate::__embassy_systimer!($peripherals),
+            $peripherals.GPIO,
+            $peripherals.IO_MUX,
+        )
+    };
+}
+
+#[doc(hidden)]
+#[cfg(feature = "embassy-time-systick")]
+#[macro_export]
+macro_rules! __embassy_systimer {
+    ($peripherals:ident) => {
+        $peripherals.SYSTIMER
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "embassy-time-systick"))]
+#[macro_export]
+macro_rules! __embassy_systimer {
+    ($peripherals:ident) => {
+        ()
+    };
+}
+
+/// Rate in Hz at which the time driver ticks.
+///
+/// This always matches the `tick-hz-*` feature `embassy-time` was built with,
+/// the divider of the TIMG0 based driver is derived from it; enable a lower
+/// tick rate to trade resolution for a longer maximum alarm duration. Returns
+/// 0 for the TIMG0 based driver before [`init`] was called.
+pub fn tick_hz() -> u64 {
+    EmbassyTimer::tick_hz()
+}
+
+/// The current time of the RTC timer as an [`embassy_time::Instant`]
+///
+/// This is a separate time base from the one of the time driver (e.g.
+/// [`embassy_time::Instant::now`]) which keeps counting in light sleep,
+/// usable for timestamping. Don't mix instants of both time bases.
+#[cfg(any(lp_clkrst, rtc_cntl))]
+pub fn rtc_now() -> embassy_time::Instant {
+    embassy_time::Instant::from_micros(crate::rtc_cntl::time_us())
+}
+
 pub struct AlarmState {
     pub timestamp: Cell<u64>,
 
@@ -54,6 +54,10 @@ impl EmbassyTimer {
         })
     }
 
+    pub(crate) fn tick_hz() -> u64 {
+        SystemTimer::TICKS_PER_SECOND
+    }
+
     pub fn init(_clocks: &Clocks, _systimer: TimerType) {
         use crate::{interrupt, interrupt::Priority, macros::interrupt};
 
@@ -1,3 +1,5 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
 use critical_section::{CriticalSection, Mutex};
 use peripherals::TIMG0;
 
@@ -20,6 +22,8 @@ pub struct EmbassyTimer {
 
 const ALARM_STATE_NONE: AlarmState = AlarmState::new();
 
+static TICK_HZ: AtomicU32 = AtomicU32::new(0);
+
 embassy_time::time_driver_impl!(static DRIVER: EmbassyTimer = EmbassyTimer {
     alarms: Mutex::new([ALARM_STATE_NONE; ALARM_COUNT]),
 });
@@ -46,12 +50,24 @@ impl EmbassyTimer {
         });
     }
 
+    pub(crate) fn tick_hz() -> u64 {
+        TICK_HZ.load(Ordering::Relaxed) as u64
+    }
+
     pub fn init(clocks: &Clocks, mut timer: TimerType) {
         use crate::{interrupt, interrupt::Priority};
 
-        // set divider to get a 1mhz clock. abp (80mhz) / 80 = 1mhz... // TODO assert
-        // abp clock is the source and its at the correct speed for the divider
-        timer.set_divider(clocks.apb_clock.to_MHz() as u16);
+        // The timer has to tick at the rate `embassy-time` was configured for
+        // (via its `tick-hz-*` features), otherwise all durations are skewed.
+        // APB clock is the source, so pick the divider accordingly.
+        let apb_hz = clocks.apb_clock.to_Hz() as u64;
+        let divider = apb_hz / embassy_time::TICK_HZ;
+        assert!(
+            (2..=u16::MAX as u64).contains(&divider) && apb_hz % embassy_time::TICK_HZ == 0,
+            "embassy-time tick rate can't be derived from the APB clock"
+        );
+        timer.set_divider(divider as u16);
+        TICK_HZ.store((apb_hz / divider) as u32, Ordering::Relaxed);
 
         interrupt::enable(peripherals::Interrupt::TG0_T0_LEVEL, Priority::max()).unwrap();
 
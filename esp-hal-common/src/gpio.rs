@@ -11,6 +11,8 @@
 use core::{convert::Infallible, marker::PhantomData};
 
 use crate::peripherals::{GPIO, IO_MUX};
+#[cfg(all(feature = "async", feature = "embassy"))]
+pub use self::asynch::{DebouncedInput, TimedOut};
 pub use crate::soc::gpio::*;
 pub(crate) use crate::{analog, gpio};
 
@@ -72,6 +74,9 @@ pub struct AF1;
 #[doc(hidden)]
 pub struct AF2;
 
+#[doc(hidden)]
+pub struct AFAny;
+
 pub enum DriveStrength {
     I5mA  = 0,
     I10mA = 1,
@@ -79,6 +84,15 @@ pub enum DriveStrength {
     I40mA = 3,
 }
 
+/// Output edge rate, see [`OutputPin::set_slew_rate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlewRate {
+    /// Slowest edges, for long traces and EMI sensitive designs
+    Slow,
+    /// Fastest edges, this is the reset default
+    Fast,
+}
+
 #[derive(PartialEq)]
 pub enum AlternateFunction {
     Function0 = 0,
@@ -89,6 +103,20 @@ pub enum AlternateFunction {
     Function5 = 5,
 }
 
+impl AlternateFunction {
+    fn from_index(index: usize) -> Self {
+        match index {
+            0 => AlternateFunction::Function0,
+            1 => AlternateFunction::Function1,
+            2 => AlternateFunction::Function2,
+            3 => AlternateFunction::Function3,
+            4 => AlternateFunction::Function4,
+            5 => AlternateFunction::Function5,
+            _ => unreachable!(),
+        }
+    }
+}
+
 pub trait RTCPin {}
 
 pub trait AnalogPin {}
@@ -155,6 +183,13 @@ pub trait InputPin: Pin {
     /// pin with the given [input `signal`](`InputSignal`). Any other
     /// connected signals remain intact.
     fn disconnect_input_from_peripheral(&mut self, signal: InputSignal) -> &mut Self;
+
+    /// Change the condition triggering the interrupt of the pin, e.g. from an
+    /// edge to a level, keeping it enabled or disabled as it is
+    ///
+    /// The interrupt is disabled while it's changed and any pending interrupt
+    /// of the pin is cleared, so the old condition doesn't fire right after.
+    fn set_interrupt_type(&mut self, event: Event) -> &mut Self;
 }
 
 pub trait OutputPin: Pin {
@@ -168,6 +203,20 @@ pub trait OutputPin: Pin {
 
     fn set_drive_strength(&mut self, strength: DriveStrength) -> &mut Self;
 
+    /// Set the slew rate of the output
+    ///
+    /// None of the supported chips has a slew rate control separate from the
+    /// drive strength, the edge rate follows from the pad drive current. So
+    /// this sets [`DriveStrength::I5mA`] for [`SlewRate::Slow`] and the reset
+    /// default [`DriveStrength::I20mA`] for [`SlewRate::Fast`], overriding any
+    /// previous [`OutputPin::set_drive_strength`].
+    fn set_slew_rate(&mut self, rate: SlewRate) -> &mut Self {
+        match rate {
+            SlewRate::Slow => self.set_drive_strength(DriveStrength::I5mA),
+            SlewRate::Fast => self.set_drive_strength(DriveStrength::I20mA),
+        }
+    }
+
     fn enable_open_drain(&mut self, on: bool) -> &mut Self;
 
     fn enable_output_in_sleep_mode(&mut self, on: bool) -> &mut Self;
@@ -199,6 +248,50 @@ pub trait OutputPin: Pin {
     fn internal_pull_up(&mut self, on: bool) -> &mut Self;
 
     fn internal_pull_down(&mut self, on: bool) -> &mut Self;
+
+    /// Keep the output driving its current level through a software or
+    /// watchdog reset
+    ///
+    /// While enabled the pad is held, i.e. its configuration and level can't
+    /// be changed, so release the hold with `persist_through_reset(false)` after
+    /// the pin was set up again on the next boot (see
+    /// [`reset::get_reset_reason`](crate::reset::get_reset_reason)). A power-on
+    /// reset always releases the hold.
+    ///
+    /// All output capable pins can persist, via the RTC pad hold for the RTC
+    /// pads (GPIO0 - GPIO5 on the ESP32-C2/C3, GPIO0 - GPIO21 on the
+    /// ESP32-S2/S3) and via the digital pad hold for all others. Not available
+    /// on the ESP32, ESP32-C6 and ESP32-H2.
+    #[cfg(any(esp32c2, esp32c3, esp32s2, esp32s3))]
+    fn persist_through_reset(&mut self, on: bool) -> &mut Self;
+}
+
+/// Enable or disable the pad hold of the GPIO
+#[cfg(any(esp32c2, esp32c3, esp32s2, esp32s3))]
+fn set_pad_hold(gpio_num: u8, on: bool) {
+    #[cfg(any(esp32c2, esp32c3))]
+    const RTC_PADS: u8 = 6;
+    #[cfg(any(esp32s2, esp32s3))]
+    const RTC_PADS: u8 = 22;
+
+    let rtc_cntl = unsafe { &*crate::peripherals::RTC_CNTL::PTR };
+
+    if gpio_num < RTC_PADS {
+        let mask = 1 << gpio_num;
+        rtc_cntl.pad_hold.modify(|r, w| unsafe {
+            w.bits(if on { r.bits() | mask } else { r.bits() & !mask })
+        });
+    } else {
+        // the digital pads are numbered on from the last RTC pad on the S2/S3
+        #[cfg(any(esp32c2, esp32c3))]
+        let mask = 1 << gpio_num;
+        #[cfg(any(esp32s2, esp32s3))]
+        let mask = 1 << (gpio_num - RTC_PADS + 1);
+
+        rtc_cntl.dig_pad_hold.modify(|r, w| unsafe {
+            w.bits(if on { r.bits() | mask } else { r.bits() & !mask })
+        });
+    }
 }
 
 #[doc(hidden)]
@@ -354,6 +447,31 @@ impl BankGpioRegisterAccess for Bank1GpioRegisterAccess {
     }
 }
 
+/// A bank of up to 32 GPIOs sharing the same input/output registers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpioBank {
+    /// GPIO0 to GPIO31
+    Bank0,
+    /// GPIO32 and up
+    #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+    Bank1,
+}
+
+/// Read the input levels of all pins of a bank at once
+///
+/// Bit `n` is the level of GPIO `n` for [`GpioBank::Bank0`] and of GPIO
+/// `32 + n` for [`GpioBank::Bank1`]. All levels are sampled by a single
+/// register read, so e.g. a parallel bus is never captured mid-transition as
+/// it could be when reading the pins one by one. Bits of pins which aren't
+/// configured as inputs are undefined.
+pub fn read_bank(bank: GpioBank) -> u32 {
+    match bank {
+        GpioBank::Bank0 => Bank0GpioRegisterAccess::read_input(),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        GpioBank::Bank1 => Bank1GpioRegisterAccess::read_input(),
+    }
+}
+
 pub fn connect_low_to_peripheral(signal: InputSignal) {
     unsafe { &*GPIO::PTR }.func_in_sel_cfg[signal as usize].modify(|_, w| unsafe {
         w.sel()
@@ -376,6 +494,84 @@ pub fn connect_high_to_peripheral(signal: InputSignal) {
     });
 }
 
+/// Detach a pin from any peripheral and leave it floating (high-Z).
+pub(crate) fn release_pin(gpio_num: u8) {
+    let gpio = unsafe { &*GPIO::PTR };
+
+    // disable the output driver before touching the routing
+    if gpio_num < 32 {
+        Bank0GpioRegisterAccess::write_out_en_clear(1 << gpio_num);
+    } else {
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Bank1GpioRegisterAccess::write_out_en_clear(1 << (gpio_num % 32));
+    }
+
+    gpio.func_out_sel_cfg[gpio_num as usize]
+        .modify(|_, w| unsafe { w.out_sel().bits(OutputSignal::GPIO as OutputSignalType) });
+    gpio.pin[gpio_num as usize].modify(|_, w| w.pad_driver().clear_bit());
+
+    get_io_mux_reg(gpio_num).modify(|_, w| unsafe {
+        w.mcu_sel()
+            .bits(GPIO_FUNCTION as u8)
+            .fun_wpu()
+            .clear_bit()
+            .fun_wpd()
+            .clear_bit()
+    });
+}
+
+/// Route a peripheral output signal to a pin through the GPIO matrix.
+pub(crate) fn connect_output_signal(gpio_num: u8, signal: OutputSignal, open_drain: bool) {
+    let gpio = unsafe { &*GPIO::PTR };
+
+    if gpio_num < 32 {
+        Bank0GpioRegisterAccess::write_out_en_set(1 << gpio_num);
+    } else {
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Bank1GpioRegisterAccess::write_out_en_set(1 << (gpio_num % 32));
+    }
+    gpio.pin[gpio_num as usize].modify(|_, w| w.pad_driver().bit(open_drain));
+
+    get_io_mux_reg(gpio_num).modify(|_, w| unsafe {
+        w.mcu_sel()
+            .bits(GPIO_FUNCTION as u8)
+            .fun_ie()
+            .bit(open_drain)
+            .fun_wpd()
+            .clear_bit()
+            .fun_wpu()
+            .clear_bit()
+            .fun_drv()
+            .bits(DriveStrength::I20mA as u8)
+            .slp_sel()
+            .clear_bit()
+    });
+
+    gpio.func_out_sel_cfg[gpio_num as usize].modify(|_, w| unsafe {
+        w.out_sel()
+            .bits(signal as OutputSignalType)
+            .inv_sel()
+            .clear_bit()
+            .oen_sel()
+            .clear_bit()
+            .oen_inv_sel()
+            .clear_bit()
+    });
+}
+
+/// Enable the input buffer of a pin, e.g. to read back what it outputs
+pub(crate) fn enable_input_buffer(gpio_num: u8) {
+    get_io_mux_reg(gpio_num).modify(|_, w| w.fun_ie().set_bit());
+}
+
+/// Whether the output driver of a pin is configured as open drain
+pub(crate) fn is_open_drain(gpio_num: u8) -> bool {
+    unsafe { &*GPIO::PTR }.pin[gpio_num as usize]
+        .read()
+        .pad_driver()
+        .bit_is_set()
+}
+
 #[doc(hidden)]
 pub trait PinType {}
 
@@ -559,15 +755,7 @@ where
             {
                 if let Some(input_signal) = input_signal {
                     if *input_signal == signal {
-                        res = match i {
-                            0 => AlternateFunction::Function0,
-                            1 => AlternateFunction::Function1,
-                            2 => AlternateFunction::Function2,
-                            3 => AlternateFunction::Function3,
-                            4 => AlternateFunction::Function4,
-                            5 => AlternateFunction::Function5,
-                            _ => unreachable!(),
-                        };
+                        res = AlternateFunction::from_index(i);
                         break;
                     }
                 }
@@ -597,6 +785,17 @@ where
         unsafe { &*GPIO::PTR }.func_in_sel_cfg[signal as usize].modify(|_, w| w.sel().clear_bit());
         self
     }
+
+    fn set_interrupt_type(&mut self, event: Event) -> &mut Self {
+        let pin = &unsafe { &*GPIO::PTR }.pin[GPIONUM as usize];
+        let int_ena = pin.read().int_ena().bits();
+
+        pin.modify(|_, w| unsafe { w.int_ena().bits(0).int_type().bits(event as u8) });
+        <Self as GpioProperties>::Bank::write_interrupt_status_clear(1 << (GPIONUM % 32));
+        pin.modify(|_, w| unsafe { w.int_ena().bits(int_ena) });
+
+        self
+    }
 }
 
 impl<MODE, const GPIONUM: u8> Pin for GpioPin<MODE, GPIONUM>
@@ -944,6 +1143,27 @@ where
         GpioPin { _mode: PhantomData }
     }
 
+    /// Turn the pin into a push-pull output which initially drives the level
+    /// currently present on the pad.
+    ///
+    /// The output latch is loaded before the output driver is enabled, so
+    /// there is no glitch on the pin. This is useful for
+    /// [strapping pins](`STRAPPING_PINS`) which are held at a certain level by
+    /// external resistors.
+    pub fn into_output_keep_boot_state(self) -> GpioPin<Output<PushPull>, GPIONUM> {
+        get_io_mux_reg(GPIONUM).modify(|_, w| w.fun_ie().set_bit());
+
+        let mask = 1 << (GPIONUM % 32);
+        if <Self as GpioProperties>::Bank::read_input() & mask != 0 {
+            <Self as GpioProperties>::Bank::write_output_set(mask);
+        } else {
+            <Self as GpioProperties>::Bank::write_output_clear(mask);
+        }
+
+        self.init_output(GPIO_FUNCTION, false);
+        GpioPin { _mode: PhantomData }
+    }
+
     pub fn into_alternate_1(self) -> GpioPin<Alternate<AF1>, GPIONUM> {
         self.init_output(AlternateFunction::Function1, false);
         GpioPin { _mode: PhantomData }
@@ -953,6 +1173,52 @@ where
         self.init_output(AlternateFunction::Function2, false);
         GpioPin { _mode: PhantomData }
     }
+
+    /// Route the pin directly to a peripheral through the IO MUX, bypassing
+    /// the GPIO matrix (and the latency it adds).
+    ///
+    /// Only some signals of some peripherals (e.g. SPI, UART0, JTAG) have a
+    /// fixed IO MUX pin. Which signal a function carries on which pin is chip
+    /// specific: the functions of each pin are listed on its `GpioN` type
+    /// alias (e.g. [`Gpio0`]) in the chip's `gpio` module, see also the "IO MUX
+    /// Pin Functions" table in the chip's technical reference manual.
+    /// [`GpioPin::alternate_function_for_output`] and
+    /// [`GpioPin::alternate_function_for_input`] look up the function for a
+    /// signal on this pin.
+    ///
+    /// The pad's input buffer is enabled as well, so bidirectional functions
+    /// work.
+    pub fn into_alternate(
+        self,
+        function: AlternateFunction,
+    ) -> GpioPin<Alternate<AFAny>, GPIONUM> {
+        self.init_output(function, false);
+        get_io_mux_reg(GPIONUM).modify(|_, w| w.fun_ie().set_bit());
+        GpioPin { _mode: PhantomData }
+    }
+}
+
+impl<MODE, const GPIONUM: u8> GpioPin<MODE, GPIONUM>
+where
+    Self: GpioProperties,
+{
+    /// The IO MUX function which connects `signal` directly to this pin, if
+    /// any
+    pub fn alternate_function_for_output(&self, signal: OutputSignal) -> Option<AlternateFunction> {
+        <Self as GpioProperties>::Signals::output_signals()
+            .iter()
+            .position(|output_signal| *output_signal == Some(signal))
+            .map(AlternateFunction::from_index)
+    }
+
+    /// The IO MUX function which connects this pin directly to `signal`, if
+    /// any
+    pub fn alternate_function_for_input(&self, signal: InputSignal) -> Option<AlternateFunction> {
+        <Self as GpioProperties>::Signals::input_signals()
+            .iter()
+            .position(|input_signal| *input_signal == Some(signal))
+            .map(AlternateFunction::from_index)
+    }
 }
 
 impl<MODE, const GPIONUM: u8> OutputPin for GpioPin<MODE, GPIONUM>
@@ -1030,15 +1296,7 @@ where
             {
                 if let Some(output_signal) = output_signal {
                     if *output_signal == signal {
-                        res = match i {
-                            0 => AlternateFunction::Function0,
-                            1 => AlternateFunction::Function1,
-                            2 => AlternateFunction::Function2,
-                            3 => AlternateFunction::Function3,
-                            4 => AlternateFunction::Function4,
-                            5 => AlternateFunction::Function5,
-                            _ => unreachable!(),
-                        };
+                        res = AlternateFunction::from_index(i);
                         break;
                     }
                 }
@@ -1082,6 +1340,12 @@ where
         get_io_mux_reg(GPIONUM).modify(|_, w| w.fun_wpd().bit(on));
         self
     }
+
+    #[cfg(any(esp32c2, esp32c3, esp32s2, esp32s3))]
+    fn persist_through_reset(&mut self, on: bool) -> &mut Self {
+        set_pad_hold(GPIONUM, on);
+        self
+    }
 }
 
 impl<MODE, const GPIONUM: u8> GpioPin<MODE, GPIONUM>
@@ -1089,7 +1353,13 @@ where
     Self: GpioProperties,
     <Self as GpioProperties>::PinType: IsAnalogPin,
 {
+    /// Configure the pin for analog use, e.g. by the ADC
+    ///
+    /// The pin is detached from the GPIO matrix, its output and input buffers
+    /// are disabled and the pull resistors of both the digital and (if
+    /// present) the RTC pad are switched off, so they don't skew a reading.
     pub fn into_analog(self) -> GpioPin<Analog, GPIONUM> {
+        release_pin(GPIONUM);
         crate::soc::gpio::internal_into_analog(GPIONUM);
 
         GpioPin { _mode: PhantomData }
@@ -1331,6 +1601,24 @@ macro_rules! gpio {
             }
 
             $(
+                #[doc = concat!("GPIO", stringify!($gpionum))]
+                $(
+                    #[doc = ""]
+                    #[doc = "IO MUX functions of this pin, see [`GpioPin::into_alternate`]:"]
+                    #[doc = ""]
+                    $(
+                        #[doc = concat!(
+                            "- input `", stringify!($af_input_signal),
+                            "`: `AlternateFunction::Function", stringify!($af_input_num), "`"
+                        )]
+                    )*
+                    $(
+                        #[doc = concat!(
+                            "- output `", stringify!($af_output_signal),
+                            "`: `AlternateFunction::Function", stringify!($af_output_num), "`"
+                        )]
+                    )*
+                )?
                 pub type [<Gpio $gpionum >]<MODE> = GpioPin<MODE, $gpionum>;
             )+
 
@@ -1371,8 +1659,25 @@ macro_rules! gpio {
                     }
                 }
             }
+
+            impl<MODE> crate::gpio::FromAnyPin<MODE> for [<Gpio $gpionum >]<MODE> {
+                fn from_any_pin(pin: AnyPin<MODE>) -> Result<Self, AnyPin<MODE>> {
+                    match pin.inner {
+                        ErasedPin::[<Gpio $gpionum >](gpio) => Ok(gpio),
+                        inner => Err(AnyPin { inner }),
+                    }
+                }
+            }
             )+
 
+            impl<MODE> AnyPin<MODE> {
+                /// Recover the concrete pin type, giving back the type-erased
+                /// pin if it's a different GPIO
+                pub fn downcast<P: crate::gpio::FromAnyPin<MODE>>(self) -> Result<P, Self> {
+                    P::from_any_pin(self)
+                }
+            }
+
             procmacros::make_gpio_enum_dispatch_macro!(
                 handle_gpio_output
                 { InputOutputAnalog, InputOutput, }
@@ -1396,6 +1701,13 @@ macro_rules! gpio {
     };
 }
 
+/// Concrete pin types an [`AnyPin`] can be downcast to, see `AnyPin::downcast`
+pub trait FromAnyPin<MODE>: Sized {
+    /// Recover the concrete pin, or give back the type-erased pin if it's a
+    /// different GPIO
+    fn from_any_pin(pin: AnyPin<MODE>) -> Result<Self, AnyPin<MODE>>;
+}
+
 // Following code enables `into_analog`
 
 #[doc(hidden)]
@@ -1618,6 +1930,18 @@ mod asynch {
         }
     }
 
+    impl<'a, P> Drop for PinFuture<'a, P>
+    where
+        P: crate::gpio::Pin + embedded_hal_1::digital::ErrorType,
+    {
+        fn drop(&mut self) {
+            // don't leave the interrupt enabled if the future is cancelled
+            if self.pin.is_listening() {
+                self.pin.unlisten();
+            }
+        }
+    }
+
     impl<'a, P> core::future::Future for PinFuture<'a, P>
     where
         P: crate::gpio::Pin + embedded_hal_1::digital::ErrorType,
@@ -1637,6 +1961,94 @@ mod asynch {
         }
     }
 
+    /// The event to wait for didn't happen in time
+    #[cfg(feature = "embassy")]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct TimedOut;
+
+    #[cfg(feature = "embassy")]
+    impl<MODE, const GPIONUM: u8> GpioPin<Input<MODE>, GPIONUM>
+    where
+        Self: GpioProperties,
+        <Self as GpioProperties>::PinType: IsInputPin,
+    {
+        /// Wait for `event` (usually an edge) on the pin, giving up after
+        /// `timeout`.
+        ///
+        /// On a timeout the pin interrupt is disabled again, dropping the
+        /// future tears down both the pin interrupt and the timer.
+        pub async fn wait_for_edge_timeout(
+            &mut self,
+            event: Event,
+            timeout: embassy_time::Duration,
+        ) -> Result<(), TimedOut> {
+            match embassy_futures::select::select(
+                PinFuture::new(self, event),
+                embassy_time::Timer::after(timeout),
+            )
+            .await
+            {
+                embassy_futures::select::Either::First(_) => Ok(()),
+                embassy_futures::select::Either::Second(_) => Err(TimedOut),
+            }
+        }
+    }
+
+    /// Software debouncing of a (button) input
+    ///
+    /// After the edge of a press the input level is checked again once the
+    /// debounce time passed, a press is only reported if the level is still
+    /// the same. This helps with contacts bouncing longer than the hardware
+    /// glitch filter can suppress.
+    #[cfg(feature = "embassy")]
+    pub struct DebouncedInput<P> {
+        pin: P,
+        active_low: bool,
+    }
+
+    #[cfg(feature = "embassy")]
+    impl<P> DebouncedInput<P>
+    where
+        P: Wait + embedded_hal_1::digital::InputPin,
+    {
+        /// Wrap an input, `active_low` tells whether a press pulls the input
+        /// low (e.g. a button to GND with a pull-up) or high
+        pub fn new(pin: P, active_low: bool) -> Self {
+            Self { pin, active_low }
+        }
+
+        /// Release the wrapped input
+        pub fn free(self) -> P {
+            self.pin
+        }
+
+        /// Wait until the input gets pressed and stays pressed for at least
+        /// `debounce`
+        pub async fn wait_for_press(
+            &mut self,
+            debounce: embassy_time::Duration,
+        ) -> Result<(), P::Error> {
+            loop {
+                if self.active_low {
+                    self.pin.wait_for_falling_edge().await?;
+                } else {
+                    self.pin.wait_for_rising_edge().await?;
+                }
+
+                embassy_time::Timer::after(debounce).await;
+
+                let pressed = if self.active_low {
+                    self.pin.is_low()?
+                } else {
+                    self.pin.is_high()?
+                };
+                if pressed {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
     pub(crate) fn set_int_enable(
         gpio_num: u8,
         int_ena: u8,
@@ -1,13 +1,19 @@
 //! I2C Driver
 //!
 //! Supports multiple I2C peripheral instances
+//!
+//! The I2C peripherals can't be served by DMA (see
+//! [`DmaPeripheral`](crate::dma::DmaPeripheral)), all data goes through the
+//! FIFO, so there are no DMA transfer methods.
 
-use fugit::HertzU32;
+use fugit::{HertzU32, MicrosDurationU64};
 
+#[cfg(feature = "async")]
+pub use self::asynch::{I2cDevice, SharedI2c};
 use crate::{
     clock::Clocks,
-    gpio::{InputPin, InputSignal, OutputPin, OutputSignal},
-    peripheral::{Peripheral, PeripheralRef},
+    gpio::{read_bank, GpioBank, InputPin, InputSignal, OutputPin, OutputSignal},
+    peripheral::{DriverGuard, Peripheral, PeripheralRef},
     peripherals::i2c0::{RegisterBlock, COMD},
     system::PeripheralClockControl,
 };
@@ -20,6 +26,36 @@ cfg_if::cfg_if! {
     }
 }
 
+cfg_if::cfg_if! {
+    if #[cfg(esp32c2)] {
+        const I2C_FIFO_SIZE: usize = 16;
+    } else {
+        const I2C_FIFO_SIZE: usize = 32;
+    }
+}
+
+/// Default TX FIFO watermark, see [`I2C::set_fifo_threshold`]
+const DEFAULT_TX_FIFO_THRESHOLD: u8 = 8;
+/// Default RX FIFO watermark, see [`I2C::set_fifo_threshold`]
+const DEFAULT_RX_FIFO_THRESHOLD: u8 = 1;
+
+/// Default bus timeout in SCL periods, see [`I2C::set_timeout`]
+const DEFAULT_TIMEOUT: u32 = 10;
+
+/// Highest supported bus frequency (fast-mode plus)
+const MAX_BUS_FREQUENCY: u32 = 1_000_000;
+
+/// Maximum number of bytes a single READ or WRITE command can transfer
+const I2C_CMD_MAX_LENGTH: usize = 255;
+
+cfg_if::cfg_if! {
+    if #[cfg(any(esp32, esp32s2))] {
+        const I2C_COMMAND_LIST_LEN: usize = 16;
+    } else {
+        const I2C_COMMAND_LIST_LEN: usize = 8;
+    }
+}
+
 /// I2C-specific transmission errors
 #[derive(Debug)]
 pub enum Error {
@@ -29,6 +65,18 @@ pub enum Error {
     ArbitrationLost,
     ExecIncomplete,
     CommandNrExceeded,
+    /// The data supplied doesn't match the lengths of the READ/WRITE commands
+    DataLengthMismatch,
+    /// The bus frequency is above 1MHz, or can't be reached from the source
+    /// clock
+    UnsupportedFrequency,
+    /// The spike filter threshold is out of range or too long for the bus
+    /// frequency, see [`I2C::set_filter`]
+    InvalidFilter,
+    /// A FIFO threshold is out of range, see [`I2C::set_fifo_threshold`]
+    InvalidFifoThreshold,
+    /// A READ or WRITE command of zero bytes was passed to [`I2C::exec`]
+    EmptyCommand,
 }
 
 #[cfg(feature = "eh1")]
@@ -44,9 +92,49 @@ impl embedded_hal_1::i2c::Error for Error {
     }
 }
 
+bitflags::bitflags! {
+    /// I2C interrupt conditions, see [`I2C::listen`]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct I2cInterrupt: u32 {
+        /// A STOP condition was detected on the bus
+        const TransComplete   = 1 << 0;
+        /// The END command of the command list was executed
+        const EndDetect       = 1 << 1;
+        /// The master lost arbitration of the bus
+        const ArbitrationLost = 1 << 2;
+        /// SCL was held low for longer than the timeout
+        const TimeOut         = 1 << 3;
+    }
+}
+
+/// Source clock of the I2C peripheral, see [`I2C::set_source_clock`]
+///
+/// Unlike the APB clock, none of these sources change with the CPU frequency.
+/// The PLL can't clock the I2C peripheral on any of these chips.
+#[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum I2cClockSource {
+    /// The external crystal, this is the default
+    Xtal,
+    /// The internal RC_FAST (8M) oscillator, its frequency isn't calibrated
+    RcFast,
+}
+
+/// Nominal frequency of the RC_FAST oscillator
+#[cfg(any(esp32c2, esp32c3, esp32c6, esp32s3))]
+const RC_FAST_FREQ: HertzU32 = HertzU32::kHz(17_500);
+#[cfg(esp32h2)]
+const RC_FAST_FREQ: HertzU32 = HertzU32::kHz(8_500);
+
 /// A generic I2C Command
-enum Command {
+///
+/// Mirrors an entry of the hardware command list, see [`I2C::exec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Generate a START condition, or a repeated START if the bus is already
+    /// owned by this master
     Start,
+    /// Generate a STOP condition
     Stop,
     Write {
         /// This bit is to set an expected ACK value for the transmitter.
@@ -57,8 +145,14 @@ enum Command {
         /// while the minimum is 1.
         length: u8,
     },
+    /// Read `length` bytes
+    ///
+    /// The master sends `ack_value` after each of the bytes. The final byte of
+    /// a read has to be NACKed, so a read of N bytes is usually split into a
+    /// `Read` of N - 1 bytes with [`Ack::Ack`] followed by a `Read` of 1 byte
+    /// with [`Ack::Nack`], like [`I2C::read`] does.
     Read {
-        /// Indicates whether the receiver will send an ACK after this byte has
+        /// Indicates whether the receiver will send an ACK after each byte has
         /// been received.
         ack_value: Ack,
         /// Length of data (in bytes) to be read. The maximum length is 255,
@@ -129,8 +223,9 @@ enum OperationType {
     Read  = 1,
 }
 
-#[derive(Eq, PartialEq, Copy, Clone)]
-enum Ack {
+/// ACK level of a transmitted or received byte
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Ack {
     Ack,
     Nack,
 }
@@ -149,36 +244,80 @@ enum Opcode {
     Write  = 1,
     Read   = 2,
     Stop   = 3,
+    End    = 4,
+}
+
+/// The input level of the GPIO `number`
+fn pin_level(number: u8) -> bool {
+    #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2))]
+    let bank = GpioBank::Bank0;
+    #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+    let bank = if number < 32 {
+        GpioBank::Bank0
+    } else {
+        GpioBank::Bank1
+    };
+
+    read_bank(bank) & (1 << (number % 32)) != 0
 }
 
 /// I2C peripheral container (I2C)
-pub struct I2C<'d, T> {
+pub struct I2C<'d, T, SDA, SCL> {
     peripheral: PeripheralRef<'d, T>,
+    sda_pin: PeripheralRef<'d, SDA>,
+    scl_pin: PeripheralRef<'d, SCL>,
+    sda: u8,
+    scl: u8,
+    max_attempts: u8,
+    timeout: u32,
+    frequency: HertzU32,
+    source_clock: HertzU32,
+    /// Spike filter set via [`I2C::set_filter`], `None` if it's chosen for
+    /// the bus frequency
+    filter: Option<Option<u8>>,
+    #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+    xtal_clock: HertzU32,
+    /// Set via [`I2C::enable_transaction_timing`]
+    transaction_timing: bool,
+    last_transaction_cycles: Option<u64>,
+    _guard: DriverGuard,
+}
+
+impl<T, SDA, SCL> crate::interrupt::InterruptClearable for I2C<'_, T, SDA, SCL>
+where
+    T: Instance,
+{
+    fn clear_interrupts(&mut self) {
+        self.peripheral
+            .register_block()
+            .int_clr
+            .write(|w| unsafe { w.bits(u32::MAX) });
+    }
 }
 
-impl<T> embedded_hal::blocking::i2c::Read for I2C<'_, T>
+impl<T, SDA, SCL> embedded_hal::blocking::i2c::Read for I2C<'_, T, SDA, SCL>
 where
     T: Instance,
 {
     type Error = Error;
 
     fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
-        self.peripheral.master_read(address, buffer)
+        I2C::read(self, address, buffer)
     }
 }
 
-impl<T> embedded_hal::blocking::i2c::Write for I2C<'_, T>
+impl<T, SDA, SCL> embedded_hal::blocking::i2c::Write for I2C<'_, T, SDA, SCL>
 where
     T: Instance,
 {
     type Error = Error;
 
     fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
-        self.peripheral.master_write(addr, bytes)
+        I2C::write(self, addr, bytes)
     }
 }
 
-impl<T> embedded_hal::blocking::i2c::WriteRead for I2C<'_, T>
+impl<T, SDA, SCL> embedded_hal::blocking::i2c::WriteRead for I2C<'_, T, SDA, SCL>
 where
     T: Instance,
 {
@@ -190,26 +329,26 @@ where
         bytes: &[u8],
         buffer: &mut [u8],
     ) -> Result<(), Self::Error> {
-        self.peripheral.master_write_read(address, bytes, buffer)
+        I2C::write_read(self, address, bytes, buffer)
     }
 }
 
 #[cfg(feature = "eh1")]
-impl<T> embedded_hal_1::i2c::ErrorType for I2C<'_, T> {
+impl<T, SDA, SCL> embedded_hal_1::i2c::ErrorType for I2C<'_, T, SDA, SCL> {
     type Error = Error;
 }
 
 #[cfg(feature = "eh1")]
-impl<T> embedded_hal_1::i2c::I2c for I2C<'_, T>
+impl<T, SDA, SCL> embedded_hal_1::i2c::I2c for I2C<'_, T, SDA, SCL>
 where
     T: Instance,
 {
     fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
-        self.peripheral.master_read(address, buffer)
+        I2C::read(self, address, buffer)
     }
 
     fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
-        self.peripheral.master_write(address, bytes)
+        I2C::write(self, address, bytes)
     }
 
     fn write_read(
@@ -218,7 +357,7 @@ where
         bytes: &[u8],
         buffer: &mut [u8],
     ) -> Result<(), Self::Error> {
-        self.peripheral.master_write_read(address, bytes, buffer)
+        I2C::write_read(self, address, bytes, buffer)
     }
 
     fn transaction<'a>(
@@ -230,44 +369,762 @@ where
     }
 }
 
-impl<'d, T> I2C<'d, T>
+/// Builder for [`I2C`] with all optional settings, created by
+/// [`I2C::builder`]
+///
+/// ```rust,ignore
+/// let i2c = I2C::builder(peripherals.I2C0)
+///     .sda(io.pins.gpio1)
+///     .scl(io.pins.gpio2)
+///     .frequency(400u32.kHz())
+///     .timeout(20)
+///     .internal_pullups(false)
+///     .build(&clocks, &mut system.peripheral_clock_control)?;
+/// ```
+///
+/// The bus frequency defaults to 100kHz, the other settings to the defaults
+/// of [`I2C::new`].
+pub struct I2cBuilder<'d, T, SDA = (), SCL = ()> {
+    i2c: PeripheralRef<'d, T>,
+    sda: SDA,
+    scl: SCL,
+    frequency: HertzU32,
+    timeout: u32,
+    internal_pullups: bool,
+    max_attempts: u8,
+    #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+    source_clock: I2cClockSource,
+}
+
+impl<'d, T, SDA, SCL> I2cBuilder<'d, T, SDA, SCL>
+where
+    T: Instance,
+{
+    /// The SDA pin
+    pub fn sda<P: OutputPin + InputPin>(
+        self,
+        sda: impl Peripheral<P = P> + 'd,
+    ) -> I2cBuilder<'d, T, PeripheralRef<'d, P>, SCL> {
+        crate::into_ref!(sda);
+
+        I2cBuilder {
+            i2c: self.i2c,
+            sda,
+            scl: self.scl,
+            frequency: self.frequency,
+            timeout: self.timeout,
+            internal_pullups: self.internal_pullups,
+            max_attempts: self.max_attempts,
+            #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+            source_clock: self.source_clock,
+        }
+    }
+
+    /// The SCL pin
+    pub fn scl<P: OutputPin + InputPin>(
+        self,
+        scl: impl Peripheral<P = P> + 'd,
+    ) -> I2cBuilder<'d, T, SDA, PeripheralRef<'d, P>> {
+        crate::into_ref!(scl);
+
+        I2cBuilder {
+            i2c: self.i2c,
+            sda: self.sda,
+            scl,
+            frequency: self.frequency,
+            timeout: self.timeout,
+            internal_pullups: self.internal_pullups,
+            max_attempts: self.max_attempts,
+            #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+            source_clock: self.source_clock,
+        }
+    }
+
+    /// The bus frequency, see [`I2C::set_frequency`]
+    pub fn frequency(mut self, frequency: HertzU32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// The bus timeout in SCL periods, see [`I2C::set_timeout`]
+    pub fn timeout(mut self, bus_cycles: u32) -> Self {
+        self.timeout = bus_cycles;
+        self
+    }
+
+    /// Enable the internal pull-ups of SDA and SCL (the default)
+    ///
+    /// They are only sufficient for short buses at low frequencies, disable
+    /// them when external pull-ups are fitted.
+    pub fn internal_pullups(mut self, enable: bool) -> Self {
+        self.internal_pullups = enable;
+        self
+    }
+
+    /// Number of attempts of transactions losing arbitration, see
+    /// [`I2C::with_retry`]
+    pub fn retry(mut self, max_attempts: u8) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// The source clock of the peripheral, see [`I2C::set_source_clock`]
+    #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+    pub fn source_clock(mut self, source: I2cClockSource) -> Self {
+        self.source_clock = source;
+        self
+    }
+}
+
+impl<'d, T, SDA, SCL> I2cBuilder<'d, T, PeripheralRef<'d, SDA>, PeripheralRef<'d, SCL>>
+where
+    T: Instance,
+    SDA: OutputPin + InputPin,
+    SCL: OutputPin + InputPin,
+{
+    /// Create the I2C instance
+    ///
+    /// Fails with [`Error::UnsupportedFrequency`] if the bus frequency can't be
+    /// reached from the source clock.
+    pub fn build(
+        self,
+        clocks: &Clocks,
+        peripheral_clock_control: &mut PeripheralClockControl,
+    ) -> Result<I2C<'d, T, SDA, SCL>, Error> {
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3)))]
+        let source_clock = clocks.i2c_clock;
+        #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+        let source_clock = match self.source_clock {
+            I2cClockSource::Xtal => clocks.xtal_clock,
+            I2cClockSource::RcFast => RC_FAST_FREQ,
+        };
+        validate_frequency(source_clock, self.frequency)?;
+
+        let mut i2c = I2C::new_internal(
+            self.i2c,
+            self.sda,
+            self.scl,
+            self.frequency,
+            source_clock,
+            self.internal_pullups,
+            peripheral_clock_control,
+            clocks,
+        )
+        .with_retry(self.max_attempts);
+        // the bus timing is already calculated for the selected source clock
+        #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+        if self.source_clock != I2cClockSource::Xtal {
+            i2c.peripheral.set_source_clock_hw(self.source_clock);
+        }
+        i2c.set_timeout(self.timeout);
+
+        Ok(i2c)
+    }
+}
+
+impl<'d, T> I2C<'d, T, (), ()>
 where
     T: Instance,
 {
+    /// Start building a new I2C instance with all optional settings, see
+    /// [`I2cBuilder`]
+    pub fn builder(i2c: impl Peripheral<P = T> + 'd) -> I2cBuilder<'d, T> {
+        crate::into_ref!(i2c);
+
+        I2cBuilder {
+            i2c,
+            sda: (),
+            scl: (),
+            frequency: HertzU32::kHz(100),
+            timeout: DEFAULT_TIMEOUT,
+            internal_pullups: true,
+            max_attempts: 1,
+            #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+            source_clock: I2cClockSource::Xtal,
+        }
+    }
+}
+
+impl<'d, T, SDA, SCL> I2C<'d, T, SDA, SCL>
+where
+    T: Instance,
+{
+    /// Size of the hardware TX/RX FIFO in bytes.
+    ///
+    /// On all chips but the ESP32 and ESP32-S2 the FIFO is refilled/drained
+    /// while the transfer is running and transfers longer than 255 bytes are
+    /// split into multiple READ/WRITE commands. The ESP32 and ESP32-S2 can't
+    /// refill the FIFO while a command list executes, there the blocking
+    /// [`I2C::read`]/[`I2C::write`] send the data in segments of one FIFO
+    /// each: an END command pauses the transfer (holding SCL low, so it's
+    /// still a single transfer on the bus) until the next segment is loaded.
+    /// The async transfers and [`I2C::exec`] are limited to one FIFO there
+    /// (including the address byte for writes) and return
+    /// [`Error::ExceedingFifo`] otherwise.
+    pub const FIFO_SIZE: usize = I2C_FIFO_SIZE;
+
+    /// Number of entries in the hardware command list, i.e. the maximum
+    /// number of [`Command`]s a single [`I2C::exec`] can execute.
+    pub const COMMAND_LIST_LEN: usize = I2C_COMMAND_LIST_LEN;
+
     /// Create a new I2C instance
     /// This will enable the peripheral but the peripheral won't get
     /// automatically disabled when this gets dropped.
-    pub fn new<SDA: OutputPin + InputPin, SCL: OutputPin + InputPin>(
+    ///
+    /// Bus frequencies up to 1MHz (fast-mode plus) are supported, see
+    /// [`I2C::set_frequency`]. This doesn't validate `frequency`: one the bus
+    /// timing can't be set up for from the source clock is silently clamped to
+    /// the closest supported one, so the bus may run at a different frequency
+    /// than requested. To have it validated, create the driver with
+    /// [`I2C::builder`] or set the frequency afterwards with
+    /// [`I2C::set_frequency`], both fail with [`Error::UnsupportedFrequency`]
+    /// instead.
+    pub fn new(
         i2c: impl Peripheral<P = T> + 'd,
         sda: impl Peripheral<P = SDA> + 'd,
         scl: impl Peripheral<P = SCL> + 'd,
         frequency: HertzU32,
         peripheral_clock_control: &mut PeripheralClockControl,
         clocks: &Clocks,
-    ) -> Self {
+    ) -> Self
+    where
+        SDA: OutputPin + InputPin,
+        SCL: OutputPin + InputPin,
+    {
         crate::into_ref!(i2c, sda, scl);
-        enable_peripheral(&i2c, peripheral_clock_control);
+        let frequency = clamp_frequency(clocks.i2c_clock, frequency);
+
+        Self::new_internal(
+            i2c,
+            sda,
+            scl,
+            frequency,
+            clocks.i2c_clock,
+            true,
+            peripheral_clock_control,
+            clocks,
+        )
+    }
 
-        let mut i2c = I2C { peripheral: i2c };
+    fn new_internal(
+        i2c: PeripheralRef<'d, T>,
+        mut sda: PeripheralRef<'d, SDA>,
+        mut scl: PeripheralRef<'d, SCL>,
+        frequency: HertzU32,
+        source_clock: HertzU32,
+        internal_pullups: bool,
+        peripheral_clock_control: &mut PeripheralClockControl,
+        clocks: &Clocks,
+    ) -> Self
+    where
+        SDA: OutputPin + InputPin,
+        SCL: OutputPin + InputPin,
+    {
+        enable_peripheral(&i2c, peripheral_clock_control);
 
         // initialize SCL first to not confuse some devices like MPU6050
         scl.set_to_open_drain_output()
             .enable_input(true)
-            .internal_pull_up(true)
-            .connect_peripheral_to_output(i2c.peripheral.scl_output_signal())
-            .connect_input_to_peripheral(i2c.peripheral.scl_input_signal());
+            .internal_pull_up(internal_pullups)
+            .connect_peripheral_to_output(i2c.scl_output_signal())
+            .connect_input_to_peripheral(i2c.scl_input_signal());
 
         sda.set_to_open_drain_output()
             .enable_input(true)
-            .internal_pull_up(true)
-            .connect_peripheral_to_output(i2c.peripheral.sda_output_signal())
-            .connect_input_to_peripheral(i2c.peripheral.sda_input_signal());
+            .internal_pull_up(internal_pullups)
+            .connect_peripheral_to_output(i2c.sda_output_signal())
+            .connect_input_to_peripheral(i2c.sda_input_signal());
+
+        let _guard = DriverGuard::new(i2c.register_block() as *const _ as *const (), "I2C");
+        let mut i2c = I2C {
+            peripheral: i2c,
+            sda: sda.number(),
+            scl: scl.number(),
+            sda_pin: sda,
+            scl_pin: scl,
+            max_attempts: 1,
+            timeout: DEFAULT_TIMEOUT,
+            frequency,
+            source_clock,
+            filter: None,
+            #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+            xtal_clock: clocks.xtal_clock,
+            transaction_timing: false,
+            last_transaction_cycles: None,
+            _guard,
+        };
 
-        i2c.peripheral.setup(frequency, clocks);
+        i2c.peripheral.setup(frequency, source_clock);
 
         i2c
     }
 
+    /// Enable the given interrupt conditions.
+    ///
+    /// The peripheral interrupt (e.g. `I2C_EXT0`) is raised as long as any of
+    /// the enabled conditions is pending. With the `async` feature enabled the
+    /// driver manages [`I2cInterrupt::TransComplete`] and
+    /// [`I2cInterrupt::EndDetect`] itself.
+    pub fn listen(&mut self, interrupts: I2cInterrupt) {
+        self.peripheral
+            .register_block()
+            .int_ena
+            .modify(|_, w| {
+                if interrupts.contains(I2cInterrupt::TransComplete) {
+                    w.trans_complete_int_ena().set_bit();
+                }
+                if interrupts.contains(I2cInterrupt::EndDetect) {
+                    w.end_detect_int_ena().set_bit();
+                }
+                if interrupts.contains(I2cInterrupt::ArbitrationLost) {
+                    w.arbitration_lost_int_ena().set_bit();
+                }
+                if interrupts.contains(I2cInterrupt::TimeOut) {
+                    w.time_out_int_ena().set_bit();
+                }
+                w
+            });
+    }
+
+    /// Disable the given interrupt conditions
+    pub fn unlisten(&mut self, interrupts: I2cInterrupt) {
+        self.peripheral
+            .register_block()
+            .int_ena
+            .modify(|_, w| {
+                if interrupts.contains(I2cInterrupt::TransComplete) {
+                    w.trans_complete_int_ena().clear_bit();
+                }
+                if interrupts.contains(I2cInterrupt::EndDetect) {
+                    w.end_detect_int_ena().clear_bit();
+                }
+                if interrupts.contains(I2cInterrupt::ArbitrationLost) {
+                    w.arbitration_lost_int_ena().clear_bit();
+                }
+                if interrupts.contains(I2cInterrupt::TimeOut) {
+                    w.time_out_int_ena().clear_bit();
+                }
+                w
+            });
+    }
+
+    /// The currently pending interrupt conditions, whether enabled or not
+    pub fn interrupts(&self) -> I2cInterrupt {
+        let int_raw = self.peripheral.register_block().int_raw.read();
+        let mut interrupts = I2cInterrupt::empty();
+
+        interrupts.set(
+            I2cInterrupt::TransComplete,
+            int_raw.trans_complete_int_raw().bit_is_set(),
+        );
+        interrupts.set(
+            I2cInterrupt::EndDetect,
+            int_raw.end_detect_int_raw().bit_is_set(),
+        );
+        interrupts.set(
+            I2cInterrupt::ArbitrationLost,
+            int_raw.arbitration_lost_int_raw().bit_is_set(),
+        );
+        interrupts.set(
+            I2cInterrupt::TimeOut,
+            int_raw.time_out_int_raw().bit_is_set(),
+        );
+
+        interrupts
+    }
+
+    /// Clear the given pending interrupt conditions
+    pub fn clear_interrupts(&mut self, interrupts: I2cInterrupt) {
+        self.peripheral.register_block().int_clr.write(|w| {
+            w.trans_complete_int_clr()
+                .bit(interrupts.contains(I2cInterrupt::TransComplete))
+                .end_detect_int_clr()
+                .bit(interrupts.contains(I2cInterrupt::EndDetect))
+                .arbitration_lost_int_clr()
+                .bit(interrupts.contains(I2cInterrupt::ArbitrationLost))
+                .time_out_int_clr()
+                .bit(interrupts.contains(I2cInterrupt::TimeOut))
+        });
+    }
+
+    /// Automatically retry a transaction which lost arbitration.
+    ///
+    /// On a multi-master bus losing arbitration is expected from time to time.
+    /// With `max_attempts` greater than 1 a blocking transaction failing with
+    /// [`Error::ArbitrationLost`] is restarted from scratch (including the
+    /// START condition) once the bus is idle again, up to `max_attempts` times
+    /// in total before the error is returned. The default is a single attempt.
+    /// If the bus doesn't become idle within the bus timeout (see
+    /// [`I2C::set_timeout`]) [`Error::TimeOut`] is returned instead.
+    ///
+    /// This doesn't apply to the async API and [`I2C::exec`].
+    pub fn with_retry(mut self, max_attempts: u8) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Change the bus frequency
+    ///
+    /// Up to 1MHz (fast-mode plus) is supported, the timing and the spike
+    /// filter are set up for the frequency (unless the filter was set via
+    /// [`I2C::set_filter`]). Fast-mode plus needs strong
+    /// external pull-ups (around 1kΩ, depending on the bus capacitance), the
+    /// internal pull-ups are much too weak for the rise time required at this
+    /// speed. Must not be called while a transaction is running.
+    ///
+    /// Fails with [`Error::UnsupportedFrequency`] if the bus timing can't be
+    /// set up for `frequency` from the source clock.
+    pub fn set_frequency(&mut self, frequency: HertzU32) -> Result<(), Error> {
+        validate_frequency(self.source_clock, frequency)?;
+        if let Some(cycles) = self.filter {
+            validate_filter(self.source_clock, frequency, cycles)?;
+        }
+
+        self.frequency = frequency;
+        self.peripheral
+            .configure_bus(self.source_clock, self.frequency, self.filter_cycles());
+        self.peripheral.set_timeout(self.source_clock, self.frequency, self.timeout);
+        self.peripheral.update_config();
+
+        Ok(())
+    }
+
+    /// Set the spike filter for SDA and SCL
+    ///
+    /// Pulses shorter than `cycles` source clock cycles (see
+    /// [`I2C::source_clock`]) are suppressed, `None` disables the filter. By
+    /// default a threshold suitable for the bus frequency is used, once set
+    /// here it's kept when the frequency or the source clock is changed.
+    /// Must not be called while a transaction is running.
+    ///
+    /// Fails with [`Error::InvalidFilter`] if `cycles` isn't in 1..=7 or
+    /// takes more than half of the SCL high time at the current bus
+    /// frequency.
+    pub fn set_filter(&mut self, cycles: Option<u8>) -> Result<(), Error> {
+        validate_filter(self.source_clock, self.frequency, cycles)?;
+
+        self.filter = Some(cycles);
+        self.peripheral
+            .configure_bus(self.source_clock, self.frequency, self.filter_cycles());
+        self.peripheral.set_timeout(self.source_clock, self.frequency, self.timeout);
+        self.peripheral.update_config();
+
+        Ok(())
+    }
+
+    /// Set the bus timeout to about `bus_cycles` SCL periods (10 by default)
+    ///
+    /// A transaction is aborted with [`Error::TimeOut`] if SCL doesn't change
+    /// for longer than this, e.g. when a device stretches the clock for too
+    /// long or holds the bus. Depending on the chip the value is rounded up or
+    /// limited to what the hardware supports.
+    pub fn set_timeout(&mut self, bus_cycles: u32) {
+        self.timeout = bus_cycles;
+        self.peripheral.set_timeout(self.source_clock, self.frequency, self.timeout);
+        self.peripheral.update_config();
+    }
+
+    /// Read bytes from the device with the address `address` into `buffer`
+    ///
+    /// Like [`I2C::write`] and [`I2C::write_read`] this polls the peripheral
+    /// until the transaction is finished, without requiring an async runtime.
+    /// A hanging bus is detected by the bus timeout (see
+    /// [`I2C::set_timeout`]), so this doesn't block forever.
+    ///
+    /// All bytes except for the last one are ACKed, the last one is NACKed to
+    /// tell the device the read is complete. Use [`I2C::exec`] with explicit
+    /// [`Command::Read`]s to control the ACK of every byte.
+    ///
+    /// Clock stretching by the device is handled by the controller at any
+    /// point of the transaction, e.g. between the address and the data bytes,
+    /// as long as SCL isn't held low for longer than the bus timeout.
+    pub fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Error> {
+        self.with_retries(|i2c| i2c.master_read(address, buffer))
+    }
+
+    /// Like [`I2C::read`], calling `on_ready` whenever the device releases SCL
+    /// after stretching the clock
+    ///
+    /// SCL being low for longer than a full bus period is taken as the device
+    /// stretching the clock, the level is sampled whenever the driver polls
+    /// the peripheral. `on_ready` is called from the polling loop, so it
+    /// should return quickly to not overrun the RX FIFO.
+    pub fn read_with_ready(
+        &mut self,
+        address: u8,
+        buffer: &mut [u8],
+        mut on_ready: impl FnMut(),
+    ) -> Result<(), Error> {
+        let scl = self.scl;
+        let period = (crate::clock::cpu_clock().raw() / self.frequency.raw()) as u64;
+        let mut low_since = None;
+        let mut stretched = false;
+
+        let mut poll = || {
+            if pin_level(scl) {
+                if stretched {
+                    on_ready();
+                }
+                stretched = false;
+                low_since = None;
+            } else {
+                let now = crate::system::cycle_count();
+                match low_since {
+                    None => low_since = Some(now),
+                    Some(since) if now - since > period => stretched = true,
+                    Some(_) => {}
+                }
+            }
+        };
+
+        self.with_retries(|i2c| i2c.master_read_polling(address, buffer, &mut poll))
+    }
+
+    /// Write `bytes` to the device with the address `address`
+    pub fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Error> {
+        self.with_retries(|i2c| i2c.master_write(address, bytes))
+    }
+
+    /// Write `bytes` to the device with the address `address` and read
+    /// `buffer.len()` bytes back afterwards
+    pub fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Error> {
+        self.with_retries(|i2c| i2c.master_write_read(address, bytes, buffer))
+    }
+
+    fn with_retries(
+        &mut self,
+        mut f: impl FnMut(&mut T) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        self.timed(|i2c| {
+            let mut attempt = 1;
+            loop {
+                match f(&mut i2c.peripheral) {
+                    Err(Error::ArbitrationLost) if attempt < i2c.max_attempts => {
+                        i2c.wait_for_bus_idle()?;
+                        attempt += 1;
+                    }
+                    result => return result,
+                }
+            }
+        })
+    }
+
+    /// Wait until no transfer of another master is ongoing on the bus
+    ///
+    /// Fails with [`Error::TimeOut`] if the bus is still busy after the bus
+    /// timeout, see [`I2C::set_timeout`].
+    fn wait_for_bus_idle(&self) -> Result<(), Error> {
+        let cpu_clock = crate::clock::cpu_clock().raw() as u64;
+        let max_cycles = cpu_clock * self.timeout as u64 / self.frequency.raw() as u64;
+
+        let start = crate::system::cycle_count();
+        while self.peripheral.register_block().sr.read().bus_busy().bit_is_set() {
+            if crate::system::cycle_count() - start > max_cycles {
+                return Err(Error::TimeOut);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run `f`, recording its duration if enabled via
+    /// [`I2C::enable_transaction_timing`]
+    fn timed<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        if !self.transaction_timing {
+            return f(self);
+        }
+
+        let start = crate::system::cycle_count();
+        let result = f(self);
+        self.last_transaction_cycles = Some(crate::system::cycle_count() - start);
+
+        result
+    }
+
+    /// Record the duration of every blocking transaction, see
+    /// [`I2C::last_transaction_duration`]
+    ///
+    /// This is off by default, when enabled the CPU cycle counter is read
+    /// before and after each transaction.
+    pub fn enable_transaction_timing(&mut self, enable: bool) {
+        self.transaction_timing = enable;
+        self.last_transaction_cycles = None;
+    }
+
+    /// The duration of the last blocking transaction (including any retries
+    /// after losing arbitration), `None` if timing isn't enabled via
+    /// [`I2C::enable_transaction_timing`] or there was no transaction since
+    ///
+    /// The duration is measured with the CPU cycle counter, so it's only
+    /// accurate if the CPU frequency didn't change during the transaction.
+    pub fn last_transaction_duration(&self) -> Option<MicrosDurationU64> {
+        let cycles = self.last_transaction_cycles?;
+        let cpu_clock = crate::clock::cpu_clock().raw() as u64;
+        if cpu_clock == 0 {
+            return None;
+        }
+
+        Some(MicrosDurationU64::micros(cycles * 1_000_000 / cpu_clock))
+    }
+
+    fn filter_cycles(&self) -> Option<u8> {
+        match self.filter {
+            Some(cycles) => cycles,
+            None => Some(filter_threshold(
+                self.source_clock.raw(),
+                self.frequency.raw(),
+            )),
+        }
+    }
+
+    /// Execute a raw command list.
+    ///
+    /// This gives full control over the framing of a transaction, including
+    /// the address byte(s) and the R/W bit: the bytes of all
+    /// [`Command::Write`]s are taken from `bytes` in order (so the address
+    /// byte has to be included there), all [`Command::Read`]s are stored into
+    /// `buffer` in order. The sum of the write/read lengths has to match the
+    /// length of `bytes`/`buffer`.
+    ///
+    /// At most [`Self::COMMAND_LIST_LEN`] commands can be executed at once,
+    /// each READ/WRITE command transfers 1 to 255 bytes, a command with a
+    /// length of 0 is rejected with [`Error::EmptyCommand`]. All write data is
+    /// queued before any data is read, so writes have to precede reads in the
+    /// command list. On ESP32 and ESP32-S2 `bytes` and `buffer` are limited to
+    /// [`Self::FIFO_SIZE`] each.
+    pub fn exec(
+        &mut self,
+        commands: &[Command],
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Error> {
+        self.timed(|i2c| i2c.peripheral.exec(commands, bytes, buffer))
+    }
+
+    /// The current levels of the SDA and SCL lines, `true` if high
+    ///
+    /// The I2C status register only exposes the state of the controller's
+    /// state machines, so the levels are sampled from the GPIO input
+    /// registers of the pins. Useful to diagnose a stuck bus, e.g. SDA held
+    /// low by a device, before starting a transaction.
+    pub fn line_states(&self) -> (bool, bool) {
+        (pin_level(self.sda), pin_level(self.scl))
+    }
+
+    /// Whether the bus is busy, read back from the status register
+    ///
+    /// This is set from the START to the STOP condition of a transfer on the
+    /// bus, including one of another master, so e.g. in a low-power path it
+    /// can be polled to not cut off a transfer which is still in progress.
+    /// The blocking transfers only return once the bus is idle again, this
+    /// is `true` while an async transfer is running.
+    pub fn is_busy(&self) -> bool {
+        self.peripheral.register_block().sr.read().bus_busy().bit_is_set()
+    }
+
+    /// Frequency of the clock the SCL timing is derived from
+    ///
+    /// This is the APB clock on the ESP32 and ESP32-S2 and the XTAL clock
+    /// (unless changed via [`I2C::set_source_clock`]) on all other chips.
+    pub fn source_clock(&self) -> HertzU32 {
+        self.source_clock
+    }
+
+    /// Select the source clock of the peripheral
+    ///
+    /// The bus timing is recalculated for the new source clock, keeping the
+    /// current bus frequency. Must not be called while a transaction is
+    /// running.
+    ///
+    /// Fails with [`Error::UnsupportedFrequency`] if the bus frequency can't be
+    /// reached from the new source clock.
+    #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+    pub fn set_source_clock(&mut self, source: I2cClockSource) -> Result<(), Error> {
+        let source_clock = match source {
+            I2cClockSource::Xtal => self.xtal_clock,
+            I2cClockSource::RcFast => RC_FAST_FREQ,
+        };
+        validate_frequency(source_clock, self.frequency)?;
+        if let Some(cycles) = self.filter {
+            validate_filter(source_clock, self.frequency, cycles)?;
+        }
+        self.source_clock = source_clock;
+
+        self.peripheral.set_source_clock_hw(source);
+        self.peripheral
+            .configure_bus(self.source_clock, self.frequency, self.filter_cycles());
+        self.peripheral.set_timeout(self.source_clock, self.frequency, self.timeout);
+        self.peripheral.update_config();
+
+        Ok(())
+    }
+
+    /// Reset the transmit and receive FIFOs and the command registers.
+    ///
+    /// This discards any data and commands still queued in the peripheral.
+    /// It's done automatically before every transaction and after a failing
+    /// one (e.g. on a NACK), so usually there is no need to call this.
+    pub fn reset_fifo(&mut self) {
+        self.peripheral.reset_fifo();
+        self.peripheral.reset_command_list();
+    }
+
+    /// Set the FIFO watermarks of interrupt driven transfers
+    ///
+    /// The TX watermark interrupt fires when no more than `tx` bytes are left
+    /// in the TX FIFO, the RX watermark interrupt when at least `rx` bytes
+    /// were received (on ESP32: the TX FIFO empty and RX FIFO full
+    /// interrupts). A lower `tx` and a higher `rx` threshold mean fewer
+    /// interrupts but less margin against an underrun/overrun of the FIFO.
+    /// The defaults are 8 and 1, the thresholds are kept across transactions.
+    /// Must not be called while a transaction is running.
+    ///
+    /// Fails with [`Error::InvalidFifoThreshold`] unless both are in
+    /// `1..FIFO_SIZE` (see [`Self::FIFO_SIZE`]).
+    pub fn set_fifo_threshold(&mut self, tx: u8, rx: u8) -> Result<(), Error> {
+        let range = 1..I2C_FIFO_SIZE;
+        if !range.contains(&(tx as usize)) || !range.contains(&(rx as usize)) {
+            return Err(Error::InvalidFifoThreshold);
+        }
+
+        self.peripheral.set_fifo_threshold_hw(tx, rx);
+        self.peripheral.update_config();
+
+        Ok(())
+    }
+
+    /// Release the I2C peripheral and its pins.
+    ///
+    /// The peripheral is reset and its clock disabled, SDA and SCL are
+    /// detached from it and left floating (output driver and internal pull-ups
+    /// disabled) so they don't drive the bus anymore. Returns the peripheral,
+    /// SDA and SCL.
+    pub fn free(
+        self,
+        peripheral_clock_control: &mut PeripheralClockControl,
+    ) -> (
+        PeripheralRef<'d, T>,
+        PeripheralRef<'d, SDA>,
+        PeripheralRef<'d, SCL>,
+    ) {
+        self.peripheral.reset();
+
+        crate::gpio::release_pin(self.scl);
+        crate::gpio::release_pin(self.sda);
+        // let the peripheral see an idle bus
+        crate::gpio::connect_high_to_peripheral(self.peripheral.scl_input_signal());
+        crate::gpio::connect_high_to_peripheral(self.peripheral.sda_input_signal());
+
+        disable_peripheral(&self.peripheral, peripheral_clock_control);
+
+        (self.peripheral, self.sda_pin, self.scl_pin)
+    }
+
     #[cfg(feature = "async")]
     pub(crate) fn inner(&self) -> &T {
         &self.peripheral
@@ -283,7 +1140,11 @@ mod asynch {
 
     use cfg_if::cfg_if;
     use embassy_futures::select::select;
-    use embassy_sync::waitqueue::AtomicWaker;
+    use embassy_sync::{
+        blocking_mutex::raw::CriticalSectionRawMutex,
+        mutex::Mutex,
+        waitqueue::AtomicWaker,
+    };
     use embedded_hal_1::i2c::Operation;
     use procmacros::interrupt;
 
@@ -362,21 +1223,51 @@ mod asynch {
         }
     }
 
-    impl<T> I2C<'_, T>
+    impl<T, SDA, SCL> I2C<'_, T, SDA, SCL>
     where
         T: Instance,
     {
+        /// Wait until no transfer is ongoing on the bus.
+        ///
+        /// Instead of busy-waiting this yields until the STOP condition of the
+        /// transfer of another master is detected. Useful on multi-master
+        /// buses, also see [`I2C::with_retry`].
+        pub async fn wait_bus_idle(&mut self) {
+            let register_block = self.peripheral.register_block();
+
+            loop {
+                // Arm the interrupt before checking the bus state so a STOP in
+                // between isn't missed
+                register_block
+                    .int_clr
+                    .write(|w| w.trans_complete_int_clr().set_bit());
+                let stop_detected = I2cFuture::new(Event::TxComplete, self.inner());
+
+                if register_block.sr.read().bus_busy().bit_is_clear() {
+                    register_block
+                        .int_ena
+                        .modify(|_, w| w.trans_complete_int_ena().clear_bit());
+                    break;
+                }
+
+                stop_detected.await;
+            }
+        }
+
         async fn master_read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Error> {
             // Reset FIFO and command list
             self.peripheral.reset_fifo();
             self.peripheral.reset_command_list();
 
-            self.perform_read(
-                addr,
-                buffer,
-                &mut self.peripheral.register_block().comd.iter(),
-            )
-            .await
+            let result = self
+                .perform_read(
+                    addr,
+                    buffer,
+                    &mut self.peripheral.register_block().comd.iter(),
+                )
+                .await;
+
+            self.peripheral.flush_after_error(result)
         }
 
         async fn perform_read<'a, I>(
@@ -399,10 +1290,7 @@ mod asynch {
 
         #[cfg(any(esp32, esp32s2))]
         async fn read_all_from_fifo(&self, buffer: &mut [u8]) -> Result<(), Error> {
-            if buffer.len() > 32 {
-                panic!("On ESP32 and ESP32-S2 the max I2C read is limited to 32 bytes");
-            }
-
+            // `setup_read` already rejected reads larger than the FIFO
             self.wait_for_completion().await?;
 
             for byte in buffer.iter_mut() {
@@ -422,12 +1310,15 @@ mod asynch {
             self.peripheral.reset_fifo();
             self.peripheral.reset_command_list();
 
-            self.perform_write(
-                addr,
-                bytes,
-                &mut self.peripheral.register_block().comd.iter(),
-            )
-            .await
+            let result = self
+                .perform_write(
+                    addr,
+                    bytes,
+                    &mut self.peripheral.register_block().comd.iter(),
+                )
+                .await;
+
+            self.peripheral.flush_after_error(result)
         }
 
         async fn perform_write<'a, I>(
@@ -511,20 +1402,88 @@ mod asynch {
                 }
             }
 
-            Ok(())
+            Ok(())
+        }
+    }
+
+    impl<'d, T, SDA, SCL> embedded_hal_async::i2c::I2c for I2C<'d, T, SDA, SCL>
+    where
+        T: Instance,
+    {
+        async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+            self.master_read(address, read).await
+        }
+
+        async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+            self.master_write(address, write).await
+        }
+
+        async fn write_read(
+            &mut self,
+            address: u8,
+            write: &[u8],
+            read: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            self.master_write(address, write).await?;
+            self.master_read(address, read).await?;
+
+            Ok(())
+        }
+
+        async fn transaction(
+            &mut self,
+            _address: u8,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            todo!()
+        }
+    }
+
+    /// An I2C bus shared by multiple async drivers
+    ///
+    /// Each driver gets its own [`I2cDevice`] handle, which locks the bus for
+    /// the whole duration of a `read`, `write`, `write_read` or `transaction`,
+    /// so transactions of different drivers never interleave.
+    pub struct SharedI2c<'d, T, SDA, SCL> {
+        bus: Mutex<CriticalSectionRawMutex, I2C<'d, T, SDA, SCL>>,
+    }
+
+    impl<'d, T, SDA, SCL> SharedI2c<'d, T, SDA, SCL>
+    where
+        T: Instance,
+    {
+        /// Share the bus of `i2c`
+        pub fn new(i2c: I2C<'d, T, SDA, SCL>) -> Self {
+            Self {
+                bus: Mutex::new(i2c),
+            }
+        }
+
+        /// A new handle to the bus for a driver
+        pub fn device(&self) -> I2cDevice<'_, 'd, T, SDA, SCL> {
+            I2cDevice { bus: &self.bus }
         }
     }
 
-    impl<'d, T> embedded_hal_async::i2c::I2c for I2C<'d, T>
+    /// A handle to a [`SharedI2c`] bus
+    pub struct I2cDevice<'a, 'd, T, SDA, SCL> {
+        bus: &'a Mutex<CriticalSectionRawMutex, I2C<'d, T, SDA, SCL>>,
+    }
+
+    impl<T, SDA, SCL> embedded_hal_1::i2c::ErrorType for I2cDevice<'_, '_, T, SDA, SCL> {
+        type Error = Error;
+    }
+
+    impl<T, SDA, SCL> embedded_hal_async::i2c::I2c for I2cDevice<'_, '_, T, SDA, SCL>
     where
         T: Instance,
     {
         async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
-            self.master_read(address, read).await
+            self.bus.lock().await.master_read(address, read).await
         }
 
         async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
-            self.master_write(address, write).await
+            self.bus.lock().await.master_write(address, write).await
         }
 
         async fn write_read(
@@ -533,18 +1492,31 @@ mod asynch {
             write: &[u8],
             read: &mut [u8],
         ) -> Result<(), Self::Error> {
-            self.master_write(address, write).await?;
-            self.master_read(address, read).await?;
+            let mut bus = self.bus.lock().await;
+            bus.master_write(address, write).await?;
+            bus.master_read(address, read).await?;
 
             Ok(())
         }
 
+        /// Execute the operations one after another while holding the bus
+        ///
+        /// Every operation is a transfer of its own with a START and STOP
+        /// condition.
         async fn transaction(
             &mut self,
-            _address: u8,
-            _operations: &mut [Operation<'_>],
+            address: u8,
+            operations: &mut [Operation<'_>],
         ) -> Result<(), Self::Error> {
-            todo!()
+            let mut bus = self.bus.lock().await;
+            for operation in operations {
+                match operation {
+                    Operation::Read(read) => bus.master_read(address, read).await?,
+                    Operation::Write(write) => bus.master_write(address, write).await?,
+                }
+            }
+
+            Ok(())
         }
     }
 
@@ -603,6 +1575,21 @@ fn enable_peripheral<'d, T>(
     }
 }
 
+fn disable_peripheral<'d, T>(
+    i2c: &PeripheralRef<'d, T>,
+    peripheral_clock_control: &mut PeripheralClockControl,
+) where
+    T: Instance,
+{
+    // disable peripheral
+    match i2c.i2c_number() {
+        0 => peripheral_clock_control.disable(crate::system::Peripheral::I2cExt0),
+        #[cfg(i2c1)]
+        1 => peripheral_clock_control.disable(crate::system::Peripheral::I2cExt1),
+        _ => unreachable!(), // will never happen
+    }
+}
+
 /// I2C Peripheral Instance
 pub trait Instance {
     fn scl_output_signal(&self) -> OutputSignal;
@@ -614,7 +1601,7 @@ pub trait Instance {
 
     fn i2c_number(&self) -> usize;
 
-    fn setup(&mut self, frequency: HertzU32, clocks: &Clocks) {
+    fn setup(&mut self, frequency: HertzU32, source_clk: HertzU32) {
         self.register_block().ctr.modify(|_, w| unsafe {
             // Clear register
             w.bits(0)
@@ -641,12 +1628,14 @@ pub trait Instance {
             .ctr
             .modify(|_, w| w.ref_always_on().set_bit());
 
-        // Configure filter
-        // FIXME if we ever change this we need to adapt `set_frequency` for ESP32
-        self.set_filter(Some(7), Some(7));
-
-        // Configure frequency
-        self.set_frequency(clocks.i2c_clock.convert(), frequency);
+        // Configure filter and frequency
+        #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
+        self.register_block()
+            .clk_conf
+            .modify(|_, w| w.sclk_sel().clear_bit());
+        let threshold = filter_threshold(source_clk.raw(), frequency.raw());
+        self.configure_bus(source_clk, frequency, Some(threshold));
+        self.set_fifo_threshold_hw(DEFAULT_TX_FIFO_THRESHOLD, DEFAULT_RX_FIFO_THRESHOLD);
 
         self.update_config();
 
@@ -689,6 +1678,27 @@ pub trait Instance {
         }
     }
 
+    /// Discard the data and commands a failed transaction left behind in the
+    /// FIFOs and command registers, so they can't leak into the next one
+    fn flush_after_error<R>(&self, result: Result<R, Error>) -> Result<R, Error> {
+        if result.is_err() {
+            self.reset_fifo();
+            self.reset_command_list();
+        }
+        result
+    }
+
+    /// Configure the spike filter and the timing for the bus frequency
+    fn configure_bus(
+        &mut self,
+        source_clk: HertzU32,
+        bus_freq: HertzU32,
+        filter: Option<u8>,
+    ) {
+        self.set_filter(filter, filter);
+        self.set_frequency(source_clk, bus_freq);
+    }
+
     /// Sets the filter with a supplied threshold in clock cycles for which a
     /// pulse must be present to pass the filter
     fn set_filter(&mut self, sda_threshold: Option<u8>, scl_threshold: Option<u8>) {
@@ -719,6 +1729,63 @@ pub trait Instance {
         }
     }
 
+    #[cfg(any(esp32c2, esp32c3, esp32s3))]
+    /// Selects the source clock, the RC_FAST oscillator is enabled for the
+    /// digital peripherals if needed
+    fn set_source_clock_hw(&self, source: I2cClockSource) {
+        if source == I2cClockSource::RcFast {
+            let rtc_cntl = unsafe { &*crate::peripherals::RTC_CNTL::PTR };
+            rtc_cntl
+                .clk_conf
+                .modify(|_, w| w.enb_ck8m().clear_bit().dig_clk8m_en().set_bit());
+        }
+
+        self.register_block()
+            .clk_conf
+            .modify(|_, w| w.sclk_sel().bit(source == I2cClockSource::RcFast));
+    }
+
+    #[cfg(any(esp32c6, esp32h2))]
+    /// Selects the source clock in the PCR, the RC_FAST oscillator is enabled
+    /// for the digital peripherals if needed
+    fn set_source_clock_hw(&self, source: I2cClockSource) {
+        let rc_fast = source == I2cClockSource::RcFast;
+        if rc_fast {
+            let pmu = unsafe { &*crate::peripherals::PMU::PTR };
+            let lp_clkrst = unsafe { &*crate::peripherals::LP_CLKRST::PTR };
+
+            if pmu
+                .hp_sleep_lp_ck_power
+                .read()
+                .hp_sleep_xpd_fosc_clk()
+                .bit_is_clear()
+            {
+                pmu.hp_sleep_lp_ck_power
+                    .modify(|_, w| w.hp_sleep_xpd_fosc_clk().set_bit());
+                crate::delay::RomDelay::new().delay(50);
+            }
+            if lp_clkrst.clk_to_hp.read().icg_hp_fosc().bit_is_clear() {
+                lp_clkrst.clk_to_hp.modify(|_, w| w.icg_hp_fosc().set_bit());
+                crate::delay::RomDelay::new().delay(5);
+            }
+        }
+
+        let pcr = unsafe { &*crate::peripherals::PCR::PTR };
+        // TODO: align register names between C6 and H2 in the PACs
+        #[cfg(esp32c6)]
+        pcr.i2c_sclk_conf
+            .modify(|_, w| w.i2c_sclk_sel().bit(rc_fast).i2c_sclk_en().set_bit());
+        #[cfg(esp32h2)]
+        match self.i2c_number() {
+            0 => pcr
+                .i2c0_sclk_conf
+                .modify(|_, w| w.i2c0_sclk_sel().bit(rc_fast).i2c0_sclk_en().set_bit()),
+            _ => pcr
+                .i2c1_sclk_conf
+                .modify(|_, w| w.i2c1_sclk_sel().bit(rc_fast).i2c1_sclk_en().set_bit()),
+        }
+    }
+
     #[cfg(esp32)]
     /// Sets the frequency of the I2C interface by calculating and applying the
     /// associated timings - corresponds to i2c_ll_cal_bus_clk and
@@ -745,23 +1812,17 @@ pub trait Instance {
         //   6 + SCL threshold if SCL filter is between 3 and 7 (included)
         // to SCL high period
         let mut scl_high = scl_high;
-        // In the "worst" case, we will subtract 13, make sure the result will still be
-        // correct
-
-        // FIXME since we always set the filter threshold to 7 we don't need conditional
-        // code here once that changes we need the conditional code here
-        scl_high -= 7 + 6;
-
-        // if (filter_cfg_en) {
-        //     if (thres <= 2) {
-        //         scl_high -= 8;
-        //     } else {
-        //         assert(hw->scl_filter_cfg.thres <= 7);
-        //         scl_high -= thres + 6;
-        //     }
-        // } else {
-        //    scl_high -= 7;
-        //}
+        // In the "worst" case, we will subtract 13, `validate_frequency` makes sure the
+        // result will still be correct
+        let filter = self.register_block().scl_filter_cfg.read();
+        let thres = filter.scl_filter_thres().bits() as u32;
+        if filter.scl_filter_en().bit_is_clear() {
+            scl_high -= 7;
+        } else if thres <= 2 {
+            scl_high -= 8;
+        } else {
+            scl_high -= thres + 6;
+        }
 
         let scl_high_period = scl_high;
         let scl_low_period = scl_low;
@@ -916,6 +1977,45 @@ pub trait Instance {
         );
     }
 
+    /// Set the bus timeout to about `bus_cycles` SCL periods, overriding the
+    /// default of 10 bus cycles applied by [`Self::set_frequency`]
+    fn set_timeout(&self, source_clk: HertzU32, bus_freq: HertzU32, bus_cycles: u32) {
+        let source_clk = source_clk.raw();
+        let bus_freq = bus_freq.raw();
+
+        cfg_if::cfg_if! {
+            if #[cfg(esp32)] {
+                // the timeout is given in APB cycles
+                let tout = (source_clk / bus_freq).saturating_mul(bus_cycles).min(0xf_ffff);
+                self.register_block()
+                    .to
+                    .write(|w| unsafe { w.time_out().bits(tout) });
+            } else if #[cfg(esp32s2)] {
+                // the timeout is given in APB cycles
+                let tout = (source_clk / bus_freq).saturating_mul(bus_cycles).min(0xff_ffff);
+                self.register_block()
+                    .to
+                    .write(|w| w.time_out_en().set_bit()
+                    .time_out_value()
+                    .variant(tout.try_into().unwrap())
+                );
+            } else {
+                // the timeout is 2^value SCLK cycles, round up
+                let clkm_div = source_clk / (bus_freq * 1024) + 1;
+                let cycles = (source_clk / clkm_div / bus_freq)
+                    .saturating_mul(bus_cycles)
+                    .max(2);
+                let tout = (32 - (cycles - 1).leading_zeros()).min(0x1f);
+                self.register_block()
+                    .to
+                    .write(|w| w.time_out_en().set_bit()
+                    .time_out_value()
+                    .variant(tout.try_into().unwrap())
+                );
+            }
+        }
+    }
+
     #[allow(unused)]
     fn configure_clock(
         &mut self,
@@ -935,12 +2035,9 @@ pub trait Instance {
         unsafe {
             // divider
             #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
-            self.register_block().clk_conf.modify(|_, w| {
-                w.sclk_sel()
-                    .clear_bit()
-                    .sclk_div_num()
-                    .bits((sclk_div - 1) as u8)
-            });
+            self.register_block()
+                .clk_conf
+                .modify(|_, w| w.sclk_div_num().bits((sclk_div - 1) as u8));
 
             // scl period
             self.register_block()
@@ -1026,8 +2123,10 @@ pub trait Instance {
     where
         I: Iterator<Item = &'a COMD>,
     {
-        if bytes.len() > 254 {
-            // we could support more by adding multiple write operations
+        // on ESP32/ESP32-S2 the FIFO is not refilled during a transfer, so the
+        // address byte and the data have to fit into it
+        #[cfg(any(esp32, esp32s2))]
+        if bytes.len() > I2C_FIFO_SIZE - 1 {
             return Err(Error::ExceedingFifo);
         }
 
@@ -1037,15 +2136,20 @@ pub trait Instance {
         // RSTART command
         add_cmd(cmd_iterator, Command::Start)?;
 
-        // WRITE command
-        add_cmd(
-            cmd_iterator,
-            Command::Write {
-                ack_exp: Ack::Ack,
-                ack_check_en: true,
-                length: 1 + bytes.len() as u8,
-            },
-        )?;
+        // WRITE command(s), the first one also includes the address byte
+        let mut remaining = 1 + bytes.len();
+        while remaining > 0 {
+            let length = remaining.min(I2C_CMD_MAX_LENGTH);
+            add_cmd(
+                cmd_iterator,
+                Command::Write {
+                    ack_exp: Ack::Ack,
+                    ack_check_en: true,
+                    length: length as u8,
+                },
+            )?;
+            remaining -= length;
+        }
 
         add_cmd(cmd_iterator, Command::Stop)?;
 
@@ -1089,8 +2193,10 @@ pub trait Instance {
     where
         I: Iterator<Item = &'a COMD>,
     {
-        if buffer.len() > 254 {
-            // we could support more by adding multiple read operations
+        // on ESP32/ESP32-S2 the FIFO is only read after the transfer completed,
+        // so all data has to fit into it
+        #[cfg(any(esp32, esp32s2))]
+        if buffer.len() > I2C_FIFO_SIZE {
             return Err(Error::ExceedingFifo);
         }
 
@@ -1110,15 +2216,18 @@ pub trait Instance {
             },
         )?;
 
-        if buffer.len() > 1 {
-            // READ command (N - 1)
+        // READ command(s) (N - 1)
+        let mut remaining = buffer.len().saturating_sub(1);
+        while remaining > 0 {
+            let length = remaining.min(I2C_CMD_MAX_LENGTH);
             add_cmd(
                 cmd_iterator,
                 Command::Read {
                     ack_value: Ack::Ack,
-                    length: buffer.len() as u8 - 1,
+                    length: length as u8,
                 },
             )?;
+            remaining -= length;
         }
 
         // READ w/o ACK
@@ -1145,26 +2254,172 @@ pub trait Instance {
         addr: u8,
         buffer: &mut [u8],
         cmd_iterator: &mut I,
+        poll: &mut dyn FnMut(),
     ) -> Result<(), Error>
     where
         I: Iterator<Item = &'a COMD>,
     {
         self.setup_read(addr, buffer, cmd_iterator)?;
         self.start_transmission();
-        self.read_all_from_fifo(buffer)?;
-        self.wait_for_completion()?;
+        self.read_all_from_fifo_polling(buffer, poll)?;
+        self.wait_for_completion_polling(poll)?;
 
         Ok(())
     }
 
-    #[cfg(not(any(esp32, esp32s2)))]
+    /// Write `bytes` in segments of one FIFO each, see [`I2C::FIFO_SIZE`]
+    #[cfg(any(esp32, esp32s2))]
+    fn perform_write_segmented(&self, addr: u8, bytes: &[u8]) -> Result<(), Error> {
+        // Clear all I2C interrupts
+        self.clear_all_interrupts();
+
+        let mut cmd_iterator = self.register_block().comd.iter();
+        add_cmd(&mut cmd_iterator, Command::Start)?;
+        write_fifo(
+            self.register_block(),
+            addr << 1 | OperationType::Write as u8,
+        );
+
+        let mut offset = 0;
+        loop {
+            // the first segment also holds the address byte
+            let address = (offset == 0) as usize;
+            let length = (bytes.len() - offset).min(I2C_FIFO_SIZE - address);
+            let last = offset + length == bytes.len();
+
+            add_cmd(
+                &mut cmd_iterator,
+                Command::Write {
+                    ack_exp: Ack::Ack,
+                    ack_check_en: true,
+                    length: (address + length) as u8,
+                },
+            )?;
+            if last {
+                add_cmd(&mut cmd_iterator, Command::Stop)?;
+            } else {
+                add_end_cmd(&mut cmd_iterator)?;
+            }
+
+            for byte in &bytes[offset..offset + length] {
+                write_fifo(self.register_block(), *byte);
+            }
+            offset += length;
+
+            self.start_transmission();
+            self.wait_for_completion()?;
+
+            if last {
+                break Ok(());
+            }
+
+            self.register_block()
+                .int_clr
+                .write(|w| w.end_detect_int_clr().set_bit());
+            self.reset_command_list();
+            cmd_iterator = self.register_block().comd.iter();
+        }
+    }
+
+    /// Read into `buffer` in segments of one FIFO each, see
+    /// [`I2C::FIFO_SIZE`]
+    #[cfg(any(esp32, esp32s2))]
+    fn perform_read_segmented(
+        &self,
+        addr: u8,
+        buffer: &mut [u8],
+        poll: &mut dyn FnMut(),
+    ) -> Result<(), Error> {
+        // Clear all I2C interrupts
+        self.clear_all_interrupts();
+
+        let mut cmd_iterator = self.register_block().comd.iter();
+        add_cmd(&mut cmd_iterator, Command::Start)?;
+        add_cmd(
+            &mut cmd_iterator,
+            Command::Write {
+                ack_exp: Ack::Ack,
+                ack_check_en: true,
+                length: 1,
+            },
+        )?;
+        write_fifo(self.register_block(), addr << 1 | OperationType::Read as u8);
+
+        let mut offset = 0;
+        loop {
+            let remaining = buffer.len() - offset;
+            let length = remaining.min(I2C_FIFO_SIZE);
+            let last = length == remaining;
+
+            if last {
+                // the final byte is NACKed
+                if length > 1 {
+                    add_cmd(
+                        &mut cmd_iterator,
+                        Command::Read {
+                            ack_value: Ack::Ack,
+                            length: (length - 1) as u8,
+                        },
+                    )?;
+                }
+                add_cmd(
+                    &mut cmd_iterator,
+                    Command::Read {
+                        ack_value: Ack::Nack,
+                        length: 1,
+                    },
+                )?;
+                add_cmd(&mut cmd_iterator, Command::Stop)?;
+            } else {
+                add_cmd(
+                    &mut cmd_iterator,
+                    Command::Read {
+                        ack_value: Ack::Ack,
+                        length: length as u8,
+                    },
+                )?;
+                add_end_cmd(&mut cmd_iterator)?;
+            }
+
+            self.start_transmission();
+            self.wait_for_completion_polling(poll)?;
+
+            for byte in buffer[offset..offset + length].iter_mut() {
+                *byte = read_fifo(self.register_block());
+            }
+            offset += length;
+
+            if last {
+                break Ok(());
+            }
+
+            self.register_block()
+                .int_clr
+                .write(|w| w.end_detect_int_clr().set_bit());
+            self.reset_command_list();
+            cmd_iterator = self.register_block().comd.iter();
+        }
+    }
+
     fn read_all_from_fifo(&self, buffer: &mut [u8]) -> Result<(), Error> {
+        self.read_all_from_fifo_polling(buffer, &mut || {})
+    }
+
+    /// Like `read_all_from_fifo`, calling `poll` on every iteration of the
+    /// wait loop
+    #[cfg(not(any(esp32, esp32s2)))]
+    fn read_all_from_fifo_polling(
+        &self,
+        buffer: &mut [u8],
+        poll: &mut dyn FnMut(),
+    ) -> Result<(), Error> {
         // Read bytes from FIFO
         // FIXME: Handle case where less data has been provided by the slave than
         // requested? Or is this prevented from a protocol perspective?
         for byte in buffer.iter_mut() {
             loop {
                 self.check_errors()?;
+                poll();
 
                 let reg = self.register_block().fifo_st.read();
                 if reg.rxfifo_raddr().bits() != reg.rxfifo_waddr().bits() {
@@ -1179,19 +2434,21 @@ pub trait Instance {
     }
 
     #[cfg(any(esp32, esp32s2))]
-    fn read_all_from_fifo(&self, buffer: &mut [u8]) -> Result<(), Error> {
+    fn read_all_from_fifo_polling(
+        &self,
+        buffer: &mut [u8],
+        poll: &mut dyn FnMut(),
+    ) -> Result<(), Error> {
         // on ESP32/ESP32-S2 we currently don't support I2C transactions larger than the
         // FIFO apparently it would be possible by using non-fifo mode
         // see https://github.com/espressif/arduino-esp32/blob/7e9afe8c5ed7b5bf29624a5cd6e07d431c027b97/cores/esp32/esp32-hal-i2c.c#L615
-
-        if buffer.len() > 32 {
-            panic!("On ESP32 and ESP32-S2 the max I2C read is limited to 32 bytes");
-        }
+        //
+        // `setup_read` already rejected reads larger than the FIFO
 
         // wait for completion - then we can just read the data from FIFO
         // once we change to non-fifo mode to support larger transfers that
         // won't work anymore
-        self.wait_for_completion()?;
+        self.wait_for_completion_polling(poll)?;
 
         // Read bytes from FIFO
         // FIXME: Handle case where less data has been provided by the slave than
@@ -1210,10 +2467,17 @@ pub trait Instance {
     }
 
     fn wait_for_completion(&self) -> Result<(), Error> {
+        self.wait_for_completion_polling(&mut || {})
+    }
+
+    /// Like `wait_for_completion`, calling `poll` on every iteration of the
+    /// wait loop
+    fn wait_for_completion_polling(&self, poll: &mut dyn FnMut()) -> Result<(), Error> {
         loop {
             let interrupts = self.register_block().int_raw.read();
 
             self.check_errors()?;
+            poll();
 
             // Handle completion cases
             // A full transmission was completed
@@ -1354,10 +2618,8 @@ pub trait Instance {
         // on ESP32/ESP32-S2 we currently don't support I2C transactions larger than the
         // FIFO apparently it would be possible by using non-fifo mode
         // see  https://github.com/espressif/arduino-esp32/blob/7e9afe8c5ed7b5bf29624a5cd6e07d431c027b97/cores/esp32/esp32-hal-i2c.c#L615
-
-        if bytes.len() > 31 {
-            panic!("On ESP32 and ESP32-S2 the max I2C transfer is limited to 31 bytes");
-        }
+        //
+        // `setup_write` already rejected writes larger than the FIFO
 
         for b in bytes {
             write_fifo(self.register_block(), *b);
@@ -1386,6 +2648,25 @@ pub trait Instance {
         Ok(())
     }
 
+    /// Sets the TX and RX FIFO watermarks, see [`I2C::set_fifo_threshold`]
+    #[cfg(not(esp32))]
+    fn set_fifo_threshold_hw(&self, tx: u8, rx: u8) {
+        self.register_block()
+            .fifo_conf
+            .modify(|_, w| w.txfifo_wm_thrhd().variant(tx).rxfifo_wm_thrhd().variant(rx));
+    }
+
+    /// Sets the TX and RX FIFO watermarks, see [`I2C::set_fifo_threshold`]
+    #[cfg(esp32)]
+    fn set_fifo_threshold_hw(&self, tx: u8, rx: u8) {
+        self.register_block().fifo_conf.modify(|_, w| {
+            w.txfifo_empty_thrhd()
+                .variant(tx)
+                .rxfifo_full_thrhd()
+                .variant(rx)
+        });
+    }
+
     /// Resets the transmit and receive FIFO buffers
     #[cfg(not(esp32))]
     fn reset_fifo(&self) {
@@ -1399,10 +2680,6 @@ pub trait Instance {
                 .clear_bit()
                 .fifo_prt_en()
                 .set_bit()
-                .rxfifo_wm_thrhd()
-                .variant(1)
-                .txfifo_wm_thrhd()
-                .variant(8)
         });
 
         self.register_block()
@@ -1445,25 +2722,99 @@ pub trait Instance {
             .write(|w| w.rxfifo_full_int_clr().set_bit());
     }
 
+    /// Execute a raw command list, see [`I2C::exec`]
+    fn exec(
+        &mut self,
+        commands: &[Command],
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Error> {
+        let mut write_len = 0;
+        let mut read_len = 0;
+        for command in commands {
+            match command {
+                Command::Write { length: 0, .. } | Command::Read { length: 0, .. } => {
+                    return Err(Error::EmptyCommand);
+                }
+                Command::Write { length, .. } => write_len += *length as usize,
+                Command::Read { length, .. } => read_len += *length as usize,
+                Command::Start | Command::Stop => {}
+            }
+        }
+
+        if write_len != bytes.len() || read_len != buffer.len() {
+            return Err(Error::DataLengthMismatch);
+        }
+
+        #[cfg(any(esp32, esp32s2))]
+        if bytes.len() > I2C_FIFO_SIZE || buffer.len() > I2C_FIFO_SIZE {
+            return Err(Error::ExceedingFifo);
+        }
+
+        // Reset FIFO and command list
+        self.reset_fifo();
+        self.reset_command_list();
+
+        // Clear all I2C interrupts
+        self.clear_all_interrupts();
+
+        let mut cmd_iterator = self.register_block().comd.iter();
+        for command in commands {
+            add_cmd(&mut cmd_iterator, *command)?;
+        }
+
+        self.update_config();
+
+        let index = self.fill_tx_fifo(bytes);
+        self.start_transmission();
+        let result = self
+            .write_remaining_tx_fifo(index, bytes)
+            .and_then(|_| self.read_all_from_fifo(buffer))
+            .and_then(|_| self.wait_for_completion());
+
+        self.flush_after_error(result)
+    }
+
     /// Send data bytes from the `bytes` array to a target slave with the
     /// address `addr`
     fn master_write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Error> {
         // Reset FIFO and command list
         self.reset_fifo();
         self.reset_command_list();
-        self.perform_write(addr, bytes, &mut self.register_block().comd.iter())?;
-        Ok(())
+        #[cfg(not(any(esp32, esp32s2)))]
+        let result = self.perform_write(addr, bytes, &mut self.register_block().comd.iter());
+        #[cfg(any(esp32, esp32s2))]
+        let result = self.perform_write_segmented(addr, bytes);
+        self.flush_after_error(result)
     }
 
     /// Read bytes from a target slave with the address `addr`
     /// The number of read bytes is deterimed by the size of the `buffer`
     /// argument
     fn master_read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Error> {
+        self.master_read_polling(addr, buffer, &mut || {})
+    }
+
+    /// Like `master_read`, calling `poll` while waiting for the peripheral
+    fn master_read_polling(
+        &mut self,
+        addr: u8,
+        buffer: &mut [u8],
+        poll: &mut dyn FnMut(),
+    ) -> Result<(), Error> {
         // Reset FIFO and command list
         self.reset_fifo();
         self.reset_command_list();
-        self.perform_read(addr, buffer, &mut self.register_block().comd.iter())?;
-        Ok(())
+        #[cfg(not(any(esp32, esp32s2)))]
+        let result = self.perform_read(
+            addr,
+            buffer,
+            &mut self.register_block().comd.iter(),
+            poll,
+        );
+        #[cfg(any(esp32, esp32s2))]
+        let result = self.perform_read_segmented(addr, buffer, poll);
+        self.flush_after_error(result)
     }
 
     /// Write bytes from the `bytes` array first and then read n bytes into
@@ -1484,6 +2835,98 @@ pub trait Instance {
     }
 }
 
+/// Spike filter threshold in source clock cycles for the bus frequency
+///
+/// Up to fast mode the longest threshold of 7 cycles is used. With fast-mode
+/// plus this would take too much of the short SCL high time, so only the 50ns
+/// spikes the standard requires to be suppressed are filtered.
+fn filter_threshold(source_clk: u32, bus_freq: u32) -> u8 {
+    if bus_freq <= 400_000 {
+        7
+    } else {
+        let cycles = (source_clk as u64 * 50 + 999_999_999) / 1_000_000_000;
+        cycles.clamp(1, 7) as u8
+    }
+}
+
+/// Check the spike filter threshold is supported and leaves enough of the SCL
+/// high time
+fn validate_filter(
+    source_clk: HertzU32,
+    bus_freq: HertzU32,
+    cycles: Option<u8>,
+) -> Result<(), Error> {
+    let cycles = match cycles {
+        Some(cycles) => cycles as u32,
+        None => return Ok(()),
+    };
+    let half_cycle = source_clk.raw() / bus_freq.raw() / 2;
+
+    if (1..=7).contains(&cycles) && cycles * 2 <= half_cycle {
+        Ok(())
+    } else {
+        Err(Error::InvalidFilter)
+    }
+}
+
+/// The supported bus frequency closest to `bus_freq`, see
+/// [`validate_frequency`]
+fn clamp_frequency(source_clk: HertzU32, bus_freq: HertzU32) -> HertzU32 {
+    let source_clk = source_clk.raw();
+
+    cfg_if::cfg_if! {
+        if #[cfg(esp32)] {
+            // at least 14 and at most 0x3fff source clock cycles per half cycle
+            let min = source_clk / 0x8000 + 1;
+            let max = source_clk / 28;
+        } else if #[cfg(esp32s2)] {
+            // at least 4 and at most 0x3fff source clock cycles per half cycle
+            let min = source_clk / 0x8000 + 1;
+            let max = source_clk / 8;
+        } else {
+            // the source clock can be divided by at most 256, at least 8
+            // cycles per half cycle
+            let min = source_clk / (256 * 1024) + 1;
+            let max = source_clk / 16;
+        }
+    }
+
+    HertzU32::Hz(bus_freq.raw().clamp(min, max.min(MAX_BUS_FREQUENCY)))
+}
+
+/// Check the bus timing for the frequency can be set up from the source clock
+fn validate_frequency(source_clk: HertzU32, bus_freq: HertzU32) -> Result<(), Error> {
+    let source_clk = source_clk.raw();
+    let bus_freq = bus_freq.raw();
+
+    if bus_freq == 0 || bus_freq > MAX_BUS_FREQUENCY {
+        return Err(Error::UnsupportedFrequency);
+    }
+
+    cfg_if::cfg_if! {
+        if #[cfg(esp32)] {
+            // SCL high is shortened by up to 13 cycles for the filter, the
+            // periods are limited to 14 bits
+            let half_cycle = source_clk / bus_freq / 2;
+            let valid = half_cycle > 13 && half_cycle <= 0x3fff;
+        } else if #[cfg(esp32s2)] {
+            let half_cycle = source_clk / bus_freq / 2;
+            let valid = half_cycle >= 4 && half_cycle <= 0x3fff;
+        } else {
+            // the source clock is divided down for low bus frequencies
+            let clkm_div = source_clk / (bus_freq * 1024) + 1;
+            let half_cycle = source_clk / clkm_div / bus_freq / 2;
+            let valid = half_cycle >= 8 && clkm_div <= 256;
+        }
+    }
+
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedFrequency)
+    }
+}
+
 fn add_cmd<'a, I>(cmd_iterator: &mut I, command: Command) -> Result<(), Error>
 where
     I: Iterator<Item = &'a COMD>,
@@ -1493,6 +2936,18 @@ where
     Ok(())
 }
 
+/// Add an END command, which pauses the transfer (holding SCL low) until the
+/// next command list is started
+#[cfg(any(esp32, esp32s2))]
+fn add_end_cmd<'a, I>(cmd_iterator: &mut I) -> Result<(), Error>
+where
+    I: Iterator<Item = &'a COMD>,
+{
+    let cmd = cmd_iterator.next().ok_or(Error::CommandNrExceeded)?;
+    cmd.write(|w| unsafe { w.command().bits((Opcode::End as u16) << 11) });
+    Ok(())
+}
+
 #[cfg(not(any(esp32, esp32s2)))]
 fn read_fifo(register_block: &RegisterBlock) -> u8 {
     register_block.data.read().fifo_rdata().bits()
@@ -3,7 +3,86 @@ pub use riscv::*;
 #[cfg(xtensa)]
 pub use xtensa::*;
 
+use crate::{peripherals::Interrupt, Cpu};
+
 #[cfg(riscv)]
 mod riscv;
 #[cfg(xtensa)]
 mod xtensa;
+
+/// A driver whose pending interrupts can be cleared without knowing the
+/// concrete peripheral, e.g. in a dispatcher shared by several handlers
+///
+/// The drivers keep their own, more specific methods to clear single
+/// interrupt conditions.
+pub trait InterruptClearable {
+    /// Clear all pending interrupts of the driver
+    fn clear_interrupts(&mut self);
+}
+
+/// Run `f` with the peripheral interrupt masked on the current core
+///
+/// Unlike a critical section this only keeps the handler of `interrupt` from
+/// running, other interrupts are still serviced. The previous mapping of the
+/// interrupt is restored when `f` returns or unwinds. A level interrupt which
+/// is raised while masked is serviced after `f` returns, the handler can
+/// still run on the other core if the interrupt is enabled there.
+pub fn free_specific<R>(interrupt: Interrupt, f: impl FnOnce() -> R) -> R {
+    struct Restore {
+        interrupt: Interrupt,
+        mapping: u32,
+    }
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            unsafe { set_mapping(crate::get_core(), self.interrupt, self.mapping) }
+        }
+    }
+
+    let _restore = Restore {
+        interrupt,
+        mapping: mapping(crate::get_core(), interrupt),
+    };
+    disable(crate::get_core(), interrupt);
+
+    f()
+}
+
+/// The peripheral interrupts pending on `core`
+///
+/// The status is read once from the interrupt matrix, including interrupts
+/// which aren't enabled or mapped to a CPU interrupt. This only reads
+/// registers, so it can be called from any context, e.g. to log the active
+/// sources from an interrupt or panic handler.
+pub fn pending_interrupts(core: Cpu) -> PendingInterrupts {
+    PendingInterrupts {
+        status: get_status(core),
+    }
+}
+
+/// Iterator over pending peripheral interrupts, see [`pending_interrupts`]
+#[derive(Debug, Clone)]
+pub struct PendingInterrupts {
+    status: u128,
+}
+
+impl Iterator for PendingInterrupts {
+    type Item = Interrupt;
+
+    fn next(&mut self) -> Option<Interrupt> {
+        while self.status != 0 {
+            let interrupt_nr = self.status.trailing_zeros();
+            self.status &= !(1u128 << interrupt_nr);
+
+            #[cfg(riscv)]
+            let interrupt = Interrupt::try_from(interrupt_nr as u8);
+            #[cfg(xtensa)]
+            let interrupt = Interrupt::try_from(interrupt_nr as u16);
+            if let Ok(interrupt) = interrupt {
+                return Some(interrupt);
+            }
+        }
+
+        None
+    }
+}
@@ -5,6 +5,11 @@
 //!
 //! On chips with a PLIC CPU interrupts 1,2,5,6,9 .. 19 are used.
 //!
+//! [`set_threshold`] masks all interrupts below a priority. On the Xtensa
+//! chips the equivalent is the interrupt level of the CPU (`PS.INTLEVEL`),
+//! which masks interrupts up to and including a level, it isn't exposed by
+//! this module there.
+//!
 //! ```rust
 //! interrupt1() => Priority::Priority1
 //! interrupt2() => Priority::Priority2
@@ -144,6 +149,8 @@ pub use vectored::*;
 
 #[cfg(feature = "vectored")]
 mod vectored {
+    use core::sync::atomic::{AtomicPtr, Ordering};
+
     use procmacros::ram;
 
     use super::*;
@@ -191,6 +198,8 @@ mod vectored {
     #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub enum Error {
         InvalidInterruptPriority,
+        /// All CPU interrupts available for [`enable_direct`] are in use
+        NoFreeCpuInterrupt,
     }
 
     /// Enables a interrupt at a given priority
@@ -210,6 +219,76 @@ mod vectored {
         Ok(())
     }
 
+    const NO_HANDLER: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+    /// Handlers installed by [`enable_direct`], indexed by CPU interrupt
+    static DIRECT_HANDLERS: [AtomicPtr<()>; 32] = [NO_HANDLER; 32];
+
+    /// Binds an interrupt directly to a CPU interrupt which calls `handler`
+    ///
+    /// Unlike [`enable`] the handler is called straight from the trap handler
+    /// without looking up the pending peripheral interrupts, this reduces the
+    /// latency and jitter of the interrupt. One of the CPU interrupts not
+    /// reserved for vectoring is used, it's returned so it can be released
+    /// again with [`disable_direct`].
+    ///
+    /// The interrupt is level triggered, so `handler` must clear the interrupt
+    /// of the peripheral. An `#[interrupt]` handler for `interrupt` is not
+    /// called.
+    pub fn enable_direct(
+        interrupt: Interrupt,
+        level: Priority,
+        handler: fn(&mut TrapFrame),
+    ) -> Result<CpuInterrupt, Error> {
+        if matches!(level, Priority::None) {
+            return Err(Error::InvalidInterruptPriority);
+        }
+
+        let cpu_interrupt = critical_section::with(|_| {
+            let free = DIRECT_INTERRUPTS
+                .iter()
+                .copied()
+                .find(|num| DIRECT_HANDLERS[*num].load(Ordering::Relaxed).is_null())?;
+            DIRECT_HANDLERS[free].store(handler as *mut (), Ordering::Relaxed);
+            Some(free)
+        })
+        .ok_or(Error::NoFreeCpuInterrupt)?;
+
+        unsafe {
+            let cpu_interrupt: CpuInterrupt = core::mem::transmute(cpu_interrupt as u32);
+            set_kind(crate::get_core(), cpu_interrupt, InterruptKind::Level);
+            set_priority(crate::get_core(), cpu_interrupt, level);
+            map(crate::get_core(), interrupt, cpu_interrupt);
+            enable_cpu_interrupt(cpu_interrupt);
+
+            Ok(cpu_interrupt)
+        }
+    }
+
+    /// Disables an interrupt bound by [`enable_direct`] and releases its CPU
+    /// interrupt
+    pub fn disable_direct(interrupt: Interrupt, cpu_interrupt: CpuInterrupt) {
+        disable(crate::get_core(), interrupt);
+        DIRECT_HANDLERS[cpu_interrupt as usize].store(core::ptr::null_mut(), Ordering::Relaxed);
+    }
+
+    /// Call the handler bound to the CPU interrupt `code` by
+    /// [`enable_direct`], returns `false` if there is none
+    #[ram]
+    pub(super) unsafe fn handle_direct(code: usize, context: &mut TrapFrame) -> bool {
+        let handler = match DIRECT_HANDLERS.get(code) {
+            Some(handler) => handler.load(Ordering::Relaxed),
+            None => return false,
+        };
+        if handler.is_null() {
+            return false;
+        }
+
+        let handler: fn(&mut TrapFrame) = core::mem::transmute(handler);
+        handler(context);
+        true
+    }
+
     #[ram]
     unsafe fn handle_interrupts(cpu_intr: CpuInterrupt, context: &mut TrapFrame) {
         let status = get_status(crate::get_core());
@@ -369,40 +448,46 @@ pub unsafe extern "C" fn start_trap_rust_hal(trap_frame: *mut TrapFrame) {
         #[cfg(feature = "interrupt-preemption")]
         let interrupt_priority = handle_priority();
         let code = mcause::read().code();
-        match code {
-            1 => interrupt1(trap_frame.as_mut().unwrap()),
-            2 => interrupt2(trap_frame.as_mut().unwrap()),
-            3 => interrupt3(trap_frame.as_mut().unwrap()),
-            4 => interrupt4(trap_frame.as_mut().unwrap()),
-            5 => interrupt5(trap_frame.as_mut().unwrap()),
-            6 => interrupt6(trap_frame.as_mut().unwrap()),
-            7 => interrupt7(trap_frame.as_mut().unwrap()),
-            8 => interrupt8(trap_frame.as_mut().unwrap()),
-            9 => interrupt9(trap_frame.as_mut().unwrap()),
-            10 => interrupt10(trap_frame.as_mut().unwrap()),
-            11 => interrupt11(trap_frame.as_mut().unwrap()),
-            12 => interrupt12(trap_frame.as_mut().unwrap()),
-            13 => interrupt13(trap_frame.as_mut().unwrap()),
-            14 => interrupt14(trap_frame.as_mut().unwrap()),
-            15 => interrupt15(trap_frame.as_mut().unwrap()),
-            16 => interrupt16(trap_frame.as_mut().unwrap()),
-            17 => interrupt17(trap_frame.as_mut().unwrap()),
-            18 => interrupt18(trap_frame.as_mut().unwrap()),
-            19 => interrupt19(trap_frame.as_mut().unwrap()),
-            20 => interrupt20(trap_frame.as_mut().unwrap()),
-            21 => interrupt21(trap_frame.as_mut().unwrap()),
-            22 => interrupt22(trap_frame.as_mut().unwrap()),
-            23 => interrupt23(trap_frame.as_mut().unwrap()),
-            24 => interrupt24(trap_frame.as_mut().unwrap()),
-            25 => interrupt25(trap_frame.as_mut().unwrap()),
-            26 => interrupt26(trap_frame.as_mut().unwrap()),
-            27 => interrupt27(trap_frame.as_mut().unwrap()),
-            28 => interrupt28(trap_frame.as_mut().unwrap()),
-            29 => interrupt29(trap_frame.as_mut().unwrap()),
-            30 => interrupt30(trap_frame.as_mut().unwrap()),
-            31 => interrupt31(trap_frame.as_mut().unwrap()),
-            _ => DefaultHandler(),
-        };
+        #[cfg(feature = "vectored")]
+        let handled = handle_direct(code, trap_frame.as_mut().unwrap());
+        #[cfg(not(feature = "vectored"))]
+        let handled = false;
+        if !handled {
+            match code {
+                1 => interrupt1(trap_frame.as_mut().unwrap()),
+                2 => interrupt2(trap_frame.as_mut().unwrap()),
+                3 => interrupt3(trap_frame.as_mut().unwrap()),
+                4 => interrupt4(trap_frame.as_mut().unwrap()),
+                5 => interrupt5(trap_frame.as_mut().unwrap()),
+                6 => interrupt6(trap_frame.as_mut().unwrap()),
+                7 => interrupt7(trap_frame.as_mut().unwrap()),
+                8 => interrupt8(trap_frame.as_mut().unwrap()),
+                9 => interrupt9(trap_frame.as_mut().unwrap()),
+                10 => interrupt10(trap_frame.as_mut().unwrap()),
+                11 => interrupt11(trap_frame.as_mut().unwrap()),
+                12 => interrupt12(trap_frame.as_mut().unwrap()),
+                13 => interrupt13(trap_frame.as_mut().unwrap()),
+                14 => interrupt14(trap_frame.as_mut().unwrap()),
+                15 => interrupt15(trap_frame.as_mut().unwrap()),
+                16 => interrupt16(trap_frame.as_mut().unwrap()),
+                17 => interrupt17(trap_frame.as_mut().unwrap()),
+                18 => interrupt18(trap_frame.as_mut().unwrap()),
+                19 => interrupt19(trap_frame.as_mut().unwrap()),
+                20 => interrupt20(trap_frame.as_mut().unwrap()),
+                21 => interrupt21(trap_frame.as_mut().unwrap()),
+                22 => interrupt22(trap_frame.as_mut().unwrap()),
+                23 => interrupt23(trap_frame.as_mut().unwrap()),
+                24 => interrupt24(trap_frame.as_mut().unwrap()),
+                25 => interrupt25(trap_frame.as_mut().unwrap()),
+                26 => interrupt26(trap_frame.as_mut().unwrap()),
+                27 => interrupt27(trap_frame.as_mut().unwrap()),
+                28 => interrupt28(trap_frame.as_mut().unwrap()),
+                29 => interrupt29(trap_frame.as_mut().unwrap()),
+                30 => interrupt30(trap_frame.as_mut().unwrap()),
+                31 => interrupt31(trap_frame.as_mut().unwrap()),
+                _ => DefaultHandler(),
+            };
+        }
         #[cfg(feature = "interrupt-preemption")]
         restore_priority(interrupt_priority);
     }
@@ -497,13 +582,66 @@ unsafe fn handle_exception(pc: usize, trap_frame: *mut TrapFrame) {
     (*trap_frame).pc = pc + 4;
 }
 
-#[doc(hidden)]
-#[no_mangle]
-pub fn _setup_interrupts() {
+/// Trap vector mode of the CPU, see [`set_vectoring`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vectoring {
+    /// Interrupts jump to the entry of their CPU interrupt in the vector
+    /// table, exceptions to the first one. This is the default.
+    Vectored,
+    /// All traps jump to the first entry of the vector table
+    Direct,
+}
+
+/// The CPU doesn't support the requested [`Vectoring`] mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedVectoring;
+
+/// Select the trap vector mode of the current core
+///
+/// With the vector table of `esp-riscv-rt` every entry leads to the same trap
+/// handler, which dispatches on `mcause`: both modes behave the same and
+/// `enable` and `enable_direct` (with the `vectored` feature) as well as the
+/// embassy time driver and interrupt executor work with either. The mode
+/// only makes a difference for an application-provided `_vector_table` with
+/// separate entries per CPU interrupt, where `Vectored` saves the dispatch in
+/// software at the cost of a `j` instruction per entry.
+///
+/// The mode field of `mtvec` may be hardwired by the CPU, so it's read back
+/// after writing it: if the mode didn't take, the previous trap vector is
+/// restored and [`UnsupportedVectoring`] is returned.
+///
+/// # Safety
+///
+/// This changes the machine trap vector (`mtvec`) that every interrupt and
+/// exception goes through. It has to be called with interrupts disabled, and
+/// an application-provided `_vector_table` has to match the mode.
+pub unsafe fn set_vectoring(vectoring: Vectoring) -> Result<(), UnsupportedVectoring> {
     extern "C" {
         static _vector_table: *const u32;
     }
 
+    let mode = match vectoring {
+        Vectoring::Vectored => mtvec::TrapMode::Vectored,
+        Vectoring::Direct => mtvec::TrapMode::Direct,
+    };
+
+    let previous = mtvec::read();
+    let vec_table = &_vector_table as *const _ as usize;
+    mtvec::write(vec_table, mode);
+
+    if mtvec::read().trap_mode() != Some(mode) {
+        if let Some(previous_mode) = previous.trap_mode() {
+            mtvec::write(previous.address(), previous_mode);
+        }
+        return Err(UnsupportedVectoring);
+    }
+
+    Ok(())
+}
+
+#[doc(hidden)]
+#[no_mangle]
+pub fn _setup_interrupts() {
     unsafe {
         // disable all known interrupts
         // at least after the 2nd stage bootloader there are some interrupts enabled
@@ -518,8 +656,9 @@ pub fn _setup_interrupts() {
                 .ok();
         }
 
-        let vec_table = &_vector_table as *const _ as usize;
-        mtvec::write(vec_table, mtvec::TrapMode::Vectored);
+        // with the vector table of `esp-riscv-rt` both modes dispatch the same,
+        // so a CPU without the vectored mode keeps working in its own mode
+        set_vectoring(Vectoring::Vectored).ok();
 
         #[cfg(feature = "vectored")]
         crate::interrupt::init_vectoring();
@@ -542,6 +681,22 @@ pub fn disable(_core: Cpu, interrupt: Interrupt) {
     }
 }
 
+/// Raw value of the interrupt map register of the peripheral interrupt
+pub(crate) fn mapping(_core: Cpu, interrupt: Interrupt) -> u32 {
+    unsafe {
+        let intr_map_base = crate::soc::registers::INTERRUPT_MAP_BASE as *mut u32;
+        intr_map_base.offset(interrupt as isize).read_volatile()
+    }
+}
+
+/// Restore a value read by [`mapping`]
+pub(crate) unsafe fn set_mapping(_core: Cpu, interrupt: Interrupt, mapping: u32) {
+    let intr_map_base = crate::soc::registers::INTERRUPT_MAP_BASE as *mut u32;
+    intr_map_base
+        .offset(interrupt as isize)
+        .write_volatile(mapping);
+}
+
 /// Get status of peripheral interrupts
 #[inline]
 pub fn get_status(_core: Cpu) -> u128 {
@@ -612,6 +767,10 @@ mod classic {
     pub(super) const INTERRUPT_TO_PRIORITY: [usize; 15] =
         [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
 
+    /// CPU interrupts not used for vectoring, for `enable_direct`
+    pub(super) const DIRECT_INTERRUPTS: [usize; 16] =
+        [16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31];
+
     /// Enable a CPU interrupt
     pub unsafe fn enable_cpu_interrupt(which: CpuInterrupt) {
         let cpu_interrupt_number = which as isize;
@@ -680,6 +839,25 @@ mod classic {
             .read_volatile();
         core::mem::transmute(prio as u8)
     }
+
+    /// Mask all CPU interrupts with a priority below `threshold`
+    ///
+    /// [`Priority::None`] lets all enabled interrupts through. With the
+    /// `interrupt-preemption` feature the threshold is raised while a handler
+    /// runs and restored afterwards, so a threshold set in a handler only
+    /// lasts until it returns.
+    pub fn set_threshold(threshold: Priority) {
+        let intr = unsafe { &*crate::peripherals::INTERRUPT_CORE0::PTR };
+        intr.cpu_int_thresh
+            .write(|w| unsafe { w.bits(threshold as u32) });
+    }
+
+    /// The current priority threshold, see [`set_threshold`]
+    pub fn get_threshold() -> Priority {
+        let intr = unsafe { &*crate::peripherals::INTERRUPT_CORE0::PTR };
+        let threshold = intr.cpu_int_thresh.read().bits().min(Priority::max() as u32);
+        unsafe { core::mem::transmute(threshold as u8) }
+    }
     #[cfg(all(feature = "interrupt-preemption"))]
     use procmacros::ram;
     #[cfg(all(feature = "interrupt-preemption"))]
@@ -733,6 +911,10 @@ mod plic {
         1, 2, 0, 0, 3, 4, 0, 0, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
     ];
 
+    /// CPU interrupts not used for vectoring, for `enable_direct`
+    pub(super) const DIRECT_INTERRUPTS: [usize; 12] =
+        [20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31];
+
     const DR_REG_PLIC_MX_BASE: u32 = 0x20001000;
     const PLIC_MXINT_ENABLE_REG: u32 = DR_REG_PLIC_MX_BASE + 0x0;
     const PLIC_MXINT_TYPE_REG: u32 = DR_REG_PLIC_MX_BASE + 0x4;
@@ -805,6 +987,24 @@ mod plic {
             .read_volatile();
         core::mem::transmute(prio as u8)
     }
+
+    /// Mask all CPU interrupts with a priority below `threshold`
+    ///
+    /// [`Priority::None`] lets all enabled interrupts through. With the
+    /// `interrupt-preemption` feature the threshold is raised while a handler
+    /// runs and restored afterwards, so a threshold set in a handler only
+    /// lasts until it returns.
+    pub fn set_threshold(threshold: Priority) {
+        let thresh_reg = PLIC_MXINT_THRESH_REG as *mut u32;
+        unsafe { thresh_reg.write_volatile(threshold as u32) };
+    }
+
+    /// The current priority threshold, see [`set_threshold`]
+    pub fn get_threshold() -> Priority {
+        let thresh_reg = PLIC_MXINT_THRESH_REG as *mut u32;
+        let threshold = unsafe { thresh_reg.read_volatile() } & 0x000000FF;
+        unsafe { core::mem::transmute(threshold.min(Priority::max() as u32) as u8) }
+    }
     #[cfg(all(feature = "interrupt-preemption"))]
     use procmacros::ram;
     #[cfg(all(feature = "interrupt-preemption"))]
@@ -86,6 +86,30 @@ pub fn disable(core: Cpu, interrupt: Interrupt) {
     }
 }
 
+/// Raw value of the interrupt map register of the peripheral interrupt
+pub(crate) fn mapping(core: Cpu, interrupt: Interrupt) -> u32 {
+    unsafe {
+        let intr_map_base = match core {
+            Cpu::ProCpu => (*core0_interrupt_peripheral()).pro_mac_intr_map.as_ptr(),
+            #[cfg(multi_core)]
+            Cpu::AppCpu => (*core1_interrupt_peripheral()).app_mac_intr_map.as_ptr(),
+        };
+        intr_map_base.offset(interrupt as isize).read_volatile()
+    }
+}
+
+/// Restore a value read by [`mapping`]
+pub(crate) unsafe fn set_mapping(core: Cpu, interrupt: Interrupt, mapping: u32) {
+    let intr_map_base = match core {
+        Cpu::ProCpu => (*core0_interrupt_peripheral()).pro_mac_intr_map.as_ptr(),
+        #[cfg(multi_core)]
+        Cpu::AppCpu => (*core1_interrupt_peripheral()).app_mac_intr_map.as_ptr(),
+    };
+    intr_map_base
+        .offset(interrupt as isize)
+        .write_volatile(mapping);
+}
+
 /// Clear the given CPU interrupt
 pub fn clear(_core: Cpu, which: CpuInterrupt) {
     unsafe {
@@ -1,3 +1,6 @@
+use core::cell::{Cell, RefCell};
+
+use critical_section::Mutex;
 use paste::paste;
 
 #[cfg(esp32)]
@@ -8,7 +11,7 @@ use super::{
 };
 use crate::{
     gpio::{OutputPin, OutputSignal},
-    peripheral::{Peripheral, PeripheralRef},
+    peripheral::PeripheralRef,
 };
 
 /// Fade parameter sub-errors
@@ -22,6 +25,8 @@ pub enum FadeError {
     DutyRange,
     /// Duration too long for timer frequency and duty resolution
     Duration,
+    /// Gamma not a positive number
+    Gamma,
 }
 
 /// Channel errors
@@ -35,9 +40,47 @@ pub enum Error {
     Channel,
     /// Fade parameters invalid
     Fade(FadeError),
+    /// Raw duty value out of range for the timer duty resolution, see
+    /// [`ChannelIFace::set_raw_duty`], [`ChannelIFace::set_duty_with_start`]
+    /// and [`Channel::pulse_n`]
+    DutyOutOfRange,
+    /// Start point out of range for the timer duty resolution
+    StartPoint,
+    /// Channel already in use
+    InUse,
+    /// GPIO already bound to another channel
+    PinInUse,
+    /// Number of PWM periods between overflow interrupts out of range
+    OverflowCount,
+    /// Number of pulses out of range
+    PulseCount,
+    /// The operation isn't available on this chip, see also
+    /// [`timer::Error::Unsupported`](super::timer::Error::Unsupported)
+    Unsupported,
+    /// The GPIO is a strapping pin, see
+    /// [`LEDC::get_channel_checked`](super::LEDC::get_channel_checked)
+    StrappingPin,
+    /// The channels of a complementary pair aren't bound to the same timer,
+    /// or aren't the high and low side of a pair
+    Deadtime,
+    /// The channel is part of a complementary pair, see
+    /// [`Channel::set_deadtime`]
+    Paired,
+    /// The channel isn't bound to the given timer, or the timer isn't
+    /// configured
+    TimerMismatch,
+    /// The timer didn't overflow within two PWM periods, e.g. because its
+    /// clock source isn't running
+    Timeout,
 }
 
 /// Channel number
+///
+/// This covers every channel of the LEDC peripheral: 8 on the ESP32-S2/S3
+/// and 6 on the ESP32-C2/C3/C6/H2. On the ESP32 each number selects one of
+/// the 8 high speed or one of the 8 low speed channels, depending on the
+/// speed mode of the channel. Only the channels of the target chip are
+/// available, see [`CHANNEL_COUNT`](super::CHANNEL_COUNT).
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum Number {
     Channel0,
@@ -82,6 +125,17 @@ where
     /// Set channel duty HW
     fn set_duty(&self, duty_pct: u8) -> Result<(), Error>;
 
+    /// Set the raw channel duty, as a count of timer ticks
+    fn set_raw_duty(&self, count: u32) -> Result<(), Error>;
+
+    /// Set the raw channel duty, with the output going high at the counter
+    /// value `start_point`
+    fn set_duty_with_start(&mut self, duty: u32, start_point: u32) -> Result<(), Error>;
+
+    /// Bind the channel to a different (already configured) timer, keeping
+    /// the current raw duty value
+    fn set_timer(&mut self, timer: &'a dyn TimerIFace<S>) -> Result<(), Error>;
+
     /// Start a duty-cycle fade
     fn start_duty_fade(
         &self,
@@ -92,6 +146,9 @@ where
 
     /// Check whether a duty-cycle fade is running
     fn is_duty_fade_running(&self) -> bool;
+
+    /// Check whether the channel is outputting the PWM signal
+    fn is_enabled(&self) -> bool;
 }
 
 /// Channel HW interface
@@ -104,6 +161,19 @@ pub trait ChannelHW<O: OutputPin> {
     /// Set channel duty HW
     fn set_duty_hw(&self, duty: u32);
 
+    /// Set the counter value at which the output goes high in HW
+    fn set_hpoint_hw(&self, hpoint: u32);
+
+    /// Hold the output at a static level in HW, or output the PWM signal
+    /// (`None`)
+    fn set_static_level_hw(&self, level: Option<bool>);
+
+    /// Read back the raw channel duty from HW
+    fn get_duty_hw(&self) -> u32;
+
+    /// Select the timer of the channel HW
+    fn set_timer_hw(&self, timer_number: u8);
+
     /// Start a duty-cycle fade HW
     fn start_duty_fade_hw(
         &self,
@@ -116,6 +186,44 @@ pub trait ChannelHW<O: OutputPin> {
 
     /// Check whether a duty-cycle fade is running HW
     fn is_duty_fade_running_hw(&self) -> bool;
+
+    /// Read back whether the PWM output of the channel is enabled in HW
+    fn is_output_enabled_hw(&self) -> bool;
+
+    /// Bit of the duty-cycle fade end interrupt in the LEDC interrupt
+    /// registers
+    fn duty_fade_int_bit(&self) -> u32;
+}
+
+/// The static output level for the raw duty `count`, `None` if the PWM signal
+/// is needed
+fn static_level(count: u32, max_count: u32) -> Option<bool> {
+    if count == 0 {
+        Some(false)
+    } else if count >= max_count {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// HW parameters of a duty fade
+#[derive(Clone, Copy)]
+pub(crate) struct FadeParameters {
+    pub(crate) start_duty: u32,
+    pub(crate) duty_inc: bool,
+    pub(crate) duty_steps: u16,
+    pub(crate) cycles_per_step: u16,
+    pub(crate) duty_per_cycle: u16,
+}
+
+/// One side of a complementary pair, see [`Channel::set_deadtime`]
+#[derive(Clone, Copy)]
+struct Deadtime {
+    partner: Number,
+    high_side: bool,
+    rising_ns: u32,
+    falling_ns: u32,
 }
 
 /// Channel struct
@@ -123,110 +231,556 @@ pub struct Channel<'a, S: TimerSpeed, O: OutputPin> {
     timer: Option<&'a dyn TimerIFace<S>>,
     number: Number,
     output_pin: PeripheralRef<'a, O>,
+    /// Cleared while the output is gated by [`Channel::output_enable`], the
+    /// HW can't tell this apart from the static level for 0% or 100% duty
+    output_enabled: Cell<bool>,
+    /// Set while the channel is part of a complementary pair
+    deadtime: Cell<Option<Deadtime>>,
 }
 
 impl<'a, S: TimerSpeed, O: OutputPin> Channel<'a, S, O> {
     /// Return a new channel
-    pub fn new(number: Number, output_pin: impl Peripheral<P = O> + 'a) -> Self {
-        crate::into_ref!(output_pin);
+    pub(crate) fn new(number: Number, output_pin: PeripheralRef<'a, O>) -> Self {
         Channel {
             timer: None,
             number,
             output_pin,
+            output_enabled: Cell::new(false),
+            deadtime: Cell::new(None),
+        }
+    }
+
+    /// Number of the GPIO the channel outputs to
+    pub(crate) fn pin_number(&self) -> u8 {
+        self.output_pin.number()
+    }
+
+    /// The complementary pair the channel is part of
+    ///
+    /// Dropping the partner splits up the pair in the registry only, so the
+    /// registry is checked as well.
+    fn pairing(&self) -> Option<Deadtime> {
+        self.deadtime
+            .get()
+            .filter(|_| super::is_paired::<S>(self.number))
+    }
+
+    /// Fails with [`Error::Paired`] if the channel is part of a complementary
+    /// pair
+    fn check_unpaired(&self) -> Result<(), Error> {
+        match self.pairing() {
+            Some(_) => Err(Error::Paired),
+            None => Ok(()),
         }
     }
 }
 
-impl<'a, S: TimerSpeed, O: OutputPin> ChannelIFace<'a, S, O> for Channel<'a, S, O>
+impl<'a, S: TimerSpeed, O: OutputPin> Channel<'a, S, O>
 where
     Channel<'a, S, O>: ChannelHW<O>,
 {
-    /// Configure channel
-    fn configure(&mut self, config: config::Config<'a, S>) -> Result<(), Error> {
-        self.timer = Some(config.timer);
+    /// Capture the configuration of the channel, `None` if not configured
+    pub(crate) fn snapshot(&self) -> Option<super::ChannelSnapshot> {
+        let timer = self.timer?;
 
-        self.set_duty(config.duty_pct)?;
-        self.configure_hw_with_pin_config(config.pin_config)?;
+        Some(super::ChannelSnapshot {
+            timer: timer.get_number(),
+            duty: self.get_duty_hw(),
+            pin: self.output_pin.number(),
+            open_drain: crate::gpio::is_open_drain(self.output_pin.number()),
+        })
+    }
 
-        Ok(())
+    /// Number of the channel
+    pub(crate) fn number(&self) -> Number {
+        self.number
     }
 
-    /// Set duty % of channel
-    fn set_duty(&self, duty_pct: u8) -> Result<(), Error> {
-        let duty_exp;
-        if let Some(timer) = self.timer {
-            if let Some(timer_duty) = timer.get_duty() {
-                duty_exp = timer_duty as u32;
-            } else {
-                return Err(Error::Timer);
+    /// The current duty in % of the bound timer's period, read back from the
+    /// HW including an ongoing fade, `None` if the channel isn't configured
+    pub fn try_duty_percent(&self) -> Option<f32> {
+        let max_duty = self.max_duty().ok()?;
+
+        Some(current_duty_hw(S::IS_HS, self.number) as f32 * 100.0 / max_duty as f32)
+    }
+
+    /// The current duty in %, see [`Channel::try_duty_percent`], or 0% if the
+    /// channel isn't configured
+    pub fn duty_percent(&self) -> f32 {
+        self.try_duty_percent().unwrap_or(0.0)
+    }
+
+    /// Drive `self` as the high side and `low_side` as the low side of a half
+    /// bridge, with `rising_ns` of dead time before the high side turns on and
+    /// `falling_ns` after it turns off
+    ///
+    /// LEDC has no complementary outputs or dead-time generator, so the low
+    /// side is a second channel, passed in here, and the gaps are placed with
+    /// its start point (hpoint): the high side is on for the first `duty`
+    /// counts of the period, the low side from `duty` plus the falling dead
+    /// time up to the rising dead time before the period ends. Both channels
+    /// must be bound to the same timer, otherwise [`Error::Deadtime`] is
+    /// returned. The dead times are rounded up to whole timer counts; if they
+    /// don't leave room for the low side it is held off. The duty of `self`
+    /// is kept, fades running on either channel are stopped.
+    ///
+    /// The two channels are a pair from then on: their duty can only be
+    /// changed with [`Channel::set_complementary_duty`]. Everything else
+    /// changing the duty, start point or timer of either channel (setting the
+    /// duty, fades, [`Channel::pulse_n`], reconfiguring or rebinding it) fails
+    /// with [`Error::Paired`] until [`Channel::clear_deadtime`] is called or
+    /// either channel is dropped. The timer can't be reconfigured while the
+    /// channels borrow it, so the dead times hold for its frequency.
+    pub fn set_deadtime<O2: OutputPin>(
+        &mut self,
+        low_side: &mut Channel<'a, S, O2>,
+        rising_ns: u32,
+        falling_ns: u32,
+    ) -> Result<(), Error>
+    where
+        Channel<'a, S, O2>: ChannelHW<O2>,
+    {
+        let timer = self.timer.ok_or(Error::Channel)?;
+        match low_side.timer {
+            Some(low_timer) if low_timer.get_number() == timer.get_number() => {}
+            Some(_) => return Err(Error::Deadtime),
+            None => return Err(Error::Channel),
+        }
+        match (self.pairing(), low_side.pairing()) {
+            (None, None) => {
+                if !super::pair_channels::<S>(self.number, low_side.number) {
+                    return Err(Error::Paired);
+                }
             }
-        } else {
-            return Err(Error::Channel);
+            (Some(pair), Some(_)) if pair.high_side && pair.partner == low_side.number => {}
+            _ => return Err(Error::Paired),
         }
 
-        let duty_range = 2u32.pow(duty_exp);
-        let duty_value = (duty_range * duty_pct as u32) as u32 / 100;
+        forget_fades(S::IS_HS, self.number);
+        forget_fades(S::IS_HS, low_side.number);
 
-        if duty_pct > 100u8 {
-            // duty_pct greater than 100%
+        self.deadtime.set(Some(Deadtime {
+            partner: low_side.number,
+            high_side: true,
+            rising_ns,
+            falling_ns,
+        }));
+        low_side.deadtime.set(Some(Deadtime {
+            partner: self.number,
+            high_side: false,
+            rising_ns,
+            falling_ns,
+        }));
+
+        let duty = current_duty_hw(S::IS_HS, self.number);
+        self.apply_complementary_duty(low_side, duty)
+    }
+
+    /// Set the duty of a complementary pair set up with
+    /// [`Channel::set_deadtime`] to `duty_pct`, keeping its dead times
+    ///
+    /// `self` has to be the high side and `low_side` the low side of the
+    /// pair, otherwise [`Error::Deadtime`] is returned. The two channels latch
+    /// their new duty on the next period boundary independently, so they are
+    /// updated in the order that keeps the outputs from overlapping if the
+    /// writes straddle a boundary.
+    pub fn set_complementary_duty<O2: OutputPin>(
+        &mut self,
+        low_side: &mut Channel<'a, S, O2>,
+        duty_pct: u8,
+    ) -> Result<(), Error>
+    where
+        Channel<'a, S, O2>: ChannelHW<O2>,
+    {
+        if duty_pct > 100 {
             return Err(Error::Duty);
         }
+        let duty = duty_pct as u32 * self.max_duty()? / 100;
 
-        self.set_duty_hw(duty_value);
+        self.apply_complementary_duty(low_side, duty)
+    }
+
+    /// Split up a complementary pair set up with [`Channel::set_deadtime`]
+    ///
+    /// Both channels keep their current duty and start point, so the dead
+    /// times hold until the duty of either channel is changed. Fails with
+    /// [`Error::Deadtime`] if `self` isn't the high side and `low_side` the
+    /// low side of a pair.
+    pub fn clear_deadtime<O2: OutputPin>(
+        &mut self,
+        low_side: &mut Channel<'a, S, O2>,
+    ) -> Result<(), Error>
+    where
+        Channel<'a, S, O2>: ChannelHW<O2>,
+    {
+        self.high_side_pairing(low_side)?;
+
+        super::unpair_channels::<S>(self.number, low_side.number);
+        self.deadtime.set(None);
+        low_side.deadtime.set(None);
 
         Ok(())
     }
 
-    /// Start a duty fade from one % to another.
+    /// The pairing of `self` as the high side with `low_side`
+    fn high_side_pairing<O2: OutputPin>(
+        &self,
+        low_side: &Channel<'a, S, O2>,
+    ) -> Result<Deadtime, Error> {
+        match (self.pairing(), low_side.pairing()) {
+            (Some(pair), Some(_)) if pair.high_side && pair.partner == low_side.number => Ok(pair),
+            _ => Err(Error::Deadtime),
+        }
+    }
+
+    /// Output the raw duty `duty` on the high side of a complementary pair and
+    /// the rest of the period minus the dead times on the low side
+    fn apply_complementary_duty<O2: OutputPin>(
+        &self,
+        low_side: &Channel<'a, S, O2>,
+        duty: u32,
+    ) -> Result<(), Error>
+    where
+        Channel<'a, S, O2>: ChannelHW<O2>,
+    {
+        let pair = self.high_side_pairing(low_side)?;
+        let timer = self.timer.ok_or(Error::Channel)?;
+        let max_count = self.max_duty()?;
+
+        let counts_per_sec = timer.frequency().raw() as u64 * max_count as u64;
+        let to_counts = |ns: u32| -> u32 {
+            let counts = (ns as u64 * counts_per_sec + 999_999_999) / 1_000_000_000;
+            counts.min(max_count as u64) as u32
+        };
+        let rising = to_counts(pair.rising_ns);
+        let falling = to_counts(pair.falling_ns);
+
+        let low_start = duty + falling;
+        let (low_duty, low_start) = match max_count.checked_sub(low_start + rising) {
+            Some(low_duty) if low_duty > 0 && low_start < max_count => (low_duty, low_start),
+            _ => (0, 0),
+        };
+
+        let set_high = || {
+            self.set_hpoint_hw(0);
+            self.set_duty_hw(duty);
+            self.set_output_level(static_level(duty, max_count));
+        };
+        let set_low = || {
+            low_side.set_hpoint_hw(low_start);
+            low_side.set_duty_hw(low_duty);
+            low_side.set_output_level(static_level(low_duty, max_count));
+        };
+
+        if duty > self.get_duty_hw() {
+            // shrink the low side before the high side grows into it
+            set_low();
+            set_high();
+        } else {
+            set_high();
+            set_low();
+        }
+
+        Ok(())
+    }
+
+    /// Gate the output of the channel, without touching its duty
     ///
-    /// There's a constraint on the combination of timer frequency, timer PWM
-    /// duty resolution (the bit count), the fade "range" (abs(start-end)), and
-    /// the duration:
+    /// While disabled the output is held low, also at 100% duty; the pin
+    /// isn't released to the GPIO. The PWM generator has no counter of its own, it compares
+    /// the duty against the counter of the bound timer, which keeps running,
+    /// so re-enabling the output continues in phase with the timer (and all
+    /// other channels on it). Enabling again restores the static level for
+    /// 0% and 100% duty. Setting a new duty or starting a fade enables the
+    /// output.
+    pub fn output_enable(&mut self, enable: bool) -> Result<(), Error> {
+        let max_duty = self.max_duty()?;
+
+        if enable {
+            self.set_output_level(static_level(self.get_duty_hw(), max_duty));
+        } else {
+            self.output_enabled.set(false);
+            self.set_static_level_hw(Some(false));
+        }
+
+        Ok(())
+    }
+
+    /// Release `timer` from reset and enable the output once the staged duty
+    /// is latched, see
+    /// [`LEDC::start_output_atomic`](super::LEDC::start_output_atomic)
+    pub(crate) fn start_with_timer_hw(&mut self, timer: &dyn TimerIFace<S>) -> Result<(), Error> {
+        self.check_unpaired()?;
+        if S::IS_HS {
+            // the output enable isn't latched at a period boundary
+            return Err(Error::Unsupported);
+        }
+        let number = timer.get_number();
+        match self.timer {
+            Some(bound) if timer.is_configured() && bound.get_number() == number => {}
+            _ => return Err(Error::TimerMismatch),
+        }
+        let max_duty = self.max_duty()?;
+        let duty = self.get_duty_hw();
+
+        // timer overflow interrupt, the high speed timers come first on the ESP32
+        let ovf_bit = number as u32 + if cfg!(esp32) { 4 } else { 0 };
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+        critical_section::with(|_| {
+            self.set_static_level_hw(Some(false));
+            self.set_duty_hw(duty);
+
+            ledc.int_clr.write(|w| unsafe { w.bits(1 << ovf_bit) });
+            super::timer::commit_hw::<S>(&[number]);
+        });
+
+        // every poll takes at least one APB clock cycle (at most 80MHz), so
+        // this waits for at least two periods
+        let max_polls = 2 * 80_000_000 / timer.frequency().raw().max(1);
+        let overflowed = (0..max_polls).any(|_| ledc.int_raw.read().bits() & (1 << ovf_bit) != 0);
+        if !overflowed {
+            return Err(Error::Timeout);
+        }
+
+        // latched at the next period boundary, so the PWM signal starts with a
+        // complete period even if this is delayed
+        self.set_output_level(static_level(duty, max_duty));
+
+        Ok(())
+    }
+
+    /// Jump to `start_duty_pct` and fade to `end_duty_pct` from there
     ///
-    /// frequency * duration / ((1<<bit_count) * abs(start-end)) < 1024
+    /// The start duty and the fade are written before a single parameter
+    /// update latches both, and the PWM output (if it was held at a static
+    /// level) is only enabled afterwards, so no period at the previous duty is
+    /// output in between. The same constraints as for
+    /// [`ChannelIFace::start_duty_fade`] apply.
+    pub fn jump_then_fade(
+        &mut self,
+        start_duty_pct: u8,
+        end_duty_pct: u8,
+        duration_ms: u16,
+    ) -> Result<(), Error> {
+        let fade = self.fade_parameters(start_duty_pct, end_duty_pct, duration_ms)?;
+
+        self.start_duty_fade_hw(
+            fade.start_duty,
+            fade.duty_inc,
+            fade.duty_steps,
+            fade.cycles_per_step,
+            fade.duty_per_cycle,
+        );
+        self.set_output_level(None);
+
+        Ok(())
+    }
+
+    /// Start a duty fade given directly in HW steps
     ///
-    /// Small percentage changes, long durations, coarse PWM resolutions (that
-    /// is, low bit counts), and high timer frequencies will all be more likely
-    /// to fail this requirement.  If it does fail, this function will return
-    /// an error Result.
-    fn start_duty_fade(
+    /// The raw duty changes from `start_duty` towards `target_duty` by
+    /// `step_size` every `cycles_per_step` PWM periods, both 1 to 1023. The
+    /// fade takes `|target_duty - start_duty| / step_size` steps (1 to 1023),
+    /// if the difference isn't a multiple of `step_size` it ends short of
+    /// `target_duty`. [`ChannelIFace::start_duty_fade`] calculates these
+    /// parameters from a duration instead.
+    pub fn start_duty_fade_steps(
+        &mut self,
+        start_duty: u32,
+        target_duty: u32,
+        step_size: u16,
+        cycles_per_step: u16,
+    ) -> Result<(), Error> {
+        self.check_unpaired()?;
+        let max_duty = self.max_duty()?;
+        if start_duty > max_duty || target_duty > max_duty {
+            return Err(Error::DutyOutOfRange);
+        }
+        if !(1..=1023).contains(&cycles_per_step) {
+            return Err(Error::Fade(FadeError::Duration));
+        }
+        if !(1..=1023).contains(&step_size) {
+            return Err(Error::Fade(FadeError::DutyRange));
+        }
+        let duty_steps = match u16::try_from(target_duty.abs_diff(start_duty) / step_size as u32) {
+            Ok(steps) if (1..=1023).contains(&steps) => steps,
+            _ => return Err(Error::Fade(FadeError::DutyRange)),
+        };
+
+        self.start_duty_fade_hw(
+            start_duty,
+            target_duty > start_duty,
+            duty_steps,
+            cycles_per_step,
+            step_size,
+        );
+        self.set_output_level(None);
+
+        Ok(())
+    }
+
+    /// Output exactly `count` PWM pulses at the current duty, then hold the
+    /// output low
+    ///
+    /// The pulses are counted from the next period boundary. Up to 1023
+    /// pulses at a raw duty of up to 1023 (the width of the fade step size)
+    /// are a single-step duty fade to 0 taking `count` periods, so the output
+    /// stops at a period boundary by the hardware, independent of interrupt
+    /// latency. [`ChannelIFace::is_duty_fade_running`] returns `false` once
+    /// all pulses were output, the duty is 0 afterwards.
+    ///
+    /// Longer pulse trains and higher duties are counted in software from
+    /// the overflow interrupt, see [`Channel::enable_overflow_interrupt`],
+    /// triggered every `n` periods for the largest `n` up to 1024 which
+    /// divides `count - 1`, in the worst case every period. The interrupt
+    /// before the last pulse holds the output low from the end of the next
+    /// period, so [`handle_pulse_interrupt`] has to run within one PWM period
+    /// of it. Without the `async` feature it has to be called from the
+    /// application's `LEDC` interrupt handler, with it the HAL's interrupt
+    /// handler does this. The `LEDC` interrupt needs to be enabled. The duty
+    /// is kept and [`Channel::is_pulse_running`] returns `false` once the
+    /// output is held low. This fails with [`Error::Unsupported`] on the
+    /// ESP32, which has no per-channel overflow counter.
+    ///
+    /// A `count` of 0 is rejected with [`Error::PulseCount`]. A raw duty of 0
+    /// or 100%, which don't output any pulses, and a single pulse with a raw
+    /// duty above 1023 are rejected with [`Error::DutyOutOfRange`].
+    pub fn pulse_n(&mut self, count: u32) -> Result<(), Error> {
+        self.check_unpaired()?;
+        let max_duty = self.max_duty()?;
+        if count == 0 {
+            return Err(Error::PulseCount);
+        }
+        let duty = self.get_duty_hw();
+        if static_level(duty, max_duty).is_some() {
+            return Err(Error::DutyOutOfRange);
+        }
+
+        if count <= 1023 && duty <= 1023 {
+            self.start_duty_fade_hw(duty, false, 1, count as u16, duty as u16);
+            self.set_output_level(None);
+            return Ok(());
+        }
+
+        if count == 1 {
+            return Err(Error::DutyOutOfRange);
+        }
+
+        #[cfg(esp32)]
+        {
+            Err(Error::Unsupported)
+        }
+        #[cfg(not(esp32))]
+        {
+            let chunk = pulse_chunk(count - 1);
+            let bit = overflow_int_bit_hw(self.number);
+
+            critical_section::with(|cs| {
+                PULSES.borrow_ref_mut(cs)[self.number as usize] = Some(Pulses {
+                    number: self.number,
+                    remaining: (count - 1) / chunk as u32,
+                });
+
+                self.set_output_level(None);
+                set_overflow_count_hw(self.number, Some(chunk));
+
+                let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+                ledc.int_clr.write(|w| unsafe { w.bits(1 << bit) });
+                ledc.int_ena
+                    .modify(|r, w| unsafe { w.bits(r.bits() | (1 << bit)) });
+            });
+
+            Ok(())
+        }
+    }
+
+    /// Whether pulses started with [`Channel::pulse_n`] are still being
+    /// output
+    pub fn is_pulse_running(&self) -> bool {
+        #[cfg(not(esp32))]
+        if critical_section::with(|cs| PULSES.borrow_ref(cs)[self.number as usize].is_some()) {
+            return true;
+        }
+
+        self.is_duty_fade_running()
+    }
+
+    /// Enable the output, holding it at `level` instead of outputting the PWM
+    /// signal if `Some`
+    fn set_output_level(&self, level: Option<bool>) {
+        self.output_enabled.set(true);
+        self.set_static_level_hw(level);
+    }
+
+    /// Largest raw duty value for the resolution of the channel's timer
+    fn max_duty(&self) -> Result<u32, Error> {
+        match self.timer {
+            Some(timer) => match timer.get_duty() {
+                Some(duty) => Ok(duty.max_count()),
+                None => Err(Error::Timer),
+            },
+            None => Err(Error::Channel),
+        }
+    }
+
+    /// Calculate the HW parameters of a duty fade, see
+    /// [`ChannelIFace::start_duty_fade`]
+    pub(crate) fn fade_parameters(
         &self,
         start_duty_pct: u8,
         end_duty_pct: u8,
         duration_ms: u16,
-    ) -> Result<(), Error> {
-        let duty_exp;
-        let frequency;
+    ) -> Result<FadeParameters, Error> {
         if start_duty_pct > 100u8 {
             return Err(Error::Fade(FadeError::StartDuty));
         }
         if end_duty_pct > 100u8 {
             return Err(Error::Fade(FadeError::EndDuty));
         }
-        if let Some(timer) = self.timer {
-            if let Some(timer_duty) = timer.get_duty() {
-                if timer.get_frequency() > 0 {
-                    duty_exp = timer_duty as u32;
-                    frequency = timer.get_frequency();
-                } else {
-                    return Err(Error::Timer);
-                }
-            } else {
-                return Err(Error::Timer);
-            }
-        } else {
-            return Err(Error::Channel);
-        }
 
-        let duty_range = (1u32 << duty_exp) - 1;
+        let duty_range = self.max_duty()? - 1;
         let start_duty_value = (duty_range * start_duty_pct as u32) as u32 / 100;
         let end_duty_value = (duty_range * end_duty_pct as u32) as u32 / 100;
 
+        self.fade_parameters_raw(start_duty_value, end_duty_value, duration_ms)
+    }
+
+    /// Calculate the HW parameters of a duty fade between two raw duty values
+    pub(crate) fn fade_parameters_raw(
+        &self,
+        start_duty_value: u32,
+        end_duty_value: u32,
+        duration_ms: u16,
+    ) -> Result<FadeParameters, Error> {
+        self.check_unpaired()?;
+
+        let frequency = match self.timer {
+            Some(timer) if timer.get_duty().is_some() && timer.frequency().raw() > 0 => {
+                timer.frequency().raw()
+            }
+            Some(_) => return Err(Error::Timer),
+            None => return Err(Error::Channel),
+        };
+
         // NB: since we do the multiplication first here, there's no loss of
         // precision from using milliseconds instead of (e.g.) nanoseconds.
         let pwm_cycles = (duration_ms as u32) * frequency / 1000;
 
         let abs_duty_diff = end_duty_value.abs_diff(start_duty_value);
+        if abs_duty_diff == 0 {
+            // a single step not changing the duty, just takes the duration
+            return match u16::try_from(pwm_cycles) {
+                Ok(cycles_per_step) if cycles_per_step <= 1023 => Ok(FadeParameters {
+                    start_duty: start_duty_value,
+                    duty_inc: true,
+                    duty_steps: 1,
+                    cycles_per_step,
+                    duty_per_cycle: 0,
+                }),
+                _ => Err(Error::Fade(FadeError::Duration)),
+            };
+        }
         let duty_steps: u32 = u16::try_from(abs_duty_diff).unwrap_or(65535).into();
         // This conversion may fail if duration_ms is too big, and if either
         // duty_steps gets truncated, or the fade is over a short range of duty
@@ -243,90 +797,321 @@ where
                 }
             })?;
         // This can't fail unless abs_duty_diff is bigger than 65536*65535-1,
-        // and so duty_steps gets truncated.  But that requires duty_exp to be
+        // and so duty_steps gets truncated.  But that requires duty.bits() to be
         // at least 32, and the hardware only supports up to 20.  Still, handle
         // it in case something changes in the future.
         let duty_per_cycle: u16 = (abs_duty_diff / duty_steps)
             .try_into()
             .map_err(|_| Error::Fade(FadeError::DutyRange))?;
 
-        self.start_duty_fade_hw(
-            start_duty_value,
-            end_duty_value > start_duty_value,
-            duty_steps.try_into().unwrap(),
+        Ok(FadeParameters {
+            start_duty: start_duty_value,
+            duty_inc: end_duty_value > start_duty_value,
+            duty_steps: duty_steps.try_into().unwrap(),
             cycles_per_step,
             duty_per_cycle,
-        );
-
-        Ok(())
-    }
-
-    fn is_duty_fade_running(&self) -> bool {
-        self.is_duty_fade_running_hw()
+        })
     }
 }
 
 #[cfg(esp32)]
-/// Macro to configure channel parameters in hw
-macro_rules! set_channel {
-    ($self: ident, $speed: ident, $num: literal, $timer_number: ident) => {{
-        paste! {
-            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
-            ledc.[<$speed sch $num _hpoint>]
-                .write(|w| unsafe { w.[<hpoint>]().bits(0x0) });
-            ledc.[<$speed sch $num _conf0>].modify(|_, w| unsafe {
-                w.[<sig_out_en>]()
-                    .set_bit()
-                    .[<timer_sel>]()
-                    .bits($timer_number)
-            });
+impl<'a, O: OutputPin> Channel<'a, HighSpeed, O> {
+    /// Move the channel to a low speed timer
+    ///
+    /// The high speed and low speed channels are separate blocks, so this
+    /// stops the high speed channel and returns the low speed channel with the
+    /// same number, bound to `timer` with the same duty % and output pin
+    /// configuration. The output pin is routed to the low speed channel.
+    ///
+    /// Returns the channel unchanged if `timer` isn't configured or the low
+    /// speed channel with the same number is already in use, in that case
+    /// take a free low speed channel via [`super::LEDC::get_channel`] instead.
+    pub fn into_low_speed(
+        self,
+        timer: &'a dyn TimerIFace<LowSpeed>,
+    ) -> Result<Channel<'a, LowSpeed, O>, Self> {
+        if !timer.is_configured()
+            || self.check_unpaired().is_err()
+            || !super::take_channel::<LowSpeed>(self.number)
+        {
+            return Err(self);
         }
-        start_duty_without_fading!($self, $speed, $num);
-    }};
-}
 
-#[cfg(not(esp32))]
-/// Macro to configure channel parameters in hw
-macro_rules! set_channel {
-    ($self: ident, $speed: ident, $num: literal, $timer_number: ident) => {{
-        paste! {
-            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
-            ledc.[<ch $num _hpoint>]
-                .write(|w| unsafe { w.[<hpoint>]().bits(0x0) });
-            ledc.[<ch $num _conf0>].modify(|_, w| unsafe {
-                w.[<sig_out_en>]()
-                    .set_bit()
-                    .[<timer_sel>]()
-                    .bits($timer_number)
-            });
-        }
-        start_duty_without_fading!($self, $num);
-    }};
+        let duty_pct = match self.max_duty() {
+            Ok(max_duty) => ((self.get_duty_hw() as u64 * 100 + max_duty as u64 / 2)
+                / max_duty as u64)
+                .min(100) as u8,
+            Err(_) => 0,
+        };
+        let pin_config = if crate::gpio::is_open_drain(self.output_pin.number()) {
+            config::PinConfig::OpenDrain
+        } else {
+            config::PinConfig::PushPull
+        };
+
+        self.set_static_level_hw(Some(false));
+
+        // move the pin out without running `Drop`, the HS channel is released
+        // by hand
+        let this = core::mem::ManuallyDrop::new(self);
+        let output_pin = unsafe { core::ptr::read(&this.output_pin) };
+        super::release_channel::<HighSpeed>(this.number);
+
+        let mut channel = Channel {
+            timer: None,
+            number: this.number,
+            output_pin,
+            output_enabled: Cell::new(false),
+            deadtime: Cell::new(None),
+        };
+        // can't fail, the timer is configured and the duty % is in range
+        channel
+            .configure(config::Config {
+                timer,
+                duty_pct,
+                pin_config,
+            })
+            .ok();
+
+        Ok(channel)
+    }
 }
 
-#[cfg(esp32)]
-/// Macro to start duty cycle, without fading
-macro_rules! start_duty_without_fading {
-    ($self: ident, $speed: ident, $num: literal) => {
-        paste! {
-            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
-            ledc.[<$speed sch $num _conf1>].write(|w| unsafe {
-                w.[<duty_start>]()
-                    .set_bit()
-                    .[<duty_inc>]()
-                    .set_bit()
-                    .[<duty_num>]()
-                    .bits(0x1)
-                    .[<duty_cycle>]()
-                    .bits(0x1)
-                    .[<duty_scale>]()
-                    .bits(0x0)
-                });
+impl<'a, S: TimerSpeed, O: OutputPin> Drop for Channel<'a, S, O> {
+    fn drop(&mut self) {
+        // the channel can be handed out again, so the interrupt handler must
+        // not continue a fade on it, and it isn't part of a pair anymore
+        forget_fades(S::IS_HS, self.number);
+        if let Some(pair) = self.pairing() {
+            super::unpair_channels::<S>(self.number, pair.partner);
         }
-    };
+        super::release_channel::<S>(self.number);
+        super::release_pin(self.output_pin.number());
+    }
 }
 
-#[cfg(any(esp32c6, esp32h2))]
+impl<'a, S: TimerSpeed, O: OutputPin> crate::interrupt::InterruptClearable for Channel<'a, S, O>
+where
+    Channel<'a, S, O>: ChannelHW<O>,
+{
+    /// Clear the duty fade end interrupt and, except on ESP32, the overflow
+    /// counter interrupt of the channel
+    fn clear_interrupts(&mut self) {
+        #[cfg(esp32)]
+        let bits = 1 << self.duty_fade_int_bit();
+        #[cfg(not(esp32))]
+        let bits = (1 << self.duty_fade_int_bit()) | (1 << overflow_int_bit_hw(self.number));
+
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+        ledc.int_clr.write(|w| unsafe { w.bits(bits) });
+    }
+}
+
+impl<'a, S: TimerSpeed, O: OutputPin> ChannelIFace<'a, S, O> for Channel<'a, S, O>
+where
+    Channel<'a, S, O>: ChannelHW<O>,
+{
+    /// Configure channel
+    fn configure(&mut self, config: config::Config<'a, S>) -> Result<(), Error> {
+        self.check_unpaired()?;
+        self.timer = Some(config.timer);
+
+        self.set_duty(config.duty_pct)?;
+        self.configure_hw_with_pin_config(config.pin_config)?;
+        // configuring the HW enables the PWM output again
+        self.set_output_level(static_level(self.get_duty_hw(), self.max_duty()?));
+
+        Ok(())
+    }
+
+    /// Set duty % of channel
+    fn set_duty(&self, duty_pct: u8) -> Result<(), Error> {
+        if duty_pct > 100u8 {
+            // duty_pct greater than 100%
+            return Err(Error::Duty);
+        }
+
+        let duty_range = self.max_duty()?;
+        let duty_value = duty_range * duty_pct as u32 / 100;
+
+        self.set_raw_duty(duty_value)
+    }
+
+    /// Set the raw duty of the channel, as a count of timer ticks.
+    ///
+    /// `count` can be anything from 0 (always low) to 2^resolution (always
+    /// high), larger values are rejected with [`Error::DutyOutOfRange`]
+    /// instead of spilling into the neighboring register fields.
+    ///
+    /// For 0 and 2^resolution the output is held at a static level instead
+    /// of generating a degenerate PWM signal, which would glitch once per
+    /// period.
+    fn set_raw_duty(&self, count: u32) -> Result<(), Error> {
+        self.check_unpaired()?;
+        let max_count = self.max_duty()?;
+        if count > max_count {
+            return Err(Error::DutyOutOfRange);
+        }
+
+        self.set_duty_hw(count);
+        self.set_output_level(static_level(count, max_count));
+
+        Ok(())
+    }
+
+    /// Set the raw channel duty, with the output going high at the counter
+    /// value `start_point` and low `duty` counts later.
+    ///
+    /// Both values are applied together at the next period boundary, so
+    /// shifting the high phase of the output doesn't produce a spike in the
+    /// middle of a period. `duty` can be at most 2^resolution, otherwise
+    /// [`Error::DutyOutOfRange`] is returned, and `start_point` has to be
+    /// below it, otherwise [`Error::StartPoint`] is returned.
+    fn set_duty_with_start(&mut self, duty: u32, start_point: u32) -> Result<(), Error> {
+        self.check_unpaired()?;
+        let max_count = self.max_duty()?;
+
+        if duty > max_count {
+            return Err(Error::DutyOutOfRange);
+        }
+        if start_point >= max_count {
+            return Err(Error::StartPoint);
+        }
+
+        self.set_hpoint_hw(start_point);
+        self.set_duty_hw(duty);
+        self.set_output_level(None);
+
+        Ok(())
+    }
+
+    /// Bind the channel to a different timer.
+    ///
+    /// The timer selection is committed together with a parameter update, so
+    /// the channel switches over on the next period boundary. The raw duty
+    /// value is kept as is; if the new timer has a different duty resolution
+    /// call [`Self::set_duty`] afterwards.
+    fn set_timer(&mut self, timer: &'a dyn TimerIFace<S>) -> Result<(), Error> {
+        self.check_unpaired()?;
+        if self.timer.is_none() {
+            return Err(Error::Channel);
+        }
+        if !timer.is_configured() {
+            return Err(Error::Timer);
+        }
+
+        self.set_timer_hw(timer.get_number() as u8);
+        self.timer = Some(timer);
+
+        Ok(())
+    }
+
+    /// Start a duty fade from one % to another.
+    ///
+    /// There's a constraint on the combination of timer frequency, timer PWM
+    /// duty resolution (the bit count), the fade "range" (abs(start-end)), and
+    /// the duration:
+    ///
+    /// frequency * duration / ((1<<bit_count) * abs(start-end)) < 1024
+    ///
+    /// Small percentage changes, long durations, coarse PWM resolutions (that
+    /// is, low bit counts), and high timer frequencies will all be more likely
+    /// to fail this requirement.  If it does fail, this function will return
+    /// an error Result.
+    fn start_duty_fade(
+        &self,
+        start_duty_pct: u8,
+        end_duty_pct: u8,
+        duration_ms: u16,
+    ) -> Result<(), Error> {
+        let fade = self.fade_parameters(start_duty_pct, end_duty_pct, duration_ms)?;
+
+        self.set_output_level(None);
+        self.start_duty_fade_hw(
+            fade.start_duty,
+            fade.duty_inc,
+            fade.duty_steps,
+            fade.cycles_per_step,
+            fade.duty_per_cycle,
+        );
+
+        Ok(())
+    }
+
+    fn is_duty_fade_running(&self) -> bool {
+        self.is_duty_fade_running_hw()
+    }
+
+    /// Check whether the output of the channel is enabled. This is `false` if
+    /// the channel isn't configured yet or the output was disabled with
+    /// [`Channel::output_enable`], and stays `true` while the output is held
+    /// at a static level for 0% or 100% duty.
+    fn is_enabled(&self) -> bool {
+        self.timer.is_some() && self.output_enabled.get()
+    }
+}
+
+#[cfg(esp32)]
+/// Macro to configure channel parameters in hw
+macro_rules! set_channel {
+    ($self: ident, $speed: ident, $num: literal, $timer_number: ident) => {{
+        paste! {
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc.[<$speed sch $num _hpoint>]
+                .write(|w| unsafe { w.[<hpoint>]().bits(0x0) });
+            ledc.[<$speed sch $num _conf0>].modify(|_, w| unsafe {
+                w.sig_out_en()
+                    .set_bit()
+                    .[<timer_sel>]()
+                    .bits($timer_number)
+            });
+        }
+        start_duty_without_fading!($self, $speed, $num);
+    }};
+}
+
+#[cfg(not(esp32))]
+/// Macro to configure channel parameters in hw
+macro_rules! set_channel {
+    ($self: ident, $speed: ident, $num: literal, $timer_number: ident) => {{
+        paste! {
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc.[<ch $num _hpoint>]
+                .write(|w| unsafe { w.[<hpoint>]().bits(0x0) });
+            ledc.[<ch $num _conf0>].modify(|_, w| unsafe {
+                w.sig_out_en()
+                    .set_bit()
+                    .[<timer_sel>]()
+                    .bits($timer_number)
+            });
+        }
+        start_duty_without_fading!($self, $num);
+    }};
+}
+
+#[cfg(esp32)]
+/// Macro to start duty cycle, without fading
+macro_rules! start_duty_without_fading {
+    ($self: ident, $speed: ident, $num: literal) => {
+        paste! {
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc.[<$speed sch $num _conf1>].write(|w| unsafe {
+                w.[<duty_start>]()
+                    .set_bit()
+                    .[<duty_inc>]()
+                    .set_bit()
+                    .[<duty_num>]()
+                    .bits(0x1)
+                    .[<duty_cycle>]()
+                    .bits(0x1)
+                    .[<duty_scale>]()
+                    .bits(0x0)
+                });
+        }
+    };
+}
+
+#[cfg(any(esp32c6, esp32h2))]
 /// Macro to start duty cycle, without fading
 macro_rules! start_duty_without_fading {
     ($self: ident, $num: literal) => {
@@ -448,6 +1233,112 @@ macro_rules! start_duty_fade {
     };
 }
 
+#[cfg(not(esp32))]
+/// Macro to configure the overflow counter of a channel, `None` disables it
+macro_rules! set_overflow_count {
+    ($num: literal, $cycles: ident) => {{
+        paste! {
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc.[<ch $num _conf0>].modify(|_, w| unsafe {
+                w.ovf_num()
+                    .bits($cycles.unwrap_or(1) - 1)
+                    .ovf_cnt_en()
+                    .bit($cycles.is_some())
+                    .ovf_cnt_reset()
+                    .set_bit()
+                    .para_up()
+                    .set_bit()
+            });
+        }
+    }};
+}
+
+#[cfg(esp32)]
+/// Macro to select the timer of a channel in hw
+macro_rules! set_timer_sel {
+    ($self: ident, $speed: ident, $num: literal, $timer_number: ident) => {{
+        paste! {
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc.[<$speed sch $num _conf0>]
+                .modify(|_, w| unsafe { w.[<timer_sel>]().bits($timer_number) });
+        }
+        update_channel!($self, $speed, $num);
+    }};
+}
+
+#[cfg(not(esp32))]
+/// Macro to select the timer of a channel in hw
+macro_rules! set_timer_sel {
+    ($self: ident, $speed: ident, $num: literal, $timer_number: ident) => {{
+        paste! {
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc.[<ch $num _conf0>]
+                .modify(|_, w| unsafe { w.[<timer_sel>]().bits($timer_number) });
+        }
+        update_channel!($self, $speed, $num);
+    }};
+}
+
+#[cfg(esp32)]
+/// Macro to hold the output at a static level, or enable the PWM output
+macro_rules! set_static_level {
+    ($self: ident, $speed: ident, $num: literal, $level: ident) => {{
+        paste! {
+            unsafe { &*crate::peripherals::LEDC::PTR }
+                .[<$speed sch $num _conf0>]
+                .modify(|_, w| {
+                    w.sig_out_en()
+                        .bit($level.is_none())
+                        .idle_lv()
+                        .bit($level.unwrap_or(false))
+                });
+        }
+        update_channel!($self, $speed, $num);
+    }};
+}
+
+#[cfg(not(esp32))]
+/// Macro to hold the output at a static level, or enable the PWM output
+macro_rules! set_static_level {
+    ($self: ident, $speed: ident, $num: literal, $level: ident) => {{
+        paste! {
+            unsafe { &*crate::peripherals::LEDC::PTR }
+                .[<ch $num _conf0>]
+                .modify(|_, w| {
+                    w.sig_out_en()
+                        .bit($level.is_none())
+                        .idle_lv()
+                        .bit($level.unwrap_or(false))
+                });
+        }
+        update_channel!($self, $speed, $num);
+    }};
+}
+
+#[cfg(esp32)]
+/// Macro to set the hpoint in hw, it's applied with the next duty update
+macro_rules! set_hpoint {
+    ($self: ident, $speed: ident, $num: literal, $hpoint: ident) => {
+        paste! {
+            unsafe { &*crate::peripherals::LEDC::PTR }
+                .[<$speed sch $num _hpoint>]
+                .write(|w| unsafe { w.[<hpoint>]().bits($hpoint) });
+        }
+    };
+}
+
+#[cfg(not(esp32))]
+/// Macro to set the hpoint in hw, it's applied with the next duty update
+macro_rules! set_hpoint {
+    ($self: ident, $speed: ident, $num: literal, $hpoint: ident) => {
+        paste! {
+            unsafe { &*crate::peripherals::LEDC::PTR }
+                .[<ch $num _hpoint>]
+                .write(|w| unsafe { w.[<hpoint>]().bits($hpoint) });
+        }
+    };
+}
+
 #[cfg(esp32)]
 /// Macro to set duty parameters in hw
 macro_rules! set_duty {
@@ -479,58 +1370,146 @@ macro_rules! set_duty {
 }
 
 #[cfg(esp32)]
-/// Macro to set duty parameters in hw for a fade
-macro_rules! set_duty_fade {
-    ($self: ident, $speed: ident, $num: literal, $start_duty: ident, $duty_inc: ident, $duty_steps: ident, $cycles_per_step: ident, $duty_per_cycle: ident) => {{
+/// Macro to read back the duty from hw
+macro_rules! get_duty {
+    ($self: ident, $speed: ident, $num: literal) => {
         paste! {
-            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
-            ledc
+            unsafe { &*crate::peripherals::LEDC::PTR }
                 .[<$speed sch $num _duty>]
-                .write(|w| unsafe { w.[<duty>]().bits($start_duty << 4) });
-            ledc
-                .[<int_clr>]
-                .write(|w| { w.[<duty_chng_end_ $speed sch $num _int_clr>]().set_bit() });
+                .read()
+                .[<duty>]()
+                .bits()
+                >> 4
         }
-        start_duty_fade!(
-            $self,
-            $speed,
-            $num,
-            $duty_inc,
-            $duty_steps,
-            $cycles_per_step,
-            $duty_per_cycle
-        );
-        update_channel!($self, $speed, $num);
-    }};
+    };
 }
 
-#[cfg(esp32c3)]
-/// Macro to set duty parameters in hw for a fade
-macro_rules! set_duty_fade {
-    ($self: ident, $speed: ident, $num: literal, $start_duty: ident, $duty_inc: ident, $duty_steps: ident, $cycles_per_step: ident, $duty_per_cycle: ident) => {{
+#[cfg(not(esp32))]
+/// Macro to read back the duty from hw
+macro_rules! get_duty {
+    ($self: ident, $speed: ident, $num: literal) => {
         paste! {
-            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
-            ledc
+            unsafe { &*crate::peripherals::LEDC::PTR }
                 .[<ch $num _duty>]
-                .write(|w| unsafe { w.[<duty>]().bits($start_duty << 4) });
-            ledc
-                .[<int_clr>]
-                .write(|w| { w.[<duty_chng_end_ $speed sch $num _int_clr>]().set_bit() });
+                .read()
+                .[<duty>]()
+                .bits()
+                >> 4
         }
-        start_duty_fade!(
-            $self,
-            $num,
-            $duty_inc,
-            $duty_steps,
-            $cycles_per_step,
-            $duty_per_cycle
-        );
-        update_channel!($self, $speed, $num);
-    }};
+    };
 }
 
-#[cfg(not(any(esp32, esp32c3)))]
-/// Macro to set duty parameters in hw for a fade
+#[cfg(esp32)]
+/// Macro to read back whether the PWM output is enabled in hw
+macro_rules! is_output_enabled {
+    ($self: ident, $speed: ident, $num: literal) => {
+        paste! {
+            unsafe { &*crate::peripherals::LEDC::PTR }
+                .[<$speed sch $num _conf0>]
+                .read()
+                .sig_out_en()
+                .bit_is_set()
+        }
+    };
+}
+
+#[cfg(not(esp32))]
+/// Macro to read back whether the PWM output is enabled in hw
+macro_rules! is_output_enabled {
+    ($self: ident, $speed: ident, $num: literal) => {
+        paste! {
+            unsafe { &*crate::peripherals::LEDC::PTR }
+                .[<ch $num _conf0>]
+                .read()
+                .sig_out_en()
+                .bit_is_set()
+        }
+    };
+}
+
+#[cfg(esp32)]
+/// Macro to read the current (possibly fading) duty from hw
+macro_rules! get_duty_r {
+    ($self: ident, $speed: ident, $num: literal) => {
+        paste! {
+            unsafe { &*crate::peripherals::LEDC::PTR }
+                .[<$speed sch $num _duty_r>]
+                .read()
+                .[<duty_r>]()
+                .bits()
+                >> 4
+        }
+    };
+}
+
+#[cfg(not(esp32))]
+/// Macro to read the current (possibly fading) duty from hw
+macro_rules! get_duty_r {
+    ($self: ident, $speed: ident, $num: literal) => {
+        paste! {
+            unsafe { &*crate::peripherals::LEDC::PTR }
+                .[<ch $num _duty_r>]
+                .read()
+                .[<duty_r>]()
+                .bits()
+                >> 4
+        }
+    };
+}
+
+#[cfg(esp32)]
+/// Macro to set duty parameters in hw for a fade
+macro_rules! set_duty_fade {
+    ($self: ident, $speed: ident, $num: literal, $start_duty: ident, $duty_inc: ident, $duty_steps: ident, $cycles_per_step: ident, $duty_per_cycle: ident) => {{
+        paste! {
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc
+                .[<$speed sch $num _duty>]
+                .write(|w| unsafe { w.[<duty>]().bits($start_duty << 4) });
+            ledc
+                .[<int_clr>]
+                .write(|w| { w.[<duty_chng_end_ $speed sch $num _int_clr>]().set_bit() });
+        }
+        start_duty_fade!(
+            $self,
+            $speed,
+            $num,
+            $duty_inc,
+            $duty_steps,
+            $cycles_per_step,
+            $duty_per_cycle
+        );
+        update_channel!($self, $speed, $num);
+    }};
+}
+
+#[cfg(esp32c3)]
+/// Macro to set duty parameters in hw for a fade
+macro_rules! set_duty_fade {
+    ($self: ident, $speed: ident, $num: literal, $start_duty: ident, $duty_inc: ident, $duty_steps: ident, $cycles_per_step: ident, $duty_per_cycle: ident) => {{
+        paste! {
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc
+                .[<ch $num _duty>]
+                .write(|w| unsafe { w.[<duty>]().bits($start_duty << 4) });
+            ledc
+                .[<int_clr>]
+                .write(|w| { w.[<duty_chng_end_ $speed sch $num _int_clr>]().set_bit() });
+        }
+        start_duty_fade!(
+            $self,
+            $num,
+            $duty_inc,
+            $duty_steps,
+            $cycles_per_step,
+            $duty_per_cycle
+        );
+        update_channel!($self, $speed, $num);
+    }};
+}
+
+#[cfg(not(any(esp32, esp32c3)))]
+/// Macro to set duty parameters in hw for a fade
 macro_rules! set_duty_fade {
     ($self: ident, $speed: ident, $num: literal, $start_duty: ident, $duty_inc: ident, $duty_steps: ident, $cycles_per_step: ident, $duty_per_cycle: ident) => {{
         paste! {
@@ -610,6 +1589,106 @@ macro_rules! update_channel {
     };
 }
 
+#[cfg(not(esp32))]
+impl<'a, O: OutputPin> Channel<'a, LowSpeed, O> {
+    /// Trigger an interrupt every `every_n_cycles` PWM periods
+    ///
+    /// The channel counts the overflows of its timer, the interrupt is
+    /// triggered at the end of every `every_n_cycles`th period (1 to 1024),
+    /// so a new duty set from the interrupt handler is applied at the start
+    /// of the next period. Counting starts when this is called. Fails with
+    /// [`Error::Unsupported`] on the ESP32, which has no per-channel overflow
+    /// counter.
+    pub fn enable_overflow_interrupt(&mut self, every_n_cycles: u16) -> Result<(), Error> {
+        if !(1..=1024).contains(&every_n_cycles) {
+            return Err(Error::OverflowCount);
+        }
+
+        set_overflow_count_hw(self.number, Some(every_n_cycles));
+        self.clear_overflow_interrupt();
+
+        let bit = self.overflow_int_bit();
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+        ledc.int_ena
+            .modify(|r, w| unsafe { w.bits(r.bits() | (1 << bit)) });
+
+        Ok(())
+    }
+
+    /// Stop the overflow interrupt enabled with
+    /// [`Channel::enable_overflow_interrupt`]
+    pub fn disable_overflow_interrupt(&mut self) {
+        let bit = self.overflow_int_bit();
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+        ledc.int_ena
+            .modify(|r, w| unsafe { w.bits(r.bits() & !(1 << bit)) });
+
+        set_overflow_count_hw(self.number, None);
+        self.clear_overflow_interrupt();
+    }
+
+    /// Whether the overflow interrupt is pending
+    pub fn is_overflow_interrupt_set(&self) -> bool {
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+        ledc.int_raw.read().bits() & (1 << self.overflow_int_bit()) != 0
+    }
+
+    /// Clear a pending overflow interrupt
+    pub fn clear_overflow_interrupt(&mut self) {
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+        ledc.int_clr
+            .write(|w| unsafe { w.bits(1 << self.overflow_int_bit()) });
+    }
+
+    /// Bit of the overflow counter interrupt in the LEDC interrupt registers
+    fn overflow_int_bit(&self) -> u32 {
+        overflow_int_bit_hw(self.number)
+    }
+}
+
+/// Bit of the overflow counter interrupt of a channel
+#[cfg(not(esp32))]
+fn overflow_int_bit_hw(number: Number) -> u32 {
+    // OVF_CNT_CHn interrupts follow the duty fade end interrupts
+    4 + super::CHANNEL_COUNT as u32 + number as u32
+}
+
+/// Configure the overflow counter of a channel in HW, `None` disables it
+#[cfg(not(esp32))]
+fn set_overflow_count_hw(number: Number, cycles: Option<u16>) {
+    match number {
+        Number::Channel0 => set_overflow_count!(0, cycles),
+        Number::Channel1 => set_overflow_count!(1, cycles),
+        Number::Channel2 => set_overflow_count!(2, cycles),
+        Number::Channel3 => set_overflow_count!(3, cycles),
+        Number::Channel4 => set_overflow_count!(4, cycles),
+        Number::Channel5 => set_overflow_count!(5, cycles),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Number::Channel6 => set_overflow_count!(6, cycles),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Number::Channel7 => set_overflow_count!(7, cycles),
+    }
+}
+
+/// Number of periods between the overflow interrupts counting `periods` in
+/// [`Channel::pulse_n`], the largest divisor of `periods` up to 1024
+#[cfg(not(esp32))]
+fn pulse_chunk(periods: u32) -> u16 {
+    (1..=1024u16)
+        .rev()
+        .find(|&chunk| periods % chunk as u32 == 0)
+        .unwrap_or(1)
+}
+
+#[cfg(esp32)]
+impl<'a, S: TimerSpeed, O: OutputPin> Channel<'a, S, O> {
+    /// Always fails with [`Error::Unsupported`], the ESP32 has no
+    /// per-channel overflow counter
+    pub fn enable_overflow_interrupt(&mut self, _every_n_cycles: u16) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+}
+
 #[cfg(esp32)]
 /// Channel HW interface for HighSpeed channels
 impl<'a, O> ChannelHW<O> for Channel<'a, HighSpeed, O>
@@ -696,6 +1775,76 @@ where
         };
     }
 
+    /// Set the counter value at which the output goes high in HW
+    fn set_hpoint_hw(&self, hpoint: u32) {
+        match self.number {
+            Number::Channel0 => set_hpoint!(self, h, 0, hpoint),
+            Number::Channel1 => set_hpoint!(self, h, 1, hpoint),
+            Number::Channel2 => set_hpoint!(self, h, 2, hpoint),
+            Number::Channel3 => set_hpoint!(self, h, 3, hpoint),
+            Number::Channel4 => set_hpoint!(self, h, 4, hpoint),
+            Number::Channel5 => set_hpoint!(self, h, 5, hpoint),
+            Number::Channel6 => set_hpoint!(self, h, 6, hpoint),
+            Number::Channel7 => set_hpoint!(self, h, 7, hpoint),
+        };
+    }
+
+    /// Hold the output at a static level in HW, or output the PWM signal
+    fn set_static_level_hw(&self, level: Option<bool>) {
+        match self.number {
+            Number::Channel0 => set_static_level!(self, h, 0, level),
+            Number::Channel1 => set_static_level!(self, h, 1, level),
+            Number::Channel2 => set_static_level!(self, h, 2, level),
+            Number::Channel3 => set_static_level!(self, h, 3, level),
+            Number::Channel4 => set_static_level!(self, h, 4, level),
+            Number::Channel5 => set_static_level!(self, h, 5, level),
+            Number::Channel6 => set_static_level!(self, h, 6, level),
+            Number::Channel7 => set_static_level!(self, h, 7, level),
+        };
+    }
+
+    /// Read back the raw channel duty from HW
+    fn get_duty_hw(&self) -> u32 {
+        match self.number {
+            Number::Channel0 => get_duty!(self, h, 0),
+            Number::Channel1 => get_duty!(self, h, 1),
+            Number::Channel2 => get_duty!(self, h, 2),
+            Number::Channel3 => get_duty!(self, h, 3),
+            Number::Channel4 => get_duty!(self, h, 4),
+            Number::Channel5 => get_duty!(self, h, 5),
+            Number::Channel6 => get_duty!(self, h, 6),
+            Number::Channel7 => get_duty!(self, h, 7),
+        }
+    }
+
+    /// Read back whether the PWM output is enabled in HW
+    fn is_output_enabled_hw(&self) -> bool {
+        match self.number {
+            Number::Channel0 => is_output_enabled!(self, h, 0),
+            Number::Channel1 => is_output_enabled!(self, h, 1),
+            Number::Channel2 => is_output_enabled!(self, h, 2),
+            Number::Channel3 => is_output_enabled!(self, h, 3),
+            Number::Channel4 => is_output_enabled!(self, h, 4),
+            Number::Channel5 => is_output_enabled!(self, h, 5),
+            Number::Channel6 => is_output_enabled!(self, h, 6),
+            Number::Channel7 => is_output_enabled!(self, h, 7),
+        }
+    }
+
+    /// Select the timer in channel HW
+    fn set_timer_hw(&self, timer_number: u8) {
+        match self.number {
+            Number::Channel0 => set_timer_sel!(self, h, 0, timer_number),
+            Number::Channel1 => set_timer_sel!(self, h, 1, timer_number),
+            Number::Channel2 => set_timer_sel!(self, h, 2, timer_number),
+            Number::Channel3 => set_timer_sel!(self, h, 3, timer_number),
+            Number::Channel4 => set_timer_sel!(self, h, 4, timer_number),
+            Number::Channel5 => set_timer_sel!(self, h, 5, timer_number),
+            Number::Channel6 => set_timer_sel!(self, h, 6, timer_number),
+            Number::Channel7 => set_timer_sel!(self, h, 7, timer_number),
+        };
+    }
+
     /// Start a duty-cycle fade HW
     fn start_duty_fade_hw(
         &self,
@@ -801,6 +1950,11 @@ where
             Number::Channel7 => is_duty_fade_running!(self, h, 7),
         }
     }
+
+    fn duty_fade_int_bit(&self) -> u32 {
+        // DUTY_CHNG_END_HSCHn interrupts start at bit 8
+        8 + self.number as u32
+    }
 }
 
 /// Channel HW interface for LowSpeed channels
@@ -903,6 +2057,86 @@ where
         };
     }
 
+    /// Set the counter value at which the output goes high in HW
+    fn set_hpoint_hw(&self, hpoint: u32) {
+        match self.number {
+            Number::Channel0 => set_hpoint!(self, l, 0, hpoint),
+            Number::Channel1 => set_hpoint!(self, l, 1, hpoint),
+            Number::Channel2 => set_hpoint!(self, l, 2, hpoint),
+            Number::Channel3 => set_hpoint!(self, l, 3, hpoint),
+            Number::Channel4 => set_hpoint!(self, l, 4, hpoint),
+            Number::Channel5 => set_hpoint!(self, l, 5, hpoint),
+            #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+            Number::Channel6 => set_hpoint!(self, l, 6, hpoint),
+            #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+            Number::Channel7 => set_hpoint!(self, l, 7, hpoint),
+        };
+    }
+
+    /// Hold the output at a static level in HW, or output the PWM signal
+    fn set_static_level_hw(&self, level: Option<bool>) {
+        match self.number {
+            Number::Channel0 => set_static_level!(self, l, 0, level),
+            Number::Channel1 => set_static_level!(self, l, 1, level),
+            Number::Channel2 => set_static_level!(self, l, 2, level),
+            Number::Channel3 => set_static_level!(self, l, 3, level),
+            Number::Channel4 => set_static_level!(self, l, 4, level),
+            Number::Channel5 => set_static_level!(self, l, 5, level),
+            #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+            Number::Channel6 => set_static_level!(self, l, 6, level),
+            #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+            Number::Channel7 => set_static_level!(self, l, 7, level),
+        };
+    }
+
+    /// Read back the raw channel duty from HW
+    fn get_duty_hw(&self) -> u32 {
+        match self.number {
+            Number::Channel0 => get_duty!(self, l, 0),
+            Number::Channel1 => get_duty!(self, l, 1),
+            Number::Channel2 => get_duty!(self, l, 2),
+            Number::Channel3 => get_duty!(self, l, 3),
+            Number::Channel4 => get_duty!(self, l, 4),
+            Number::Channel5 => get_duty!(self, l, 5),
+            #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+            Number::Channel6 => get_duty!(self, l, 6),
+            #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+            Number::Channel7 => get_duty!(self, l, 7),
+        }
+    }
+
+    /// Read back whether the PWM output is enabled in HW
+    fn is_output_enabled_hw(&self) -> bool {
+        match self.number {
+            Number::Channel0 => is_output_enabled!(self, l, 0),
+            Number::Channel1 => is_output_enabled!(self, l, 1),
+            Number::Channel2 => is_output_enabled!(self, l, 2),
+            Number::Channel3 => is_output_enabled!(self, l, 3),
+            Number::Channel4 => is_output_enabled!(self, l, 4),
+            Number::Channel5 => is_output_enabled!(self, l, 5),
+            #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+            Number::Channel6 => is_output_enabled!(self, l, 6),
+            #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+            Number::Channel7 => is_output_enabled!(self, l, 7),
+        }
+    }
+
+    /// Select the timer in channel HW
+    fn set_timer_hw(&self, timer_number: u8) {
+        match self.number {
+            Number::Channel0 => set_timer_sel!(self, l, 0, timer_number),
+            Number::Channel1 => set_timer_sel!(self, l, 1, timer_number),
+            Number::Channel2 => set_timer_sel!(self, l, 2, timer_number),
+            Number::Channel3 => set_timer_sel!(self, l, 3, timer_number),
+            Number::Channel4 => set_timer_sel!(self, l, 4, timer_number),
+            Number::Channel5 => set_timer_sel!(self, l, 5, timer_number),
+            #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+            Number::Channel6 => set_timer_sel!(self, l, 6, timer_number),
+            #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+            Number::Channel7 => set_timer_sel!(self, l, 7, timer_number),
+        };
+    }
+
     /// Start a duty-cycle fade HW
     fn start_duty_fade_hw(
         &self,
@@ -1012,4 +2246,901 @@ where
             Number::Channel7 => is_duty_fade_running!(self, l, 7),
         }
     }
+
+    #[cfg(esp32)]
+    fn duty_fade_int_bit(&self) -> u32 {
+        // DUTY_CHNG_END_LSCHn interrupts start at bit 16
+        16 + self.number as u32
+    }
+
+    #[cfg(not(esp32))]
+    fn duty_fade_int_bit(&self) -> u32 {
+        // DUTY_CHNG_END_CHn interrupts follow the four timer overflow interrupts
+        4 + self.number as u32
+    }
+}
+
+/// Bit of the duty fade end interrupt of a channel
+fn duty_fade_int_bit_hw(high_speed: bool, number: Number) -> u32 {
+    #[cfg(esp32)]
+    if high_speed {
+        // DUTY_CHNG_END_HSCHn interrupts start at bit 8
+        return 8 + number as u32;
+    }
+    #[cfg(not(esp32))]
+    let _ = high_speed;
+
+    #[cfg(esp32)]
+    {
+        // DUTY_CHNG_END_LSCHn interrupts start at bit 16
+        16 + number as u32
+    }
+    #[cfg(not(esp32))]
+    {
+        // DUTY_CHNG_END_CHn interrupts follow the four timer overflow interrupts
+        4 + number as u32
+    }
+}
+
+/// Read the current duty of a channel from HW, including any ongoing fade
+pub(crate) fn current_duty_hw(high_speed: bool, number: Number) -> u32 {
+    #[cfg(esp32)]
+    if high_speed {
+        return match number {
+            Number::Channel0 => get_duty_r!(duty, h, 0),
+            Number::Channel1 => get_duty_r!(duty, h, 1),
+            Number::Channel2 => get_duty_r!(duty, h, 2),
+            Number::Channel3 => get_duty_r!(duty, h, 3),
+            Number::Channel4 => get_duty_r!(duty, h, 4),
+            Number::Channel5 => get_duty_r!(duty, h, 5),
+            Number::Channel6 => get_duty_r!(duty, h, 6),
+            Number::Channel7 => get_duty_r!(duty, h, 7),
+        };
+    }
+    #[cfg(not(esp32))]
+    let _ = high_speed;
+
+    match number {
+        Number::Channel0 => get_duty_r!(duty, l, 0),
+        Number::Channel1 => get_duty_r!(duty, l, 1),
+        Number::Channel2 => get_duty_r!(duty, l, 2),
+        Number::Channel3 => get_duty_r!(duty, l, 3),
+        Number::Channel4 => get_duty_r!(duty, l, 4),
+        Number::Channel5 => get_duty_r!(duty, l, 5),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Number::Channel6 => get_duty_r!(duty, l, 6),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Number::Channel7 => get_duty_r!(duty, l, 7),
+    }
+}
+
+/// Restore the configuration of a channel in HW, without touching its pin
+pub(crate) fn restore_hw<S: TimerSpeed>(number: Number, timer_number: u8, duty: u32) {
+    #[cfg(esp32)]
+    if S::IS_HS {
+        match number {
+            Number::Channel0 => {
+                set_channel!(restore, h, 0, timer_number);
+                set_duty!(restore, h, 0, duty);
+            }
+            Number::Channel1 => {
+                set_channel!(restore, h, 1, timer_number);
+                set_duty!(restore, h, 1, duty);
+            }
+            Number::Channel2 => {
+                set_channel!(restore, h, 2, timer_number);
+                set_duty!(restore, h, 2, duty);
+            }
+            Number::Channel3 => {
+                set_channel!(restore, h, 3, timer_number);
+                set_duty!(restore, h, 3, duty);
+            }
+            Number::Channel4 => {
+                set_channel!(restore, h, 4, timer_number);
+                set_duty!(restore, h, 4, duty);
+            }
+            Number::Channel5 => {
+                set_channel!(restore, h, 5, timer_number);
+                set_duty!(restore, h, 5, duty);
+            }
+            Number::Channel6 => {
+                set_channel!(restore, h, 6, timer_number);
+                set_duty!(restore, h, 6, duty);
+            }
+            Number::Channel7 => {
+                set_channel!(restore, h, 7, timer_number);
+                set_duty!(restore, h, 7, duty);
+            }
+        }
+        return;
+    }
+
+    match number {
+        Number::Channel0 => {
+            set_channel!(restore, l, 0, timer_number);
+            update_channel!(restore, l, 0);
+            set_duty!(restore, l, 0, duty);
+        }
+        Number::Channel1 => {
+            set_channel!(restore, l, 1, timer_number);
+            update_channel!(restore, l, 1);
+            set_duty!(restore, l, 1, duty);
+        }
+        Number::Channel2 => {
+            set_channel!(restore, l, 2, timer_number);
+            update_channel!(restore, l, 2);
+            set_duty!(restore, l, 2, duty);
+        }
+        Number::Channel3 => {
+            set_channel!(restore, l, 3, timer_number);
+            update_channel!(restore, l, 3);
+            set_duty!(restore, l, 3, duty);
+        }
+        Number::Channel4 => {
+            set_channel!(restore, l, 4, timer_number);
+            update_channel!(restore, l, 4);
+            set_duty!(restore, l, 4, duty);
+        }
+        Number::Channel5 => {
+            set_channel!(restore, l, 5, timer_number);
+            update_channel!(restore, l, 5);
+            set_duty!(restore, l, 5, duty);
+        }
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Number::Channel6 => {
+            set_channel!(restore, l, 6, timer_number);
+            update_channel!(restore, l, 6);
+            set_duty!(restore, l, 6, duty);
+        }
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Number::Channel7 => {
+            set_channel!(restore, l, 7, timer_number);
+            update_channel!(restore, l, 7);
+            set_duty!(restore, l, 7, duty);
+        }
+    }
+}
+
+/// Disable the PWM output of a channel, holding it at the low idle level
+pub(crate) fn disable_output_hw<S: TimerSpeed>(number: Number) {
+    let level = Some(false);
+
+    #[cfg(esp32)]
+    if S::IS_HS {
+        match number {
+            Number::Channel0 => set_static_level!(shutdown, h, 0, level),
+            Number::Channel1 => set_static_level!(shutdown, h, 1, level),
+            Number::Channel2 => set_static_level!(shutdown, h, 2, level),
+            Number::Channel3 => set_static_level!(shutdown, h, 3, level),
+            Number::Channel4 => set_static_level!(shutdown, h, 4, level),
+            Number::Channel5 => set_static_level!(shutdown, h, 5, level),
+            Number::Channel6 => set_static_level!(shutdown, h, 6, level),
+            Number::Channel7 => set_static_level!(shutdown, h, 7, level),
+        }
+        return;
+    }
+
+    match number {
+        Number::Channel0 => set_static_level!(shutdown, l, 0, level),
+        Number::Channel1 => set_static_level!(shutdown, l, 1, level),
+        Number::Channel2 => set_static_level!(shutdown, l, 2, level),
+        Number::Channel3 => set_static_level!(shutdown, l, 3, level),
+        Number::Channel4 => set_static_level!(shutdown, l, 4, level),
+        Number::Channel5 => set_static_level!(shutdown, l, 5, level),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Number::Channel6 => set_static_level!(shutdown, l, 6, level),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Number::Channel7 => set_static_level!(shutdown, l, 7, level),
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(esp32)] {
+        const NUM_INT_BITS: usize = 24;
+    } else {
+        // timer overflow, duty fade end and overflow counter interrupts
+        const NUM_INT_BITS: usize = 4 + 2 * super::CHANNEL_COUNT;
+    }
+}
+
+/// Start a duty fade on a channel in HW
+fn start_fade_hw(high_speed: bool, number: Number, fade: FadeParameters) {
+    let FadeParameters {
+        start_duty,
+        duty_inc,
+        duty_steps,
+        cycles_per_step,
+        duty_per_cycle,
+    } = fade;
+
+    #[cfg(esp32)]
+    if high_speed {
+        match number {
+            Number::Channel0 => set_duty_fade!(
+                fade,
+                h,
+                0,
+                start_duty,
+                duty_inc,
+                duty_steps,
+                cycles_per_step,
+                duty_per_cycle
+            ),
+            Number::Channel1 => set_duty_fade!(
+                fade,
+                h,
+                1,
+                start_duty,
+                duty_inc,
+                duty_steps,
+                cycles_per_step,
+                duty_per_cycle
+            ),
+            Number::Channel2 => set_duty_fade!(
+                fade,
+                h,
+                2,
+                start_duty,
+                duty_inc,
+                duty_steps,
+                cycles_per_step,
+                duty_per_cycle
+            ),
+            Number::Channel3 => set_duty_fade!(
+                fade,
+                h,
+                3,
+                start_duty,
+                duty_inc,
+                duty_steps,
+                cycles_per_step,
+                duty_per_cycle
+            ),
+            Number::Channel4 => set_duty_fade!(
+                fade,
+                h,
+                4,
+                start_duty,
+                duty_inc,
+                duty_steps,
+                cycles_per_step,
+                duty_per_cycle
+            ),
+            Number::Channel5 => set_duty_fade!(
+                fade,
+                h,
+                5,
+                start_duty,
+                duty_inc,
+                duty_steps,
+                cycles_per_step,
+                duty_per_cycle
+            ),
+            Number::Channel6 => set_duty_fade!(
+                fade,
+                h,
+                6,
+                start_duty,
+                duty_inc,
+                duty_steps,
+                cycles_per_step,
+                duty_per_cycle
+            ),
+            Number::Channel7 => set_duty_fade!(
+                fade,
+                h,
+                7,
+                start_duty,
+                duty_inc,
+                duty_steps,
+                cycles_per_step,
+                duty_per_cycle
+            ),
+        }
+        return;
+    }
+    #[cfg(not(esp32))]
+    let _ = high_speed;
+
+    match number {
+        Number::Channel0 => set_duty_fade!(
+            fade,
+            l,
+            0,
+            start_duty,
+            duty_inc,
+            duty_steps,
+            cycles_per_step,
+            duty_per_cycle
+        ),
+        Number::Channel1 => set_duty_fade!(
+            fade,
+            l,
+            1,
+            start_duty,
+            duty_inc,
+            duty_steps,
+            cycles_per_step,
+            duty_per_cycle
+        ),
+        Number::Channel2 => set_duty_fade!(
+            fade,
+            l,
+            2,
+            start_duty,
+            duty_inc,
+            duty_steps,
+            cycles_per_step,
+            duty_per_cycle
+        ),
+        Number::Channel3 => set_duty_fade!(
+            fade,
+            l,
+            3,
+            start_duty,
+            duty_inc,
+            duty_steps,
+            cycles_per_step,
+            duty_per_cycle
+        ),
+        Number::Channel4 => set_duty_fade!(
+            fade,
+            l,
+            4,
+            start_duty,
+            duty_inc,
+            duty_steps,
+            cycles_per_step,
+            duty_per_cycle
+        ),
+        Number::Channel5 => set_duty_fade!(
+            fade,
+            l,
+            5,
+            start_duty,
+            duty_inc,
+            duty_steps,
+            cycles_per_step,
+            duty_per_cycle
+        ),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Number::Channel6 => set_duty_fade!(
+            fade,
+            l,
+            6,
+            start_duty,
+            duty_inc,
+            duty_steps,
+            cycles_per_step,
+            duty_per_cycle
+        ),
+        #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+        Number::Channel7 => set_duty_fade!(
+            fade,
+            l,
+            7,
+            start_duty,
+            duty_inc,
+            duty_steps,
+            cycles_per_step,
+            duty_per_cycle
+        ),
+    }
+}
+
+/// State of a channel running a breathe effect, chained from the ISR
+#[derive(Clone, Copy)]
+struct Breathe {
+    high_speed: bool,
+    number: Number,
+    up: FadeParameters,
+    down: FadeParameters,
+    rising: bool,
+}
+
+static BREATHE: Mutex<RefCell<[Option<Breathe>; NUM_INT_BITS]>> =
+    Mutex::new(RefCell::new([None; NUM_INT_BITS]));
+
+/// Start the next fade of a channel running a breathe effect whose fade
+/// end interrupt `bit` fired, returns `false` if it isn't breathing
+fn advance_breathe(bit: u32) -> bool {
+    critical_section::with(|cs| match &mut BREATHE.borrow_ref_mut(cs)[bit as usize] {
+        Some(breathe) => {
+            breathe.rising = !breathe.rising;
+            let fade = if breathe.rising {
+                breathe.up
+            } else {
+                breathe.down
+            };
+            start_fade_hw(breathe.high_speed, breathe.number, fade);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Chain the fades of the channels running a breathe effect, see
+/// [`Channel::breathe`]
+///
+/// Without the `async` feature this has to be called from the `LEDC`
+/// interrupt handler of the application, interrupts of other sources are left
+/// pending for the application to handle. With the `async` feature the HAL
+/// provides the interrupt handler, which already does this.
+pub fn handle_breathe_interrupt() {
+    let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+    let mut pending = ledc.int_st.read().bits();
+
+    while pending != 0 {
+        let bit = pending.trailing_zeros();
+        if (bit as usize) < NUM_INT_BITS {
+            advance_breathe(bit);
+        }
+        pending &= !(1 << bit);
+    }
+}
+
+/// State of a channel outputting pulses counted in software, see
+/// [`Channel::pulse_n`]
+#[cfg(not(esp32))]
+#[derive(Clone, Copy)]
+struct Pulses {
+    number: Number,
+    /// Overflow interrupts left until the last pulse is output
+    remaining: u32,
+}
+
+#[cfg(not(esp32))]
+static PULSES: Mutex<RefCell<[Option<Pulses>; super::CHANNEL_COUNT]>> =
+    Mutex::new(RefCell::new([None; super::CHANNEL_COUNT]));
+
+/// Count the overflow interrupt `bit` of a channel outputting pulses, holding
+/// the output low at the end of the next period after the last one. Returns
+/// `false` if it isn't outputting pulses (anymore)
+#[cfg(not(esp32))]
+fn advance_pulses(bit: u32) -> bool {
+    let index = match bit.checked_sub(4 + super::CHANNEL_COUNT as u32) {
+        Some(index) if (index as usize) < super::CHANNEL_COUNT => index as usize,
+        _ => return false,
+    };
+
+    critical_section::with(|cs| {
+        let mut pulses = PULSES.borrow_ref_mut(cs);
+        let state = match &mut pulses[index] {
+            Some(state) => state,
+            None => return false,
+        };
+
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+        ledc.int_clr.write(|w| unsafe { w.bits(1 << bit) });
+
+        state.remaining -= 1;
+        if state.remaining > 0 {
+            return true;
+        }
+
+        // applied at the end of the period which just started, the last pulse
+        disable_output_hw::<LowSpeed>(state.number);
+        set_overflow_count_hw(state.number, None);
+        ledc.int_ena
+            .modify(|r, w| unsafe { w.bits(r.bits() & !(1 << bit)) });
+        pulses[index] = None;
+
+        false
+    })
+}
+
+/// Count the pulses of the channels outputting more pulses than the hardware
+/// can count, see [`Channel::pulse_n`]
+///
+/// Without the `async` feature this has to be called from the `LEDC`
+/// interrupt handler of the application, interrupts of other sources are left
+/// pending for the application to handle. With the `async` feature the HAL
+/// provides the interrupt handler, which already does this.
+#[cfg(not(esp32))]
+pub fn handle_pulse_interrupt() {
+    let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+    let mut pending = ledc.int_st.read().bits();
+
+    while pending != 0 {
+        let bit = pending.trailing_zeros();
+        advance_pulses(bit);
+        pending &= !(1 << bit);
+    }
+}
+
+/// Stop a breathe effect, queued fade or pulses counted in software of a
+/// channel, so the interrupt handler doesn't touch it anymore
+fn forget_fades(high_speed: bool, number: Number) {
+    let bit = duty_fade_int_bit_hw(high_speed, number);
+
+    #[cfg(not(esp32))]
+    critical_section::with(|cs| {
+        if PULSES.borrow_ref_mut(cs)[number as usize].take().is_some() {
+            let bit = overflow_int_bit_hw(number);
+            set_overflow_count_hw(number, None);
+
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc.int_ena
+                .modify(|r, w| unsafe { w.bits(r.bits() & !(1 << bit)) });
+        }
+    });
+
+    critical_section::with(|cs| {
+        let breathing = BREATHE.borrow_ref_mut(cs)[bit as usize].take().is_some();
+        #[cfg(feature = "async")]
+        let queued = asynch::forget_queued(cs, bit);
+        #[cfg(not(feature = "async"))]
+        let queued = false;
+
+        if breathing || queued {
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc.int_ena
+                .modify(|r, w| unsafe { w.bits(r.bits() & !(1 << bit)) });
+        }
+    });
+}
+
+impl<'a, S: TimerSpeed, O: OutputPin> Channel<'a, S, O>
+where
+    Channel<'a, S, O>: ChannelHW<O>,
+{
+    /// Continuously fade the duty cycle from 0% to 100% and back, taking
+    /// `period_ms` for a full cycle.
+    ///
+    /// Each fade is started from the `LEDC` interrupt handler when the
+    /// previous one ends, so the CPU is free in between. The `LEDC`
+    /// interrupt needs to be enabled. With the `async` feature the HAL's
+    /// interrupt handler chains the fades, without it
+    /// [`handle_breathe_interrupt`] has to be called from the application's
+    /// `LEDC` interrupt handler. The same constraints as for
+    /// [`ChannelIFace::start_duty_fade`] apply to half of `period_ms`.
+    pub fn breathe(&mut self, period_ms: u16) -> Result<(), Error> {
+        let up = self.fade_parameters(0, 100, period_ms / 2)?;
+        let down = self.fade_parameters(100, 0, period_ms / 2)?;
+        let bit = self.duty_fade_int_bit();
+
+        self.set_output_level(None);
+
+        critical_section::with(|cs| {
+            BREATHE.borrow_ref_mut(cs)[bit as usize] = Some(Breathe {
+                high_speed: S::IS_HS,
+                number: self.number,
+                up,
+                down,
+                rising: true,
+            });
+
+            start_fade_hw(S::IS_HS, self.number, up);
+
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc.int_ena
+                .modify(|r, w| unsafe { w.bits(r.bits() | (1 << bit)) });
+        });
+
+        Ok(())
+    }
+
+    /// Stop a breathe effect started with [`Channel::breathe`], keeping
+    /// the duty cycle at its current level.
+    pub fn stop_breathe(&mut self) {
+        let bit = self.duty_fade_int_bit();
+
+        critical_section::with(|cs| {
+            BREATHE.borrow_ref_mut(cs)[bit as usize] = None;
+
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc.int_ena
+                .modify(|r, w| unsafe { w.bits(r.bits() & !(1 << bit)) });
+
+            self.set_duty_hw(current_duty_hw(S::IS_HS, self.number));
+        });
+    }
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use core::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use embassy_sync::waitqueue::AtomicWaker;
+    use fugit::MillisDurationU32;
+    use procmacros::interrupt;
+
+    use super::*;
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: AtomicWaker = AtomicWaker::new();
+    static WAKERS: [AtomicWaker; NUM_INT_BITS] = [INIT; NUM_INT_BITS];
+
+    /// Number of linear fades a gamma corrected fade is made of
+    #[cfg(feature = "ledc-gamma")]
+    const GAMMA_SEGMENTS: usize = 16;
+
+    /// Next fade of a sequence, started from the ISR when the current one ends
+    #[derive(Clone, Copy)]
+    struct QueuedFade {
+        high_speed: bool,
+        number: Number,
+        fade: FadeParameters,
+    }
+
+    static QUEUED: Mutex<RefCell<[Option<QueuedFade>; NUM_INT_BITS]>> =
+        Mutex::new(RefCell::new([None; NUM_INT_BITS]));
+
+    pub(crate) struct FadeFuture {
+        bit: u32,
+    }
+
+    impl FadeFuture {
+        pub fn new(bit: u32) -> Self {
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            ledc.int_ena
+                .modify(|r, w| unsafe { w.bits(r.bits() | (1 << bit)) });
+
+            Self { bit }
+        }
+    }
+
+    impl core::future::Future for FadeFuture {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+            WAKERS[self.bit as usize].register(ctx.waker());
+
+            // the interrupt handler disables the interrupt once the fade ended
+            let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+            if ledc.int_ena.read().bits() & (1 << self.bit) == 0 {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Resolves once the ISR started the queued fade of a channel
+    struct QueuedFadeFuture {
+        bit: u32,
+    }
+
+    impl QueuedFadeFuture {
+        fn new(bit: u32, queued: QueuedFade) -> Self {
+            critical_section::with(|cs| {
+                QUEUED.borrow_ref_mut(cs)[bit as usize] = Some(queued);
+
+                // if the current fade already ended, the ISR runs right away
+                let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+                ledc.int_ena
+                    .modify(|r, w| unsafe { w.bits(r.bits() | (1 << bit)) });
+            });
+
+            Self { bit }
+        }
+    }
+
+    impl core::future::Future for QueuedFadeFuture {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+            WAKERS[self.bit as usize].register(ctx.waker());
+
+            if critical_section::with(|cs| QUEUED.borrow_ref(cs)[self.bit as usize].is_none()) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Drop the queued fade of the fade end interrupt `bit`, returns whether
+    /// there was one
+    pub(super) fn forget_queued(cs: critical_section::CriticalSection<'_>, bit: u32) -> bool {
+        QUEUED.borrow_ref_mut(cs)[bit as usize].take().is_some()
+    }
+
+    impl Drop for QueuedFadeFuture {
+        fn drop(&mut self) {
+            critical_section::with(|cs| QUEUED.borrow_ref_mut(cs)[self.bit as usize] = None);
+        }
+    }
+
+    impl<'a, S: TimerSpeed, O: OutputPin> Channel<'a, S, O>
+    where
+        Channel<'a, S, O>: ChannelHW<O>,
+    {
+        /// Fade the duty cycle from its current value to `target` % over
+        /// `duration` and wait for the hardware to signal the end of the fade.
+        ///
+        /// The fade starts at the duty read back from the HW, so it also
+        /// continues smoothly from an interrupted fade. The same constraints as
+        /// for [`ChannelIFace::start_duty_fade`] apply. The `LEDC` interrupt
+        /// needs to be enabled for the returned future to be woken.
+        pub async fn fade(&mut self, target: u8, duration: MillisDurationU32) -> Result<(), Error> {
+            if target > 100u8 {
+                return Err(Error::Fade(FadeError::EndDuty));
+            }
+            let duration_ms = u16::try_from(duration.to_millis())
+                .map_err(|_| Error::Fade(FadeError::Duration))?;
+
+            let duty_range = self.max_duty()? - 1;
+            let start_duty = current_duty_hw(S::IS_HS, self.number);
+            let end_duty = duty_range * target as u32 / 100;
+            let fade = self.fade_parameters_raw(start_duty, end_duty, duration_ms)?;
+
+            let bit = self.duty_fade_int_bit();
+            self.set_output_level(None);
+            start_fade_hw(S::IS_HS, self.number, fade);
+            FadeFuture::new(bit).await;
+
+            Ok(())
+        }
+
+        /// Fade the duty cycle from one % to another with gamma correction and
+        /// wait for the end of the fade.
+        ///
+        /// The duty % are taken as perceived brightness, the output duty is
+        /// `brightness ^ gamma` (around 2.2 to 2.8 is visually linear for
+        /// LEDs). The curve is approximated by consecutive linear hardware
+        /// fades of `duration_ms / 16` each, the same constraints as for
+        /// [`ChannelIFace::start_duty_fade`] apply to these, a `duration_ms`
+        /// below 16 is rejected with [`FadeError::Duration`]. All segments
+        /// are validated before the fade starts. The `LEDC` interrupt needs to
+        /// be enabled for the returned future to be woken. Needs the
+        /// `ledc-gamma` feature.
+        #[cfg(feature = "ledc-gamma")]
+        pub async fn fade_gamma(
+            &mut self,
+            start_duty_pct: u8,
+            end_duty_pct: u8,
+            duration_ms: u16,
+            gamma: f32,
+        ) -> Result<(), Error> {
+            if start_duty_pct > 100u8 {
+                return Err(Error::Fade(FadeError::StartDuty));
+            }
+            if end_duty_pct > 100u8 {
+                return Err(Error::Fade(FadeError::EndDuty));
+            }
+            if !gamma.is_finite() || gamma <= 0.0 {
+                return Err(Error::Fade(FadeError::Gamma));
+            }
+            let segment_ms = duration_ms / GAMMA_SEGMENTS as u16;
+            if segment_ms == 0 {
+                return Err(Error::Fade(FadeError::Duration));
+            }
+
+            let duty_range = (self.max_duty()? - 1) as f32;
+            let duty_at = |segment: usize| {
+                let brightness = (start_duty_pct as f32
+                    + (end_duty_pct as f32 - start_duty_pct as f32) * segment as f32
+                        / GAMMA_SEGMENTS as f32)
+                    / 100.0;
+                (libm::powf(brightness, gamma) * duty_range + 0.5) as u32
+            };
+
+            let mut segments = [None; GAMMA_SEGMENTS];
+            for (i, segment) in segments.iter_mut().enumerate() {
+                *segment =
+                    Some(self.fade_parameters_raw(duty_at(i), duty_at(i + 1), segment_ms)?);
+            }
+
+            let bit = self.duty_fade_int_bit();
+            self.set_output_level(None);
+            for fade in segments.into_iter().flatten() {
+                start_fade_hw(S::IS_HS, self.number, fade);
+                FadeFuture::new(bit).await;
+            }
+
+            Ok(())
+        }
+
+        /// Fade the duty cycle through a sequence of `(target_duty_pct,
+        /// duration)` segments, starting from the current duty cycle, and wait
+        /// for the end of the last one.
+        ///
+        /// Each segment is started from the `LEDC` interrupt handler when the
+        /// previous one ends, so there's no gap in between even if the task
+        /// isn't polled in time. The same constraints as for
+        /// [`ChannelIFace::start_duty_fade`] apply to every segment, all of
+        /// them are validated before the first one starts. The `LEDC`
+        /// interrupt needs to be enabled for the returned future to be woken.
+        pub async fn fade_sequence(
+            &mut self,
+            segments: &[(u8, MillisDurationU32)],
+        ) -> Result<(), Error> {
+            let duty_range = self.max_duty()? - 1;
+            let start_duty = current_duty_hw(S::IS_HS, self.number);
+
+            let fade_at = |channel: &Self, index: usize| -> Result<FadeParameters, Error> {
+                let duty_of = |duty_pct: u8| duty_range * duty_pct as u32 / 100;
+
+                let (end_duty_pct, duration) = segments[index];
+                if end_duty_pct > 100u8 {
+                    return Err(Error::Fade(FadeError::EndDuty));
+                }
+                let duration_ms = u16::try_from(duration.to_millis())
+                    .map_err(|_| Error::Fade(FadeError::Duration))?;
+                let start = match index {
+                    0 => start_duty,
+                    _ => duty_of(segments[index - 1].0),
+                };
+
+                channel.fade_parameters_raw(start, duty_of(end_duty_pct), duration_ms)
+            };
+
+            for index in 0..segments.len() {
+                fade_at(self, index)?;
+            }
+            if segments.is_empty() {
+                return Ok(());
+            }
+
+            let bit = self.duty_fade_int_bit();
+            self.set_output_level(None);
+            start_fade_hw(S::IS_HS, self.number, fade_at(self, 0)?);
+            for index in 1..segments.len() {
+                QueuedFadeFuture::new(
+                    bit,
+                    QueuedFade {
+                        high_speed: S::IS_HS,
+                        number: self.number,
+                        fade: fade_at(self, index)?,
+                    },
+                )
+                .await;
+            }
+            FadeFuture::new(bit).await;
+
+            Ok(())
+        }
+    }
+
+    #[cfg(not(esp32))]
+    impl<'a, O: OutputPin> Channel<'a, LowSpeed, O> {
+        /// Wait for the next overflow interrupt enabled with
+        /// [`Channel::enable_overflow_interrupt`]
+        ///
+        /// The interrupt handler disables the overflow interrupt when it
+        /// fires, it's enabled again here, so an overflow in between two calls
+        /// isn't reported. The `LEDC` interrupt needs to be
+        /// enabled for the returned future to be woken.
+        pub async fn wait_for_overflow(&mut self) {
+            self.clear_overflow_interrupt();
+            FadeFuture::new(self.overflow_int_bit()).await;
+        }
+    }
+
+    #[interrupt]
+    fn LEDC() {
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+        let mut pending = ledc.int_st.read().bits() & ledc.int_ena.read().bits();
+
+        ledc.int_ena
+            .modify(|r, w| unsafe { w.bits(r.bits() & !pending) });
+
+        while pending != 0 {
+            let bit = pending.trailing_zeros();
+            if (bit as usize) < NUM_INT_BITS {
+                let breathing = advance_breathe(bit);
+                #[cfg(not(esp32))]
+                let breathing = breathing || advance_pulses(bit);
+
+                let queued =
+                    critical_section::with(|cs| QUEUED.borrow_ref_mut(cs)[bit as usize].take());
+                if let Some(queued) = queued {
+                    start_fade_hw(queued.high_speed, queued.number, queued.fade);
+                }
+
+                if breathing || queued.is_some() {
+                    ledc.int_ena
+                        .modify(|r, w| unsafe { w.bits(r.bits() | (1 << bit)) });
+                }
+                if !breathing {
+                    WAKERS[bit as usize].wake();
+                }
+            }
+            pending &= !(1 << bit);
+        }
+    }
 }
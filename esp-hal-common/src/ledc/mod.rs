@@ -1,8 +1,9 @@
 //! LEDC (LED PWM Controller) peripheral control
 //!
-//! Currently only supports fixed-frequency output. Interrupts are not currently
-//! implemented. High Speed channels are available for the ESP32 only, while Low
-//! Speed channels are available for all supported chips.
+//! Currently only supports fixed-frequency output. With the `async` feature
+//! enabled, the end of a hardware duty fade can be awaited via
+//! [`channel::Channel::fade`]. High Speed channels are available for the ESP32
+//! only, while Low Speed channels are available for all supported chips.
 //!
 //! # LowSpeed Example:
 //!
@@ -13,7 +14,7 @@
 //! let mut ledc = LEDC::new(peripherals.LEDC, &mut system.peripheral_clock_control);
 //! ledc.set_global_slow_clock(LSGlobalClkSource::APBClk);
 //!
-//! let mut lstimer0 = ledc.get_timer::<LowSpeed>(timer::Number::Timer0);
+//! let mut lstimer0 = ledc.get_timer::<LowSpeed>(timer::Number::Timer0).unwrap();
 //! lstimer0
 //! .configure(timer::config::Config {
 //!            duty: timer::config::Duty::Duty5Bit,
@@ -22,7 +23,7 @@
 //!        })
 //!        .unwrap();
 //!
-//! let mut channel0 = ledc.get_channel(channel::Number::Channel0, led);
+//! let mut channel0 = ledc.get_channel(channel::Number::Channel0, led).unwrap();
 //! channel0
 //!     .configure(channel::config::Config {
 //!         timer: &lstimer0,
@@ -39,7 +40,7 @@
 //! ```rust,ignore
 //! let ledc = LEDC::new(peripherals.LEDC, &mut system.peripheral_clock_control);
 //!
-//! let mut hstimer0 = ledc.get_timer::<HighSpeed>(timer::Number::Timer0);
+//! let mut hstimer0 = ledc.get_timer::<HighSpeed>(timer::Number::Timer0).unwrap();
 //! hstimer0
 //! .configure(timer::config::Config {
 //!            duty: timer::config::Duty::Duty5Bit,
@@ -48,7 +49,7 @@
 //!        })
 //!        .unwrap();
 //!
-//! let mut channel0 = ledc.get_channel(channel::Number::Channel0, led);
+//! let mut channel0 = ledc.get_channel(channel::Number::Channel0, led).unwrap();
 //! channel0
 //!     .configure(channel::config::Config {
 //!         timer: &hstimer0,
@@ -57,34 +58,214 @@
 //!     .unwrap();
 //! ```
 //!
+//! Every timer and channel can only be handed out once at a time:
+//! [`LEDC::get_timer`] and [`LEDC::get_channel`] return `None` while the
+//! requested one is still in use, it becomes available again once dropped.
+//! The same goes for the GPIO of a channel, see [`LEDC::try_get_channel`].
+//!
+//! The configuration of timers and channels can be captured in a
+//! [`LedcSnapshot`], e.g. kept in RTC memory, and restored with
+//! [`LEDC::restore`] after waking up from deep sleep.
+//!
 //! # TODO
 //!
 //! - Source clock selection
-//! - Interrupts
+
+use core::cell::Cell;
+
+use critical_section::Mutex;
+use fugit::HertzU32;
 
 use self::{
-    channel::Channel,
-    timer::{Timer, TimerSpeed},
+    channel::{Channel, ChannelHW},
+    timer::{LSClockSource, Timer, TimerHW, TimerIFace, TimerSpeed},
 };
 use crate::{
     clock::Clocks,
-    gpio::OutputPin,
-    peripheral::{Peripheral, PeripheralRef},
+    gpio::{OutputPin, OutputSignal},
+    peripheral::{DriverGuard, Peripheral, PeripheralRef},
     system::{Peripheral as PeripheralEnable, PeripheralClockControl},
 };
 
 pub mod channel;
 pub mod timer;
 
+/// Measure the frequency a channel actually outputs, e.g. as a self-test
+/// during board bring-up.
+///
+/// The channel's own pin is read back through the GPIO matrix and its rising
+/// edges are counted with the given PCNT unit (whose configuration is
+/// overwritten) for `window_ms`. At most 32767 edges can be counted, `None`
+/// is returned if the counter overflowed, so keep
+/// `frequency * window_ms / 1000` below that. `None` is also returned for a
+/// `window_ms` of 0 or above `u32::MAX / 1000`.
+#[cfg(all(pcnt, any(xtensa, all(riscv, systimer))))]
+pub fn measure_output_frequency<S: TimerSpeed, O: OutputPin>(
+    channel: &Channel<'_, S, O>,
+    unit: &mut crate::pcnt::unit::Unit,
+    delay: &crate::delay::Delay,
+    window_ms: u32,
+) -> Option<HertzU32> {
+    use crate::pcnt::{channel as pcnt_channel, unit as pcnt_unit};
+
+    if window_ms == 0 {
+        return None;
+    }
+    let window_us = window_ms.checked_mul(1000)?;
+
+    let pin = channel.pin_number();
+    crate::gpio::enable_input_buffer(pin);
+
+    unit.configure(pcnt_unit::Config {
+        low_limit: -1,
+        high_limit: i16::MAX,
+        ..Default::default()
+    })
+    .ok()?;
+    unit.events(pcnt_unit::Events {
+        high_limit: true,
+        ..Default::default()
+    });
+    unit.get_channel(pcnt_channel::Number::Channel0).configure(
+        pcnt_channel::PcntSource::always_high(),
+        pcnt_channel::PcntSource::from_gpio_num(pin),
+        pcnt_channel::Config {
+            lctrl_mode: pcnt_channel::CtrlMode::Keep,
+            hctrl_mode: pcnt_channel::CtrlMode::Keep,
+            pos_edge: pcnt_channel::EdgeMode::Increment,
+            neg_edge: pcnt_channel::EdgeMode::Hold,
+            ..Default::default()
+        },
+    );
+
+    unit.pause();
+    unit.clear();
+    unit.reset_interrupt();
+    unit.resume();
+    delay.delay(window_us);
+    unit.pause();
+
+    if unit.get_events().high_limit {
+        return None;
+    }
+
+    let edges = unit.get_value() as u32;
+    Some(HertzU32::Hz(edges * 1000 / window_ms))
+}
+
+/// Number of LEDC channels per speed mode, see [`channel::Number`]
+///
+/// The ESP32 has this many high speed and this many low speed channels.
+#[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+pub const CHANNEL_COUNT: usize = 8;
+/// Number of LEDC channels, see [`channel::Number`]
+#[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2))]
+pub const CHANNEL_COUNT: usize = 6;
+
+/// Number of LEDC timers per speed mode, see [`timer::Number`]
+///
+/// The ESP32 has this many high speed and this many low speed timers.
+pub const TIMER_COUNT: usize = 4;
+
+#[cfg(esp32)]
+const SPEED_MODES: usize = 2;
+#[cfg(not(esp32))]
+const SPEED_MODES: usize = 1;
+
+const TIMER_NUMBERS: [timer::Number; TIMER_COUNT] = [
+    timer::Number::Timer0,
+    timer::Number::Timer1,
+    timer::Number::Timer2,
+    timer::Number::Timer3,
+];
+
+const CHANNEL_NUMBERS: [channel::Number; CHANNEL_COUNT] = [
+    channel::Number::Channel0,
+    channel::Number::Channel1,
+    channel::Number::Channel2,
+    channel::Number::Channel3,
+    channel::Number::Channel4,
+    channel::Number::Channel5,
+    #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+    channel::Number::Channel6,
+    #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+    channel::Number::Channel7,
+];
+
+const LS_SIGNALS: [OutputSignal; CHANNEL_COUNT] = [
+    OutputSignal::LEDC_LS_SIG0,
+    OutputSignal::LEDC_LS_SIG1,
+    OutputSignal::LEDC_LS_SIG2,
+    OutputSignal::LEDC_LS_SIG3,
+    OutputSignal::LEDC_LS_SIG4,
+    OutputSignal::LEDC_LS_SIG5,
+    #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+    OutputSignal::LEDC_LS_SIG6,
+    #[cfg(not(any(esp32c2, esp32c3, esp32c6, esp32h2)))]
+    OutputSignal::LEDC_LS_SIG7,
+];
+
+#[cfg(esp32)]
+const HS_SIGNALS: [OutputSignal; CHANNEL_COUNT] = [
+    OutputSignal::LEDC_HS_SIG0,
+    OutputSignal::LEDC_HS_SIG1,
+    OutputSignal::LEDC_HS_SIG2,
+    OutputSignal::LEDC_HS_SIG3,
+    OutputSignal::LEDC_HS_SIG4,
+    OutputSignal::LEDC_HS_SIG5,
+    OutputSignal::LEDC_HS_SIG6,
+    OutputSignal::LEDC_HS_SIG7,
+];
+
 /// Global slow clock source
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum LSGlobalClkSource {
     APBClk,
 }
 
+/// A clock the LEDC timers can be derived from, on any chip
+///
+/// The per-speed-mode enums [`LSClockSource`] and
+/// [`HSClockSource`](timer::HSClockSource) name the sources a timer can
+/// select and convert into this. None of the supported chips can clock LEDC
+/// from the RTC slow clock.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ClockSource {
+    /// The APB clock
+    Apb,
+    /// The external crystal
+    #[cfg(not(any(esp32, esp32s2)))]
+    Xtal,
+    /// The internal RC_FAST oscillator, its frequency isn't calibrated
+    RcFast,
+    /// REF_TICK, 1MHz derived from the APB clock
+    #[cfg(any(esp32, esp32s2))]
+    RefTick,
+}
+
+impl ClockSource {
+    /// The (nominal) frequency of the clock, `None` if it's unknown
+    pub fn frequency(&self, clocks: &Clocks) -> Option<HertzU32> {
+        match self {
+            ClockSource::Apb => Some(clocks.apb_clock),
+            #[cfg(not(any(esp32, esp32s2)))]
+            ClockSource::Xtal => Some(clocks.xtal_clock),
+            #[cfg(any(esp32, esp32s2))]
+            ClockSource::RcFast => Some(HertzU32::MHz(8)),
+            #[cfg(esp32h2)]
+            ClockSource::RcFast => Some(HertzU32::kHz(8_500)),
+            #[cfg(not(any(esp32, esp32s2, esp32h2)))]
+            ClockSource::RcFast => Some(HertzU32::kHz(17_500)),
+            #[cfg(any(esp32, esp32s2))]
+            ClockSource::RefTick => Some(HertzU32::MHz(1)),
+        }
+    }
+}
+
 /// LEDC (LED PWM Controller)
 pub struct LEDC<'d> {
     _instance: PeripheralRef<'d, crate::peripherals::LEDC>,
+    _guard: DriverGuard,
 }
 
 #[cfg(esp32)]
@@ -94,12 +275,300 @@ pub struct HighSpeed {}
 /// Used to specify LowSpeed Timer/Channel
 pub struct LowSpeed {}
 
-pub trait Speed {}
+pub trait Speed {
+    #[doc(hidden)]
+    const IS_HS: bool;
+}
 
 #[cfg(esp32)]
-impl Speed for HighSpeed {}
+impl Speed for HighSpeed {
+    const IS_HS: bool = true;
+}
+
+impl Speed for LowSpeed {
+    const IS_HS: bool = false;
+}
+
+/// Index of a timer/channel of speed `S` in a per-speed-mode table
+fn table_index<S: Speed>(number: usize, count: usize) -> usize {
+    if S::IS_HS {
+        count + number
+    } else {
+        number
+    }
+}
 
-impl Speed for LowSpeed {}
+#[derive(Clone, Copy)]
+struct TimerSnapshot {
+    duty: timer::config::Duty,
+    frequency: HertzU32,
+    clock_source: ClockSource,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct ChannelSnapshot {
+    pub(crate) timer: timer::Number,
+    pub(crate) duty: u32,
+    pub(crate) pin: u8,
+    pub(crate) open_drain: bool,
+}
+
+/// Snapshot of the LEDC timer and channel configuration
+///
+/// The snapshot is `Copy` and doesn't reference the drivers, so it can be
+/// kept in RTC memory across deep sleep and reapplied with [`LEDC::restore`]
+/// after waking up.
+#[derive(Clone, Copy)]
+pub struct LedcSnapshot {
+    timers: [Option<TimerSnapshot>; TIMER_COUNT * SPEED_MODES],
+    channels: [Option<ChannelSnapshot>; CHANNEL_COUNT * SPEED_MODES],
+}
+
+impl LedcSnapshot {
+    /// Create an empty snapshot
+    pub const fn new() -> Self {
+        LedcSnapshot {
+            timers: [None; TIMER_COUNT * SPEED_MODES],
+            channels: [None; CHANNEL_COUNT * SPEED_MODES],
+        }
+    }
+
+    /// Capture the configuration of a timer (duty resolution, frequency and
+    /// clock source)
+    pub fn save_timer<'t, S: TimerSpeed>(&mut self, timer: &Timer<'t, S>)
+    where
+        Timer<'t, S>: TimerHW<S>,
+    {
+        let index = table_index::<S>(timer.get_number() as usize, TIMER_COUNT);
+        self.timers[index] = match (timer.get_duty(), timer.clock_source()) {
+            (Some(duty), Some(clock_source)) if timer.is_configured() => Some(TimerSnapshot {
+                duty,
+                frequency: timer.frequency(),
+                clock_source: clock_source.into(),
+            }),
+            _ => None,
+        };
+    }
+
+    /// Capture the configuration of a channel (timer, raw duty and pin)
+    pub fn save_channel<'a, S: TimerSpeed, O: OutputPin>(
+        &mut self,
+        channel: &Channel<'a, S, O>,
+    ) where
+        Channel<'a, S, O>: ChannelHW<O>,
+    {
+        let index = table_index::<S>(channel.number() as usize, CHANNEL_COUNT);
+        self.channels[index] = channel.snapshot();
+    }
+}
+
+impl Default for LedcSnapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn restore_timer<S: TimerSpeed>(
+    ledc: &LEDC,
+    number: timer::Number,
+    clocks: &Clocks,
+    saved: &TimerSnapshot,
+) -> Result<(), timer::Error>
+where
+    for<'t> Timer<'t, S>: TimerHW<S>,
+{
+    let clock_source = S::clock_source_type(saved.clock_source).ok_or(timer::Error::ClockSource)?;
+
+    let mut timer = ledc.get_timer::<S>(number).ok_or(timer::Error::InUse)?;
+    timer.configure(
+        clocks,
+        timer::config::Config {
+            duty: saved.duty,
+            clock_source,
+            frequency: saved.frequency,
+        },
+    )?;
+    drop(timer);
+    // the timer keeps running without a handle, so e.g. `LEDC::pause_all`
+    // still covers it
+    mark_timer_configured::<S>(number);
+
+    Ok(())
+}
+
+fn restore_channel<S: TimerSpeed>(
+    number: channel::Number,
+    saved: &ChannelSnapshot,
+    signal: OutputSignal,
+) -> Result<(), timer::Error> {
+    let bit = registry_bit::<S>(number as u8);
+    if !take(&TAKEN_CHANNELS, bit) {
+        return Err(timer::Error::InUse);
+    }
+    if !take_pin(saved.pin) {
+        release(&TAKEN_CHANNELS, bit);
+        return Err(timer::Error::InUse);
+    }
+
+    channel::restore_hw::<S>(number, saved.timer as u8, saved.duty);
+    crate::gpio::connect_output_signal(saved.pin, signal, saved.open_drain);
+
+    // the channel keeps driving the pin, so both stay taken until the channel
+    // is handed out again and dropped
+    critical_section::with(|cs| {
+        let restored = RESTORED_PINS.borrow(cs);
+        let mut pins = restored.get();
+        pins[table_index::<S>(number as usize, CHANNEL_COUNT)] = Some(saved.pin);
+        restored.set(pins);
+    });
+
+    Ok(())
+}
+
+/// Hand out a channel restored by [`LEDC::restore`], returns `false` if it
+/// wasn't restored on the GPIO `pin`
+fn take_restored<S: Speed>(number: channel::Number, pin: u8) -> bool {
+    critical_section::with(|cs| {
+        let restored = RESTORED_PINS.borrow(cs);
+        let mut pins = restored.get();
+        let index = table_index::<S>(number as usize, CHANNEL_COUNT);
+        if pins[index] == Some(pin) {
+            pins[index] = None;
+            restored.set(pins);
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Timers currently handed out, bit `n` is LS timer `n`, bit `16 + n` HS timer
+/// `n`
+static TAKEN_TIMERS: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+
+/// Timers which have been configured, with the same layout as `TAKEN_TIMERS`
+static CONFIGURED_TIMERS: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+
+/// Channels currently handed out, bit `n` is LS channel `n`, bit `16 + n` HS
+/// channel `n`
+static TAKEN_CHANNELS: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+
+/// GPIOs bound to a channel, bit `n` is GPIO `n`
+static TAKEN_PINS: Mutex<Cell<u64>> = Mutex::new(Cell::new(0));
+
+/// Channels which are part of a complementary pair, with the same layout as
+/// `TAKEN_CHANNELS`
+static PAIRED_CHANNELS: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+
+/// GPIOs of the channels restored by [`LEDC::restore`] which haven't been
+/// handed out again, indexed like the channels of a [`LedcSnapshot`]
+static RESTORED_PINS: Mutex<Cell<[Option<u8>; CHANNEL_COUNT * SPEED_MODES]>> =
+    Mutex::new(Cell::new([None; CHANNEL_COUNT * SPEED_MODES]));
+
+fn registry_bit<S: Speed>(number: u8) -> u32 {
+    1 << (number as u32 + if S::IS_HS { 16 } else { 0 })
+}
+
+/// Mark the bits in `bit` as taken, returns `false` if any of them already
+/// was
+fn take(registry: &Mutex<Cell<u32>>, bit: u32) -> bool {
+    critical_section::with(|cs| {
+        let taken = registry.borrow(cs);
+        if taken.get() & bit != 0 {
+            false
+        } else {
+            taken.set(taken.get() | bit);
+            true
+        }
+    })
+}
+
+fn release(registry: &Mutex<Cell<u32>>, bit: u32) {
+    critical_section::with(|cs| {
+        let taken = registry.borrow(cs);
+        taken.set(taken.get() & !bit);
+    })
+}
+
+pub(crate) fn release_timer<S: Speed>(number: timer::Number) {
+    release(&TAKEN_TIMERS, registry_bit::<S>(number as u8));
+    release(&CONFIGURED_TIMERS, registry_bit::<S>(number as u8));
+}
+
+pub(crate) fn mark_timer_configured<S: Speed>(number: timer::Number) {
+    take(&CONFIGURED_TIMERS, registry_bit::<S>(number as u8));
+}
+
+/// Pause or resume all configured timers
+fn pause_configured_timers(paused: bool) {
+    critical_section::with(|cs| {
+        let configured = CONFIGURED_TIMERS.borrow(cs).get();
+
+        for number in TIMER_NUMBERS {
+            #[cfg(esp32)]
+            if configured & registry_bit::<HighSpeed>(number as u8) != 0 {
+                timer::pause_hw::<HighSpeed>(number, paused);
+            }
+            if configured & registry_bit::<LowSpeed>(number as u8) != 0 {
+                timer::pause_hw::<LowSpeed>(number, paused);
+            }
+        }
+    });
+}
+
+pub(crate) fn release_channel<S: Speed>(number: channel::Number) {
+    release(&TAKEN_CHANNELS, registry_bit::<S>(number as u8));
+}
+
+/// Mark the channels `a` and `b` as a complementary pair, returns `false` if
+/// either of them already is part of one
+pub(crate) fn pair_channels<S: Speed>(a: channel::Number, b: channel::Number) -> bool {
+    take(
+        &PAIRED_CHANNELS,
+        registry_bit::<S>(a as u8) | registry_bit::<S>(b as u8),
+    )
+}
+
+/// Mark the channels `a` and `b` as not part of a complementary pair anymore
+pub(crate) fn unpair_channels<S: Speed>(a: channel::Number, b: channel::Number) {
+    release(
+        &PAIRED_CHANNELS,
+        registry_bit::<S>(a as u8) | registry_bit::<S>(b as u8),
+    );
+}
+
+/// Whether the channel is part of a complementary pair
+pub(crate) fn is_paired<S: Speed>(number: channel::Number) -> bool {
+    critical_section::with(|cs| {
+        PAIRED_CHANNELS.borrow(cs).get() & registry_bit::<S>(number as u8) != 0
+    })
+}
+
+/// Mark the GPIO as bound to a channel, returns `false` if it already was
+fn take_pin(pin: u8) -> bool {
+    critical_section::with(|cs| {
+        let taken = TAKEN_PINS.borrow(cs);
+        if taken.get() & (1 << pin) != 0 {
+            false
+        } else {
+            taken.set(taken.get() | (1 << pin));
+            true
+        }
+    })
+}
+
+pub(crate) fn release_pin(pin: u8) {
+    critical_section::with(|cs| {
+        let taken = TAKEN_PINS.borrow(cs);
+        taken.set(taken.get() & !(1 << pin));
+    })
+}
+
+/// Mark the channel as taken, returns `false` if it already was
+#[cfg(esp32)]
+pub(crate) fn take_channel<S: Speed>(number: channel::Number) -> bool {
+    take(&TAKEN_CHANNELS, registry_bit::<S>(number as u8))
+}
 
 impl<'d> LEDC<'d> {
     /// Return a new LEDC
@@ -112,10 +581,18 @@ impl<'d> LEDC<'d> {
 
         LEDC {
             _instance,
+            _guard: DriverGuard::new(crate::peripherals::LEDC::PTR as *const (), "LEDC"),
         }
     }
 
     /// Set global slow clock source
+    ///
+    /// None of the supported chips has a divider for the global slow clock
+    /// shared by all timers, the slow clock is always the selected source
+    /// itself. The lowest PWM frequency is therefore limited by the per-timer
+    /// divisor (up to 1023 plus a fractional part) and the duty resolution:
+    /// for very low frequencies use a higher duty resolution, or a slower
+    /// clock source (the timers fall back to REF_TICK where available).
     #[cfg(esp32)]
     pub fn set_global_slow_clock(&mut self, _clock_source: LSGlobalClkSource) {
         let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
@@ -125,6 +602,13 @@ impl<'d> LEDC<'d> {
 
     #[cfg(not(esp32))]
     /// Set global slow clock source
+    ///
+    /// None of the supported chips has a divider for the global slow clock
+    /// shared by all timers, the slow clock is always the selected source
+    /// itself. The lowest PWM frequency is therefore limited by the per-timer
+    /// divisor (up to 1023 plus a fractional part) and the duty resolution:
+    /// for very low frequencies use a higher duty resolution, or a slower
+    /// clock source (the timers fall back to REF_TICK where available).
     pub fn set_global_slow_clock(&mut self, clock_source: LSGlobalClkSource) {
         let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
 
@@ -149,17 +633,233 @@ impl<'d> LEDC<'d> {
         ledc.timer0_conf.modify(|_, w| w.para_up().set_bit());
     }
 
-    /// Return a new timer
-    pub fn get_timer<S: TimerSpeed>(&self, number: timer::Number) -> Timer<S> {
-        Timer::new(number)
+    /// Start timers configured with [`TimerIFace::configure_deferred`]
+    /// together, so the PWM periods of their channels are in lockstep.
+    ///
+    /// The timers are released back to back in a critical section, so they
+    /// start within a few APB clock cycles of each other.
+    pub fn commit<S: TimerSpeed>(&self, timers: &[timer::Number]) {
+        critical_section::with(|_| timer::commit_hw::<S>(timers));
     }
 
-    /// Return a new channel
+    /// Start a timer and the output of a channel bound to it together, so the
+    /// first PWM period on the pin is already a complete period at the
+    /// configured duty
+    ///
+    /// `timer` has to be configured with [`TimerIFace::configure_deferred`]
+    /// and `channel` configured with its duty set afterwards. The output is
+    /// held low and the duty is committed again, then the timer is released
+    /// from reset. Once the timer overflowed, i.e. the new duty is latched,
+    /// the output is enabled, which the channel latches at the next period
+    /// boundary: the PWM signal starts with a complete period, the periods
+    /// before are output low. This waits for the first overflow, outside of a
+    /// critical section, and fails with [`channel::Error::Timeout`] if it
+    /// doesn't come within two periods.
+    ///
+    /// The high speed channels of the ESP32 apply the output enable
+    /// immediately instead of at a period boundary, so they fail with
+    /// [`channel::Error::Unsupported`].
+    pub fn start_output_atomic<'t, 'a, S: TimerSpeed, O: OutputPin>(
+        &self,
+        timer: &Timer<'t, S>,
+        channel: &mut Channel<'a, S, O>,
+    ) -> Result<(), channel::Error>
+    where
+        Timer<'t, S>: TimerHW<S>,
+        Channel<'a, S, O>: ChannelHW<O>,
+    {
+        channel.start_with_timer_hw(timer)
+    }
+
+    /// Pause all configured timers, e.g. for an emergency stop
+    ///
+    /// The outputs of all channels freeze at their current level. The timers
+    /// are paused back to back in a critical section, so no output keeps
+    /// running for more than a few APB clock cycles after the first one
+    /// stopped. Timers which weren't configured are not touched.
+    pub fn pause_all(&self) {
+        pause_configured_timers(true);
+    }
+
+    /// Resume all configured timers paused by [`LEDC::pause_all`]
+    pub fn resume_all(&self) {
+        pause_configured_timers(false);
+    }
+
+    /// Shut the peripheral down, e.g. before a low-power transition or to hand
+    /// the pins to another peripheral
+    ///
+    /// All channels stop their PWM output and are held at the low idle
+    /// level, all timers are paused, then the clock of the peripheral is
+    /// gated and it's held in reset. Timers and channels borrow the `LEDC`,
+    /// so none of them is left; the timers, channels and pins of handles
+    /// which were leaked (e.g. with [`core::mem::forget`]) are released. The
+    /// pins stay connected to the LEDC in the GPIO matrix (at the low level)
+    /// until they're configured otherwise.
+    pub fn shutdown(self, system: &mut PeripheralClockControl) {
+        critical_section::with(|cs| {
+            for number in CHANNEL_NUMBERS {
+                #[cfg(esp32)]
+                channel::disable_output_hw::<HighSpeed>(number);
+                channel::disable_output_hw::<LowSpeed>(number);
+            }
+            for number in TIMER_NUMBERS {
+                #[cfg(esp32)]
+                timer::pause_hw::<HighSpeed>(number, true);
+                timer::pause_hw::<LowSpeed>(number, true);
+            }
+
+            TAKEN_TIMERS.borrow(cs).set(0);
+            CONFIGURED_TIMERS.borrow(cs).set(0);
+            TAKEN_CHANNELS.borrow(cs).set(0);
+            TAKEN_PINS.borrow(cs).set(0);
+            PAIRED_CHANNELS.borrow(cs).set(0);
+            RESTORED_PINS
+                .borrow(cs)
+                .set([None; CHANNEL_COUNT * SPEED_MODES]);
+        });
+
+        system.disable(PeripheralEnable::Ledc);
+    }
+
+    /// Reapply the configuration captured in a [`LedcSnapshot`], e.g. after
+    /// waking up from deep sleep
+    ///
+    /// The timers are restored with their saved clock source, their divisors
+    /// are recalculated (and validated) for the current `clocks`, the pins of
+    /// the channels are connected again. This is meant to be called before any
+    /// timer and channel is handed out: fails with [`timer::Error::InUse`] if
+    /// a saved timer, channel or its GPIO is in use. The restored timers can
+    /// be handed out again afterwards. The restored channels keep driving
+    /// their GPIOs, so each channel and its GPIO stay in use until the channel
+    /// is handed out again with the same number and GPIO (and dropped).
+    pub fn restore(
+        &mut self,
+        snapshot: &LedcSnapshot,
+        clocks: &Clocks,
+    ) -> Result<(), timer::Error> {
+        // the low speed timers are clocked from the global slow clock
+        if let Some(saved) = snapshot.timers[..TIMER_COUNT].iter().flatten().next() {
+            match LowSpeed::clock_source_type(saved.clock_source) {
+                Some(LSClockSource::APBClk) => {
+                    self.set_global_slow_clock(LSGlobalClkSource::APBClk)
+                }
+                None => return Err(timer::Error::ClockSource),
+            }
+        }
+
+        for (index, saved) in snapshot.timers.iter().enumerate() {
+            let saved = match saved {
+                Some(saved) => saved,
+                None => continue,
+            };
+            let number = TIMER_NUMBERS[index % TIMER_COUNT];
+
+            #[cfg(esp32)]
+            if index >= TIMER_COUNT {
+                restore_timer::<HighSpeed>(self, number, clocks, saved)?;
+                continue;
+            }
+
+            restore_timer::<LowSpeed>(self, number, clocks, saved)?;
+        }
+
+        for (index, saved) in snapshot.channels.iter().enumerate() {
+            let saved = match saved {
+                Some(saved) => saved,
+                None => continue,
+            };
+            let number = CHANNEL_NUMBERS[index % CHANNEL_COUNT];
+
+            #[cfg(esp32)]
+            if index >= CHANNEL_COUNT {
+                restore_channel::<HighSpeed>(number, saved, HS_SIGNALS[number as usize])?;
+                continue;
+            }
+
+            restore_channel::<LowSpeed>(number, saved, LS_SIGNALS[number as usize])?;
+        }
+
+        Ok(())
+    }
+
+    /// Return a new timer, or `None` if the timer is already in use
+    pub fn get_timer<S: TimerSpeed>(&self, number: timer::Number) -> Option<Timer<S>> {
+        if take(&TAKEN_TIMERS, registry_bit::<S>(number as u8)) {
+            Some(Timer::new(number))
+        } else {
+            None
+        }
+    }
+
+    /// Return a new channel, or `None` if the channel is already in use or
+    /// the pin is bound to another channel, see [`LEDC::try_get_channel`]
     pub fn get_channel<S: TimerSpeed, O: OutputPin>(
         &self,
         number: channel::Number,
         output_pin: impl Peripheral<P = O> + 'd,
-    ) -> Channel<S, O> {
-        Channel::new(number, output_pin)
+    ) -> Option<Channel<S, O>> {
+        self.try_get_channel(number, output_pin).ok()
+    }
+
+    /// Return a new channel
+    ///
+    /// Fails with [`channel::Error::InUse`] if the channel is already in use
+    /// and with [`channel::Error::PinInUse`] if the GPIO is already bound to
+    /// another LEDC channel, two channels driving one pin produce an undefined
+    /// output. Both become available again once the channel is dropped.
+    ///
+    /// Strapping pins are accepted, see [`LEDC::get_channel_checked`].
+    pub fn try_get_channel<S: TimerSpeed, O: OutputPin>(
+        &self,
+        number: channel::Number,
+        output_pin: impl Peripheral<P = O> + 'd,
+    ) -> Result<Channel<S, O>, channel::Error> {
+        crate::into_ref!(output_pin);
+
+        self.bind_channel(number, output_pin)
+    }
+
+    /// Return a new channel, rejecting the
+    /// [strapping pins](crate::gpio::STRAPPING_PINS)
+    ///
+    /// The level of a strapping pin is sampled at every reset to select the
+    /// boot mode: while the PWM output drives it, a reset (e.g. by a watchdog
+    /// or `software_reset`) can boot the chip into the wrong mode, unless the
+    /// external circuit dominates the pin during reset. This fails with
+    /// [`channel::Error::StrappingPin`] for those, otherwise it's the same as
+    /// [`LEDC::try_get_channel`].
+    pub fn get_channel_checked<S: TimerSpeed, O: OutputPin>(
+        &self,
+        number: channel::Number,
+        output_pin: impl Peripheral<P = O> + 'd,
+    ) -> Result<Channel<S, O>, channel::Error> {
+        crate::into_ref!(output_pin);
+
+        if crate::gpio::STRAPPING_PINS.contains(&output_pin.number()) {
+            return Err(channel::Error::StrappingPin);
+        }
+
+        self.bind_channel(number, output_pin)
+    }
+
+    fn bind_channel<S: TimerSpeed, O: OutputPin>(
+        &self,
+        number: channel::Number,
+        output_pin: PeripheralRef<'d, O>,
+    ) -> Result<Channel<S, O>, channel::Error> {
+        if take_restored::<S>(number, output_pin.number()) {
+            // the channel and its GPIO are still taken from `LEDC::restore`
+            return Ok(Channel::new(number, output_pin));
+        }
+        if !take(&TAKEN_CHANNELS, registry_bit::<S>(number as u8)) {
+            return Err(channel::Error::InUse);
+        }
+        if !take_pin(output_pin.number()) {
+            release_channel::<S>(number);
+            return Err(channel::Error::PinInUse);
+        }
+
+        Ok(Channel::new(number, output_pin))
     }
 }
@@ -1,8 +1,10 @@
+use core::marker::PhantomData;
+
 use fugit::HertzU32;
 
 #[cfg(esp32)]
 use super::HighSpeed;
-use super::{LowSpeed, Speed};
+use super::{ClockSource, LowSpeed, Speed};
 use crate::{clock::Clocks, peripherals::ledc};
 
 const LEDC_TIMER_DIV_NUM_MAX: u64 = 0x3FFFF;
@@ -12,6 +14,17 @@ const LEDC_TIMER_DIV_NUM_MAX: u64 = 0x3FFFF;
 pub enum Error {
     /// Invalid Divisor
     Divisor,
+    /// The timer can't be reconfigured seamlessly (not configured yet, or the
+    /// new configuration needs a different clock source)
+    Seamless,
+    /// The configuration or operation isn't available on this chip, see also
+    /// [`channel::Error::Unsupported`](super::channel::Error::Unsupported)
+    Unsupported,
+    /// The frequency of the timer's clock source isn't known
+    ClockSource,
+    /// The timer, or a channel or GPIO restored together with it, is in use,
+    /// see [`LEDC::restore`](super::LEDC::restore)
+    InUse,
 }
 
 #[cfg(esp32)]
@@ -22,6 +35,15 @@ pub enum HSClockSource {
     // TODO RefTick,
 }
 
+#[cfg(esp32)]
+impl From<HSClockSource> for ClockSource {
+    fn from(source: HSClockSource) -> Self {
+        match source {
+            HSClockSource::APBClk => ClockSource::Apb,
+        }
+    }
+}
+
 /// Clock source for LS Timers
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum LSClockSource {
@@ -29,7 +51,19 @@ pub enum LSClockSource {
     // TODO SLOWClk
 }
 
+impl From<LSClockSource> for ClockSource {
+    fn from(source: LSClockSource) -> Self {
+        match source {
+            LSClockSource::APBClk => ClockSource::Apb,
+        }
+    }
+}
+
 /// Timer number
+///
+/// The LEDC peripheral of every chip has 4 timers (4 high speed and 4 low
+/// speed timers on the ESP32), so unlike [`channel::Number`](super::channel::Number)
+/// all numbers are valid on every chip, see [`TIMER_COUNT`](super::TIMER_COUNT).
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum Number {
     Timer0,
@@ -73,6 +107,56 @@ pub mod config {
         Duty20Bit,
     }
 
+    impl Duty {
+        /// Number of bits of the duty resolution
+        pub fn bits(self) -> u8 {
+            self as u8
+        }
+
+        /// The duty resolution with `bits` bits, `None` if it isn't supported
+        /// by the chip
+        pub fn from_bits(bits: u8) -> Option<Self> {
+            let duty = match bits {
+                1 => Self::Duty1Bit,
+                2 => Self::Duty2Bit,
+                3 => Self::Duty3Bit,
+                4 => Self::Duty4Bit,
+                5 => Self::Duty5Bit,
+                6 => Self::Duty6Bit,
+                7 => Self::Duty7Bit,
+                8 => Self::Duty8Bit,
+                9 => Self::Duty9Bit,
+                10 => Self::Duty10Bit,
+                11 => Self::Duty11Bit,
+                12 => Self::Duty12Bit,
+                13 => Self::Duty13Bit,
+                14 => Self::Duty14Bit,
+                #[cfg(esp32)]
+                15 => Self::Duty15Bit,
+                #[cfg(esp32)]
+                16 => Self::Duty16Bit,
+                #[cfg(esp32)]
+                17 => Self::Duty17Bit,
+                #[cfg(esp32)]
+                18 => Self::Duty18Bit,
+                #[cfg(esp32)]
+                19 => Self::Duty19Bit,
+                #[cfg(esp32)]
+                20 => Self::Duty20Bit,
+                _ => return None,
+            };
+            Some(duty)
+        }
+
+        /// Number of counts in a PWM period, i.e. `1 << bits`
+        ///
+        /// A raw duty of `max_count()` keeps the output high for the whole
+        /// period (100%).
+        pub fn max_count(self) -> u32 {
+            1 << self.bits()
+        }
+    }
+
     /// Timer configuration
     #[derive(Copy, Clone)]
     pub struct Config<CS> {
@@ -84,28 +168,160 @@ pub mod config {
 
 /// Trait defining the type of timer source
 pub trait TimerSpeed: Speed {
-    type ClockSourceType: Sync;
+    type ClockSourceType: Sync + PartialEq + Copy + Into<ClockSource>;
+
+    /// The timer clock source selecting `source`, `None` if timers of this
+    /// speed mode can't be clocked from it
+    #[doc(hidden)]
+    fn clock_source_type(source: ClockSource) -> Option<Self::ClockSourceType>;
 }
 
 /// Timer source type for LowSpeed timers
 impl TimerSpeed for LowSpeed {
     type ClockSourceType = LSClockSource;
+
+    fn clock_source_type(source: ClockSource) -> Option<LSClockSource> {
+        match source {
+            ClockSource::Apb => Some(LSClockSource::APBClk),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(esp32)]
 /// Timer source type for HighSpeed timers
 impl TimerSpeed for HighSpeed {
     type ClockSourceType = HSClockSource;
+
+    fn clock_source_type(source: ClockSource) -> Option<HSClockSource> {
+        match source {
+            ClockSource::Apb => Some(HSClockSource::APBClk),
+            _ => None,
+        }
+    }
+}
+
+/// Frequency of REF_TICK, used if the divisor for APB_CLK is too high
+const REF_TICK_FREQ: u32 = 1_000_000;
+
+/// Calculate the divisor for the given source clock, duty resolution and
+/// frequency, and whether REF_TICK has to be used to reach it
+///
+/// The divisor is a fixed point number with 8 fractional bits (the lower bits
+/// of `div_num`/`clk_div`), it's rounded to the nearest value so frequencies
+/// which aren't an integer division of the source clock are matched as close
+/// as possible.
+fn divisor_for(src_freq: u32, duty: config::Duty, frequency: u32) -> Result<(u32, bool), Error> {
+    let precision = duty.max_count();
+    let mut use_ref_tick = false;
+
+    let mut divisor = fractional_divisor(src_freq, frequency, precision);
+
+    if divisor > LEDC_TIMER_DIV_NUM_MAX {
+        // APB_CLK results in divisor which too high. Try using REF_TICK as clock
+        // source.
+        use_ref_tick = true;
+        divisor = fractional_divisor(REF_TICK_FREQ, frequency, precision);
+    }
+
+    if divisor >= LEDC_TIMER_DIV_NUM_MAX || divisor < 256 {
+        return Err(Error::Divisor);
+    }
+
+    Ok((divisor as u32, use_ref_tick))
+}
+
+/// `src_freq / (frequency * precision)` with 8 fractional bits, rounded
+fn fractional_divisor(src_freq: u32, frequency: u32, precision: u32) -> u64 {
+    let denominator = frequency as u64 * precision as u64;
+
+    (((src_freq as u64) << 8) + denominator / 2) / denominator
+}
+
+/// The output frequency for the given source clock, duty resolution and
+/// divisor
+fn frequency_for(src_freq: u32, duty: config::Duty, divisor: u32) -> u32 {
+    (((src_freq as u64) << 8) / (divisor as u64 * duty.max_count() as u64)) as u32
+}
+
+/// Snap `requested` to the closest frequency a timer with the given duty
+/// resolution can output from the APB clock (or REF_TICK for low frequencies)
+///
+/// Configuring a timer with the returned frequency and duty resolution always
+/// succeeds. Requests out of range are clamped to the lowest or highest
+/// reachable frequency.
+pub fn nearest_valid_frequency(
+    clocks: &Clocks,
+    duty: config::Duty,
+    requested: HertzU32,
+) -> HertzU32 {
+    let apb_freq = clocks.apb_clock.to_Hz();
+    let requested = requested.raw().max(1);
+    let precision = duty.max_count() as u64;
+
+    let mut nearest: Option<u32> = None;
+    for src_freq in [apb_freq, REF_TICK_FREQ] {
+        let divisor = ((src_freq as u64) << 8) / requested as u64 / precision;
+
+        // the neighbouring divisors give the closest frequency above and below
+        for divisor in [divisor, divisor + 1] {
+            let divisor = divisor.clamp(256, LEDC_TIMER_DIV_NUM_MAX - 1) as u32;
+            let candidate = frequency_for(src_freq, duty, divisor);
+
+            // the truncated frequency has to be accepted by `configure` as well
+            if candidate == 0 || divisor_for(apb_freq, duty, candidate).is_err() {
+                continue;
+            }
+
+            nearest = match nearest {
+                Some(nearest) if nearest.abs_diff(requested) <= candidate.abs_diff(requested) => {
+                    Some(nearest)
+                }
+                _ => Some(candidate),
+            };
+        }
+    }
+
+    HertzU32::from_raw(nearest.unwrap_or(requested))
 }
 
 /// Interface for Timers
 pub trait TimerIFace<S: TimerSpeed>: Sync {
-    /// Return the frequency of the timer
+    /// Return the frequency of the timer's clock source
     fn get_freq(&self, clocks: &Clocks) -> Option<HertzU32>;
 
     /// Configure the timer
     fn configure(&mut self, clocks: &Clocks, config: config::Config<S::ClockSourceType>) -> Result<(), Error>;
 
+    /// Configure the timer and return the frequency actually achieved, which
+    /// differs from the requested one by the quantization error of the divisor
+    fn try_configure(
+        &mut self,
+        clocks: &Clocks,
+        config: config::Config<S::ClockSourceType>,
+    ) -> Result<HertzU32, Error>;
+
+    /// Reconfigure a running timer without glitching the outputs of the
+    /// channels using it
+    ///
+    /// The new divisor and duty resolution are staged and latched by the
+    /// hardware at the next period boundary. Raw channel duty values are kept,
+    /// so if the duty resolution changes the channels' duty should be set
+    /// again afterwards.
+    fn configure_seamless(
+        &mut self,
+        clocks: &Clocks,
+        config: config::Config<S::ClockSourceType>,
+    ) -> Result<(), Error>;
+
+    /// Configure the timer, but keep it in reset until it's started together
+    /// with other timers by [`LEDC::commit`](super::LEDC::commit)
+    fn configure_deferred(
+        &mut self,
+        clocks: &Clocks,
+        config: config::Config<S::ClockSourceType>,
+    ) -> Result<(), Error>;
+
     /// Check if the timer has been configured
     fn is_configured(&self) -> bool;
 
@@ -115,8 +331,14 @@ pub trait TimerIFace<S: TimerSpeed>: Sync {
     /// Return the timer number
     fn get_number(&self) -> Number;
 
+    /// Return the timer frequency, or 0 Hz if not configured
+    fn frequency(&self) -> HertzU32;
+
     /// Return the timer frequency, or 0 if not configured
-    fn get_frequency(&self) -> u32;
+    #[deprecated(note = "use `TimerIFace::frequency` instead")]
+    fn get_frequency(&self) -> u32 {
+        self.frequency().raw()
+    }
 }
 
 /// Interface for HW configuration of timer
@@ -124,60 +346,131 @@ pub trait TimerHW<S: TimerSpeed> {
     /// Get the current source timer frequency from the HW
     fn get_freq_hw(&self, clocks: &Clocks) -> Option<HertzU32>;
 
+    /// Read the clock source the timer is actually using back from the HW,
+    /// `None` if it's none of the supported ones
+    fn read_clock_source_hw(&self) -> Option<S::ClockSourceType>;
+
     /// Configure the HW for the timer
     fn configure_hw(&self, divisor: u32);
 
+    /// Configure the HW for the timer, keeping the timer in reset
+    fn configure_deferred_hw(&self, divisor: u32);
+
+    /// Stage a new divisor and duty resolution for a running timer, without
+    /// resetting or pausing it
+    fn configure_seamless_hw(&self, divisor: u32);
+
     /// Update the timer in HW
     fn update_hw(&self);
+
+    /// Read back the divisor and the duty resolution in bits from the HW
+    fn read_divisor_hw(&self) -> (u32, u8);
+
+    /// Read back the duty resolution from the HW, e.g. for a timer which was
+    /// configured before a deep-sleep restore. `None` if the `duty_res` field
+    /// doesn't hold a supported resolution.
+    fn read_duty_res_hw(&self) -> Option<config::Duty> {
+        config::Duty::from_bits(self.read_divisor_hw().1)
+    }
 }
 
 /// Timer struct
-pub struct Timer<S: TimerSpeed> {
+///
+/// A timer borrows the [`LEDC`](super::LEDC) it was taken from, so it can't
+/// outlive [`LEDC::shutdown`](super::LEDC::shutdown).
+pub struct Timer<'a, S: TimerSpeed> {
     number: Number,
     duty: Option<config::Duty>,
     frequency: u32,
     configured: bool,
     use_ref_tick: bool,
     clock_source: Option<S::ClockSourceType>,
+    _ledc: PhantomData<&'a ()>,
 }
 
-impl<S: TimerSpeed> TimerIFace<S> for Timer<S>
+impl<'a, S: TimerSpeed> TimerIFace<S> for Timer<'a, S>
 where
-    Timer<S>: TimerHW<S>,
+    Timer<'a, S>: TimerHW<S>,
 {
-    /// Return the frequency of the timer
+    /// Return the frequency of the timer's clock source
     fn get_freq(&self, clocks: &Clocks) -> Option<HertzU32> {
         self.get_freq_hw(clocks)
     }
 
     /// Configure the timer
     fn configure(&mut self, clocks: &Clocks, config: config::Config<S::ClockSourceType>) -> Result<(), Error> {
+        self.try_configure(clocks, config).map(|_| ())
+    }
+
+    /// Configure the timer, returning the achieved frequency
+    fn try_configure(
+        &mut self,
+        clocks: &Clocks,
+        config: config::Config<S::ClockSourceType>,
+    ) -> Result<HertzU32, Error> {
         self.duty = Some(config.duty);
         self.clock_source = Some(config.clock_source);
 
-        // TODO: we should return some error here if `unwrap()` fails
-        let src_freq: u32 = self.get_freq(clocks).unwrap().to_Hz();
-        let precision = 1 << config.duty as u32;
-        let frequency: u32 = config.frequency.raw();
-        self.frequency = frequency;
+        let (divisor, use_ref_tick) = self.calculate_divisor(clocks, config.duty, config.frequency)?;
+        self.use_ref_tick = use_ref_tick;
+        self.frequency = config.frequency.raw();
+        let achieved = self.achieved_frequency(clocks, config.duty, divisor)?;
+
+        self.configure_hw(divisor);
+        self.update_hw();
 
-        let mut divisor = ((src_freq as u64) << 8) / frequency as u64 / precision as u64;
+        self.configured = true;
+        super::mark_timer_configured::<S>(self.number);
 
-        if divisor > LEDC_TIMER_DIV_NUM_MAX {
-            // APB_CLK results in divisor which too high. Try using REF_TICK as clock
-            // source.
-            self.use_ref_tick = true;
-            divisor = ((1_000_000 as u64) << 8) / frequency as u64 / precision as u64;
+        Ok(achieved)
+    }
+
+    /// Reconfigure a running timer without glitching its channels
+    fn configure_seamless(
+        &mut self,
+        clocks: &Clocks,
+        config: config::Config<S::ClockSourceType>,
+    ) -> Result<(), Error> {
+        if !self.configured || self.clock_source != Some(config.clock_source) {
+            return Err(Error::Seamless);
         }
 
-        if divisor >= LEDC_TIMER_DIV_NUM_MAX || divisor < 256 {
-            return Err(Error::Divisor);
+        let (divisor, use_ref_tick) = self.calculate_divisor(clocks, config.duty, config.frequency)?;
+        if use_ref_tick != self.use_ref_tick {
+            // Switching the tick source can't be synchronized to the period
+            return Err(Error::Seamless);
         }
 
-        self.configure_hw(divisor as u32);
+        self.duty = Some(config.duty);
+        self.frequency = config.frequency.raw();
+
+        self.configure_seamless_hw(divisor);
         self.update_hw();
 
+        Ok(())
+    }
+
+    /// Configure the timer, keeping it in reset until committed.
+    ///
+    /// Channels bound to the timer stay at their idle level. Once all timers
+    /// (and their channels) are set up, [`LEDC::commit`](super::LEDC::commit)
+    /// starts them, so their periods are aligned.
+    fn configure_deferred(
+        &mut self,
+        clocks: &Clocks,
+        config: config::Config<S::ClockSourceType>,
+    ) -> Result<(), Error> {
+        self.duty = Some(config.duty);
+        self.clock_source = Some(config.clock_source);
+
+        let (divisor, use_ref_tick) = self.calculate_divisor(clocks, config.duty, config.frequency)?;
+        self.use_ref_tick = use_ref_tick;
+        self.frequency = config.frequency.raw();
+
+        self.configure_deferred_hw(divisor);
+
         self.configured = true;
+        super::mark_timer_configured::<S>(self.number);
 
         Ok(())
     }
@@ -198,14 +491,106 @@ where
     }
 
     /// Return the timer frequency
-    fn get_frequency(&self) -> u32 {
-        self.frequency
+    fn frequency(&self) -> HertzU32 {
+        HertzU32::from_raw(self.frequency)
+    }
+}
+
+impl<'a, S: TimerSpeed> Timer<'a, S>
+where
+    Timer<'a, S>: TimerHW<S>,
+{
+    /// Calculate the divisor for the given duty resolution and frequency from
+    /// the current clock source, and whether REF_TICK has to be used to reach
+    /// it
+    fn calculate_divisor(
+        &self,
+        clocks: &Clocks,
+        duty: config::Duty,
+        frequency: HertzU32,
+    ) -> Result<(u32, bool), Error> {
+        let src_freq: u32 = self.source_frequency(clocks)?;
+
+        divisor_for(src_freq, duty, frequency.raw())
+    }
+
+    /// Back-compute the output frequency from a divisor returned by
+    /// [`Self::calculate_divisor`]
+    fn achieved_frequency(
+        &self,
+        clocks: &Clocks,
+        duty: config::Duty,
+        divisor: u32,
+    ) -> Result<HertzU32, Error> {
+        let src_freq: u32 = if self.use_ref_tick {
+            REF_TICK_FREQ
+        } else {
+            self.source_frequency(clocks)?
+        };
+
+        Ok(HertzU32::from_raw(frequency_for(src_freq, duty, divisor)))
+    }
+
+    /// Frequency of the timer's clock source in Hz, fails with
+    /// [`Error::ClockSource`] if no clock source is set or its frequency is
+    /// unknown
+    fn source_frequency(&self, clocks: &Clocks) -> Result<u32, Error> {
+        self.get_freq_hw(clocks)
+            .map(|freq| freq.to_Hz())
+            .ok_or(Error::ClockSource)
+    }
+
+    /// The output frequency computed from the divisor programmed in the HW and
+    /// the current clocks, or 0 Hz if not configured
+    ///
+    /// [`TimerIFace::frequency`] returns the frequency the timer was
+    /// configured with, after the APB clock changed (e.g. by dynamic frequency
+    /// scaling) this is the frequency actually output.
+    pub fn actual_frequency(&self, clocks: &Clocks) -> HertzU32 {
+        if !self.configured {
+            return HertzU32::from_raw(0);
+        }
+
+        let src_freq: u32 = if self.use_ref_tick {
+            REF_TICK_FREQ
+        } else {
+            match self.get_freq_hw(clocks) {
+                Some(freq) => freq.to_Hz(),
+                None => return HertzU32::from_raw(0),
+            }
+        };
+        let (divisor, duty_bits) = self.read_divisor_hw();
+        if divisor == 0 {
+            return HertzU32::from_raw(0);
+        }
+
+        HertzU32::from_raw((((src_freq as u64) << 8) / ((divisor as u64) << duty_bits)) as u32)
+    }
+
+    /// The clock source the timer was configured with
+    pub(crate) fn clock_source(&self) -> Option<S::ClockSourceType> {
+        self.clock_source
+    }
+
+    /// Integer part of the divisor programmed in the HW
+    ///
+    /// Together with [`Timer::divisor_fraction`] this explains the remaining
+    /// deviation of [`Timer::actual_frequency`] from the requested frequency.
+    pub fn divisor_integer(&self) -> u16 {
+        (self.read_divisor_hw().0 >> 8) as u16
+    }
+
+    /// Fractional part of the divisor programmed in the HW, in 1/256
+    ///
+    /// 0 if the requested frequency was reached by an integer divisor.
+    pub fn divisor_fraction(&self) -> u8 {
+        self.read_divisor_hw().0 as u8
     }
 }
 
-impl<S: TimerSpeed> Timer<S> {
+impl<'a, S: TimerSpeed> Timer<'a, S> {
     /// Create a new intance of a timer
-    pub fn new(
+    pub(crate) fn new(
         number: Number,
     ) -> Self {
         Timer {
@@ -215,17 +600,80 @@ impl<S: TimerSpeed> Timer<S> {
             configured: false,
             use_ref_tick: false,
             clock_source: None,
+            _ledc: PhantomData,
         }
     }
 }
 
+impl<'a, S: TimerSpeed> Drop for Timer<'a, S> {
+    fn drop(&mut self) {
+        super::release_timer::<S>(self.number);
+    }
+}
+
 /// Timer HW implementation for LowSpeed timers
-impl TimerHW<LowSpeed> for Timer<LowSpeed> {
+impl<'a> TimerHW<LowSpeed> for Timer<'a, LowSpeed> {
     /// Get the current source timer frequency from the HW
     fn get_freq_hw(&self, clocks: &Clocks) -> Option<fugit::HertzU32> {
-        self.clock_source.map(|cs| match cs {
-            LSClockSource::APBClk => clocks.apb_clock,
-        })
+        self.clock_source.and_then(|cs| ClockSource::from(cs).frequency(clocks))
+    }
+
+    #[cfg(esp32)]
+    /// Read the clock source the timer is actually using back from the HW
+    fn read_clock_source_hw(&self) -> Option<LSClockSource> {
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+        // tick_sel selects the global slow clock, which is APB_CLK when
+        // apb_clk_sel is set (REF_TICK otherwise)
+        let tick_sel = match self.number {
+            Number::Timer0 => ledc.lstimer0_conf.read().tick_sel().bit_is_set(),
+            Number::Timer1 => ledc.lstimer1_conf.read().tick_sel().bit_is_set(),
+            Number::Timer2 => ledc.lstimer2_conf.read().tick_sel().bit_is_set(),
+            Number::Timer3 => ledc.lstimer3_conf.read().tick_sel().bit_is_set(),
+        };
+
+        if tick_sel && ledc.conf.read().apb_clk_sel().bit_is_set() {
+            Some(LSClockSource::APBClk)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(esp32))]
+    /// Read the clock source the timer is actually using back from the HW
+    fn read_clock_source_hw(&self) -> Option<LSClockSource> {
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+        // tick_sel selects REF_TICK, otherwise the global LEDC clock is used
+        let ref_tick = match self.number {
+            Number::Timer0 => ledc.timer0_conf.read().tick_sel().bit_is_set(),
+            Number::Timer1 => ledc.timer1_conf.read().tick_sel().bit_is_set(),
+            Number::Timer2 => ledc.timer2_conf.read().tick_sel().bit_is_set(),
+            Number::Timer3 => ledc.timer3_conf.read().tick_sel().bit_is_set(),
+        };
+
+        #[cfg(not(any(esp32c6, esp32h2)))]
+        let global_apb = ledc.conf.read().apb_clk_sel().bits() == 1;
+        #[cfg(esp32c6)]
+        let global_apb = unsafe { &*crate::peripherals::PCR::ptr() }
+            .ledc_sclk_conf
+            .read()
+            .ledc_sclk_sel()
+            .bits()
+            == 1;
+        #[cfg(esp32h2)]
+        let global_apb = unsafe { &*crate::peripherals::PCR::ptr() }
+            .ledc_sclk_conf
+            .read()
+            .ledc_sclk_sel()
+            .bits()
+            == 0;
+
+        if !ref_tick && global_apb {
+            Some(LSClockSource::APBClk)
+        } else {
+            None
+        }
     }
 
     #[cfg(esp32)]
@@ -287,6 +735,65 @@ impl TimerHW<LowSpeed> for Timer<LowSpeed> {
         };
     }
 
+    #[cfg(esp32)]
+    /// Configure the HW for the timer, keeping the timer in reset
+    fn configure_deferred_hw(&self, divisor: u32) {
+        let duty = self.duty.unwrap() as u8;
+        let use_apb = !self.use_ref_tick;
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+        match self.number {
+            Number::Timer0 => ledc.lstimer0_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(use_apb)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .div_num()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+            Number::Timer1 => ledc.lstimer1_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(use_apb)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .div_num()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+            Number::Timer2 => ledc.lstimer2_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(use_apb)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .div_num()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+            Number::Timer3 => ledc.lstimer3_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(use_apb)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .div_num()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+        };
+    }
+
     #[cfg(not(esp32))]
     /// Configure the HW for the timer
     fn configure_hw(&self, divisor: u32) {
@@ -346,6 +853,109 @@ impl TimerHW<LowSpeed> for Timer<LowSpeed> {
         };
     }
 
+    #[cfg(not(esp32))]
+    /// Configure the HW for the timer, keeping the timer in reset
+    fn configure_deferred_hw(&self, divisor: u32) {
+        let duty = self.duty.unwrap() as u8;
+        let use_ref_tick = self.use_ref_tick;
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+        match self.number {
+            Number::Timer0 => ledc.timer0_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(use_ref_tick)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .clk_div()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+            Number::Timer1 => ledc.timer1_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(use_ref_tick)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .clk_div()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+            Number::Timer2 => ledc.timer2_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(use_ref_tick)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .clk_div()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+            Number::Timer3 => ledc.timer3_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(use_ref_tick)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .clk_div()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+        };
+    }
+
+    #[cfg(esp32)]
+    /// Stage a new divisor and duty resolution for a running timer
+    fn configure_seamless_hw(&self, divisor: u32) {
+        let duty = self.duty.unwrap() as u8;
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+        match self.number {
+            Number::Timer0 => ledc.lstimer0_conf.modify(|_, w| unsafe {
+                w.div_num().bits(divisor).duty_res().bits(duty)
+            }),
+            Number::Timer1 => ledc.lstimer1_conf.modify(|_, w| unsafe {
+                w.div_num().bits(divisor).duty_res().bits(duty)
+            }),
+            Number::Timer2 => ledc.lstimer2_conf.modify(|_, w| unsafe {
+                w.div_num().bits(divisor).duty_res().bits(duty)
+            }),
+            Number::Timer3 => ledc.lstimer3_conf.modify(|_, w| unsafe {
+                w.div_num().bits(divisor).duty_res().bits(duty)
+            }),
+        };
+    }
+
+    #[cfg(not(esp32))]
+    /// Stage a new divisor and duty resolution for a running timer
+    fn configure_seamless_hw(&self, divisor: u32) {
+        let duty = self.duty.unwrap() as u8;
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+        match self.number {
+            Number::Timer0 => ledc.timer0_conf.modify(|_, w| unsafe {
+                w.clk_div().bits(divisor).duty_res().bits(duty)
+            }),
+            Number::Timer1 => ledc.timer1_conf.modify(|_, w| unsafe {
+                w.clk_div().bits(divisor).duty_res().bits(duty)
+            }),
+            Number::Timer2 => ledc.timer2_conf.modify(|_, w| unsafe {
+                w.clk_div().bits(divisor).duty_res().bits(duty)
+            }),
+            Number::Timer3 => ledc.timer3_conf.modify(|_, w| unsafe {
+                w.clk_div().bits(divisor).duty_res().bits(duty)
+            }),
+        };
+    }
+
     #[cfg(esp32)]
     /// Update the timer in HW
     fn update_hw(&self) {
@@ -369,23 +979,88 @@ impl TimerHW<LowSpeed> for Timer<LowSpeed> {
             Number::Timer3 => ledc.timer3_conf.modify(|_, w| w.para_up().set_bit()),
         };
     }
+
+    #[cfg(esp32)]
+    /// Read back the divisor and the duty resolution from the HW
+    fn read_divisor_hw(&self) -> (u32, u8) {
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+        match self.number {
+            Number::Timer0 => {
+                let conf = ledc.lstimer0_conf.read();
+                (conf.div_num().bits(), conf.duty_res().bits())
+            }
+            Number::Timer1 => {
+                let conf = ledc.lstimer1_conf.read();
+                (conf.div_num().bits(), conf.duty_res().bits())
+            }
+            Number::Timer2 => {
+                let conf = ledc.lstimer2_conf.read();
+                (conf.div_num().bits(), conf.duty_res().bits())
+            }
+            Number::Timer3 => {
+                let conf = ledc.lstimer3_conf.read();
+                (conf.div_num().bits(), conf.duty_res().bits())
+            }
+        }
+    }
+
+    #[cfg(not(esp32))]
+    /// Read back the divisor and the duty resolution from the HW
+    fn read_divisor_hw(&self) -> (u32, u8) {
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+        match self.number {
+            Number::Timer0 => {
+                let conf = ledc.timer0_conf.read();
+                (conf.clk_div().bits(), conf.duty_res().bits())
+            }
+            Number::Timer1 => {
+                let conf = ledc.timer1_conf.read();
+                (conf.clk_div().bits(), conf.duty_res().bits())
+            }
+            Number::Timer2 => {
+                let conf = ledc.timer2_conf.read();
+                (conf.clk_div().bits(), conf.duty_res().bits())
+            }
+            Number::Timer3 => {
+                let conf = ledc.timer3_conf.read();
+                (conf.clk_div().bits(), conf.duty_res().bits())
+            }
+        }
+    }
 }
 
 #[cfg(esp32)]
 /// Timer HW implementation for HighSpeed timers
-impl TimerHW<HighSpeed> for Timer<HighSpeed> {
+impl<'a> TimerHW<HighSpeed> for Timer<'a, HighSpeed> {
     /// Get the current source timer frequency from the HW
     fn get_freq_hw(&self, clocks: &Clocks) -> Option<HertzU32> {
-        self.clock_source.map(|cs| match cs {
-            // TODO RefTick HSClockSource::RefTick => clocks.apb_clock,
-            HSClockSource::APBClk => clocks.apb_clock,
-        })
+        self.clock_source.and_then(|cs| ClockSource::from(cs).frequency(clocks))
+    }
+
+    /// Read the clock source the timer is actually using back from the HW
+    fn read_clock_source_hw(&self) -> Option<HSClockSource> {
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+        // tick_sel selects APB_CLK, REF_TICK otherwise
+        let apb = match self.number {
+            Number::Timer0 => ledc.hstimer0_conf.read().tick_sel().bit_is_set(),
+            Number::Timer1 => ledc.hstimer1_conf.read().tick_sel().bit_is_set(),
+            Number::Timer2 => ledc.hstimer2_conf.read().tick_sel().bit_is_set(),
+            Number::Timer3 => ledc.hstimer3_conf.read().tick_sel().bit_is_set(),
+        };
+
+        if apb {
+            Some(HSClockSource::APBClk)
+        } else {
+            None
+        }
     }
 
     /// Configure the HW for the timer
     fn configure_hw(&self, divisor: u32) {
         let duty = self.duty.unwrap() as u8;
-        let sel_hstimer = self.clock_source == Some(HSClockSource::APBClk);
+        // tick_sel selects APB_CLK, REF_TICK for frequencies too low for it
+        let sel_hstimer = !self.use_ref_tick;
         let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
 
         match self.number {
@@ -440,8 +1115,213 @@ impl TimerHW<HighSpeed> for Timer<HighSpeed> {
         };
     }
 
+    /// Configure the HW for the timer, keeping the timer in reset
+    fn configure_deferred_hw(&self, divisor: u32) {
+        let duty = self.duty.unwrap() as u8;
+        // tick_sel selects APB_CLK, REF_TICK for frequencies too low for it
+        let sel_hstimer = !self.use_ref_tick;
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+        match self.number {
+            Number::Timer0 => ledc.hstimer0_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(sel_hstimer)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .div_num()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+            Number::Timer1 => ledc.hstimer1_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(sel_hstimer)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .div_num()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+            Number::Timer2 => ledc.hstimer2_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(sel_hstimer)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .div_num()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+            Number::Timer3 => ledc.hstimer3_conf.modify(|_, w| unsafe {
+                w.tick_sel()
+                    .bit(sel_hstimer)
+                    .rst()
+                    .set_bit()
+                    .pause()
+                    .clear_bit()
+                    .div_num()
+                    .bits(divisor)
+                    .duty_res()
+                    .bits(duty)
+            }),
+        };
+    }
+
+    /// Stage a new divisor and duty resolution for a running timer
+    ///
+    /// HS timers have no update mechanism, the new values take effect right
+    /// away
+    fn configure_seamless_hw(&self, divisor: u32) {
+        let duty = self.duty.unwrap() as u8;
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+        match self.number {
+            Number::Timer0 => ledc.hstimer0_conf.modify(|_, w| unsafe {
+                w.div_num().bits(divisor).duty_res().bits(duty)
+            }),
+            Number::Timer1 => ledc.hstimer1_conf.modify(|_, w| unsafe {
+                w.div_num().bits(divisor).duty_res().bits(duty)
+            }),
+            Number::Timer2 => ledc.hstimer2_conf.modify(|_, w| unsafe {
+                w.div_num().bits(divisor).duty_res().bits(duty)
+            }),
+            Number::Timer3 => ledc.hstimer3_conf.modify(|_, w| unsafe {
+                w.div_num().bits(divisor).duty_res().bits(duty)
+            }),
+        };
+    }
+
     /// Update the timer in HW
     fn update_hw(&self) {
-        // Nothing to do for HS timers
+        // Nothing to do for HS timers, they don't latch their configuration
+        // and apply changes right away
+    }
+
+    /// Read back the divisor and the duty resolution from the HW
+    fn read_divisor_hw(&self) -> (u32, u8) {
+        let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+        match self.number {
+            Number::Timer0 => {
+                let conf = ledc.hstimer0_conf.read();
+                (conf.div_num().bits(), conf.duty_res().bits())
+            }
+            Number::Timer1 => {
+                let conf = ledc.hstimer1_conf.read();
+                (conf.div_num().bits(), conf.duty_res().bits())
+            }
+            Number::Timer2 => {
+                let conf = ledc.hstimer2_conf.read();
+                (conf.div_num().bits(), conf.duty_res().bits())
+            }
+            Number::Timer3 => {
+                let conf = ledc.hstimer3_conf.read();
+                (conf.div_num().bits(), conf.duty_res().bits())
+            }
+        }
+    }
+}
+
+/// Release the given timers from reset at once, latching their configuration
+pub(crate) fn commit_hw<S: TimerSpeed>(timers: &[Number]) {
+    let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+    #[cfg(esp32)]
+    if S::IS_HS {
+        for timer in timers {
+            match timer {
+                Number::Timer0 => ledc.hstimer0_conf.modify(|_, w| w.rst().clear_bit()),
+                Number::Timer1 => ledc.hstimer1_conf.modify(|_, w| w.rst().clear_bit()),
+                Number::Timer2 => ledc.hstimer2_conf.modify(|_, w| w.rst().clear_bit()),
+                Number::Timer3 => ledc.hstimer3_conf.modify(|_, w| w.rst().clear_bit()),
+            };
+        }
+        return;
     }
+
+    for timer in timers {
+        #[cfg(esp32)]
+        match timer {
+            Number::Timer0 => ledc
+                .lstimer0_conf
+                .modify(|_, w| w.rst().clear_bit().para_up().set_bit()),
+            Number::Timer1 => ledc
+                .lstimer1_conf
+                .modify(|_, w| w.rst().clear_bit().para_up().set_bit()),
+            Number::Timer2 => ledc
+                .lstimer2_conf
+                .modify(|_, w| w.rst().clear_bit().para_up().set_bit()),
+            Number::Timer3 => ledc
+                .lstimer3_conf
+                .modify(|_, w| w.rst().clear_bit().para_up().set_bit()),
+        };
+        #[cfg(not(esp32))]
+        match timer {
+            Number::Timer0 => ledc
+                .timer0_conf
+                .modify(|_, w| w.rst().clear_bit().para_up().set_bit()),
+            Number::Timer1 => ledc
+                .timer1_conf
+                .modify(|_, w| w.rst().clear_bit().para_up().set_bit()),
+            Number::Timer2 => ledc
+                .timer2_conf
+                .modify(|_, w| w.rst().clear_bit().para_up().set_bit()),
+            Number::Timer3 => ledc
+                .timer3_conf
+                .modify(|_, w| w.rst().clear_bit().para_up().set_bit()),
+        };
+    }
+}
+
+/// Pause or resume a timer in HW
+pub(crate) fn pause_hw<S: TimerSpeed>(number: Number, paused: bool) {
+    let ledc = unsafe { &*crate::peripherals::LEDC::PTR };
+
+    #[cfg(esp32)]
+    if S::IS_HS {
+        match number {
+            Number::Timer0 => ledc.hstimer0_conf.modify(|_, w| w.pause().bit(paused)),
+            Number::Timer1 => ledc.hstimer1_conf.modify(|_, w| w.pause().bit(paused)),
+            Number::Timer2 => ledc.hstimer2_conf.modify(|_, w| w.pause().bit(paused)),
+            Number::Timer3 => ledc.hstimer3_conf.modify(|_, w| w.pause().bit(paused)),
+        };
+        return;
+    }
+
+    #[cfg(esp32)]
+    match number {
+        Number::Timer0 => ledc
+            .lstimer0_conf
+            .modify(|_, w| w.pause().bit(paused).para_up().set_bit()),
+        Number::Timer1 => ledc
+            .lstimer1_conf
+            .modify(|_, w| w.pause().bit(paused).para_up().set_bit()),
+        Number::Timer2 => ledc
+            .lstimer2_conf
+            .modify(|_, w| w.pause().bit(paused).para_up().set_bit()),
+        Number::Timer3 => ledc
+            .lstimer3_conf
+            .modify(|_, w| w.pause().bit(paused).para_up().set_bit()),
+    };
+    #[cfg(not(esp32))]
+    match number {
+        Number::Timer0 => ledc
+            .timer0_conf
+            .modify(|_, w| w.pause().bit(paused).para_up().set_bit()),
+        Number::Timer1 => ledc
+            .timer1_conf
+            .modify(|_, w| w.pause().bit(paused).para_up().set_bit()),
+        Number::Timer2 => ledc
+            .timer2_conf
+            .modify(|_, w| w.pause().bit(paused).para_up().set_bit()),
+        Number::Timer3 => ledc
+            .timer3_conf
+            .modify(|_, w| w.pause().bit(paused).para_up().set_bit()),
+    };
 }
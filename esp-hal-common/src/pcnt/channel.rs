@@ -64,6 +64,9 @@ impl PcntSource {
             source: pin.number(),
         }
     }
+    pub(crate) fn from_gpio_num(gpio_num: u8) -> Self {
+        Self { source: gpio_num }
+    }
     pub fn always_high() -> Self {
         Self { source: ONE_INPUT }
     }
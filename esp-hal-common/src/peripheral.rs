@@ -184,6 +184,97 @@ pub(crate) mod sealed {
     pub trait Sealed {}
 }
 
+/// Panic if a driver currently owns the peripheral with the register block at
+/// `ptr`, e.g. `assert_not_owned(LEDC::PTR as _)` before accessing the LEDC
+/// registers directly.
+///
+/// Drivers track the peripherals they own in debug builds only, in release
+/// builds this does nothing.
+pub fn assert_not_owned(ptr: *const ()) {
+    #[cfg(debug_assertions)]
+    if let Some(name) = owner::owner(ptr as usize) {
+        panic!("{} is already taken by a driver", name);
+    }
+    #[cfg(not(debug_assertions))]
+    let _ = ptr;
+}
+
+/// Marks a peripheral as owned by a driver while alive (in debug builds)
+pub(crate) struct DriverGuard {
+    #[cfg(debug_assertions)]
+    ptr: usize,
+}
+
+impl DriverGuard {
+    /// Claim the peripheral with the register block at `ptr`, panics if
+    /// another driver owns it already
+    pub(crate) fn new(ptr: *const (), name: &'static str) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            owner::claim(ptr as usize, name);
+            Self { ptr: ptr as usize }
+        }
+        #[cfg(not(debug_assertions))]
+        {
+            let _ = (ptr, name);
+            Self {}
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Drop for DriverGuard {
+    fn drop(&mut self) {
+        owner::release(self.ptr);
+    }
+}
+
+#[cfg(debug_assertions)]
+mod owner {
+    use core::cell::RefCell;
+
+    use critical_section::Mutex;
+
+    const MAX_OWNED: usize = 16;
+
+    static OWNED: Mutex<RefCell<[Option<(usize, &'static str)>; MAX_OWNED]>> =
+        Mutex::new(RefCell::new([None; MAX_OWNED]));
+
+    pub(super) fn owner(ptr: usize) -> Option<&'static str> {
+        critical_section::with(|cs| {
+            OWNED
+                .borrow_ref(cs)
+                .iter()
+                .flatten()
+                .find(|(owned, _)| *owned == ptr)
+                .map(|(_, name)| *name)
+        })
+    }
+
+    pub(super) fn claim(ptr: usize, name: &'static str) {
+        critical_section::with(|cs| {
+            let mut owned = OWNED.borrow_ref_mut(cs);
+            if owned.iter().flatten().any(|(owned, _)| *owned == ptr) {
+                panic!("{} is already taken by a driver", name);
+            }
+            // running out of slots only disables the check
+            if let Some(slot) = owned.iter_mut().find(|slot| slot.is_none()) {
+                *slot = Some((ptr, name));
+            }
+        })
+    }
+
+    pub(super) fn release(ptr: usize) {
+        critical_section::with(|cs| {
+            for slot in OWNED.borrow_ref_mut(cs).iter_mut() {
+                if matches!(slot, Some((owned, _)) if *owned == ptr) {
+                    *slot = None;
+                }
+            }
+        })
+    }
+}
+
 mod peripheral_macros {
     #[macro_export]
     macro_rules! peripherals {
@@ -207,18 +298,32 @@ mod peripheral_macros {
 
             impl Peripherals {
                 /// Returns all the peripherals *once*
+                ///
+                /// # Panics
+                ///
+                /// If called more than once, see [`Peripherals::try_take`].
                 #[inline]
                 pub fn take() -> Self {
+                    match Self::try_take() {
+                        Some(peripherals) => peripherals,
+                        None => panic!("init called more than once!"),
+                    }
+                }
+
+                /// Returns all the peripherals *once*, `None` if they were
+                /// already taken
+                #[inline]
+                pub fn try_take() -> Option<Self> {
 
                     #[no_mangle]
                     static mut _ESP_HAL_DEVICE_PERIPHERALS: bool = false;
 
                     critical_section::with(|_| unsafe {
                         if _ESP_HAL_DEVICE_PERIPHERALS {
-                            panic!("init called more than once!")
+                            return None;
                         }
                         _ESP_HAL_DEVICE_PERIPHERALS = true;
-                        Self::steal()
+                        Some(Self::steal())
                     })
                 }
             }
@@ -55,6 +55,7 @@ pub use crate::gpio::{
 };
 #[cfg(any(i2c0, i2c1))]
 pub use crate::i2c::Instance as _esp_hal_i2c_Instance;
+pub use crate::interrupt::InterruptClearable as _esp_hal_interrupt_InterruptClearable;
 #[cfg(ledc)]
 pub use crate::ledc::{
     channel::{
@@ -1,4 +1,4 @@
-use crate::rtc_cntl::SocResetReason;
+use crate::{rtc_cntl::SocResetReason, Cpu};
 
 pub enum SleepSource {
     /// In case of deep sleep, reset was not caused by exit from deep sleep
@@ -78,6 +78,15 @@ pub fn get_reset_reason() -> Option<SocResetReason> {
     crate::rtc_cntl::get_reset_reason(crate::get_core())
 }
 
+/// The reset reason of the given core
+///
+/// On the ESP32 and ESP32-S3 each core has its own reset cause, e.g. a reset
+/// by the MWDT of the PRO core is reported as the PRO core's reset reason
+/// while the APP core was reset along with it.
+pub fn get_reset_reason_for(cpu: Cpu) -> Option<SocResetReason> {
+    crate::rtc_cntl::get_reset_reason(cpu)
+}
+
 pub fn get_wakeup_cause() -> SleepSource {
     crate::rtc_cntl::get_wakeup_cause()
 }
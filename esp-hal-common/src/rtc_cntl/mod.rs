@@ -121,6 +121,7 @@ pub struct Rtc<'d> {
     pub rwdt: Rwdt,
     #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
     pub swd: Swd,
+    counter_base: u64,
 }
 
 impl<'d> Rtc<'d> {
@@ -133,6 +134,7 @@ impl<'d> Rtc<'d> {
             rwdt: Rwdt::default(),
             #[cfg(any(esp32c2, esp32c3, esp32c6, esp32h2, esp32s3))]
             swd: Swd::new(),
+            counter_base: 0,
         }
     }
 
@@ -144,11 +146,216 @@ impl<'d> Rtc<'d> {
 
     /// read the current value of the rtc time registers.
     pub fn get_time_raw(&self) -> u64 {
-        #[cfg(not(any(esp32c6, esp32h2)))]
+        read_time_raw()
+    }
+
+    /// Read the RTC timer ticks since the last call and restart the count
+    ///
+    /// The 48 bit RTC timer is free-running and can't be written, so the
+    /// counter is reset in software: the value latched by a single read is
+    /// both the end of the current and the start of the next interval. No
+    /// ticks are lost or counted twice between consecutive calls. The first
+    /// call returns the ticks since the RTC timer started counting (power-on,
+    /// deep sleep doesn't reset it).
+    ///
+    /// The RTC timer keeps counting in light sleep, so a wakeup only affects
+    /// which interval its ticks are counted in: the read is done with
+    /// interrupts disabled, a wakeup interrupt arriving meanwhile is handled
+    /// after the read and its ticks are part of the next interval.
+    pub fn read_and_reset_counter(&mut self) -> u64 {
+        let now = read_time_raw();
+        let ticks = now.wrapping_sub(self.counter_base) & 0xffff_ffff_ffff;
+        self.counter_base = now;
+
+        ticks
+    }
+
+    /// read the current value of the rtc time registers in microseconds.
+    pub fn get_time_us(&self) -> u64 {
+        time_us()
+    }
+
+    /// read the current value of the rtc time registers in milliseconds
+    pub fn get_time_ms(&self) -> u64 {
+        self.get_time_raw() * 1_000 / RtcClock::get_slow_freq().frequency().to_Hz() as u64
+    }
+
+    /// The current time of the RTC timer
+    ///
+    /// Unlike the embassy time driver the RTC timer keeps counting in light
+    /// sleep, but it isn't monotonic across a change of the RTC SLOW_CLK
+    /// source or frequency since the raw counter is converted using the
+    /// current slow clock frequency.
+    pub fn now(&self) -> RtcInstant {
+        RtcInstant::from_ticks(time_us())
+    }
+
+    /// Hold the state of all pads, e.g. before entering a sleep mode
+    ///
+    /// The configuration and level of all GPIOs are latched, so the
+    /// transition into sleep can't cause glitches: the RTC pads (GPIO0 -
+    /// GPIO5 on the ESP32-C2/C3, GPIO0 - GPIO21 on the ESP32-S2/S3) via their
+    /// pad hold, all other pads via the digital pad force hold. Every pad
+    /// keeps its state until [`Rtc::unhold_all_gpios`] is called, also when a
+    /// driver tries to reconfigure it.
+    #[cfg(any(esp32c2, esp32c3, esp32s2, esp32s3))]
+    pub fn hold_all_gpios(&mut self) {
         let rtc_cntl = unsafe { &*RTC_CNTL::ptr() };
-        #[cfg(any(esp32c6, esp32h2))]
-        let rtc_cntl = unsafe { &*LP_TIMER::ptr() };
 
+        rtc_cntl
+            .pad_hold
+            .modify(|r, w| unsafe { w.bits(r.bits() | RTC_PAD_HOLD_MASK) });
+        rtc_cntl.dig_iso.modify(|_, w| {
+            w.dg_pad_force_unhold()
+                .clear_bit()
+                .dg_pad_force_hold()
+                .set_bit()
+        });
+    }
+
+    /// Release the hold of all pads, see [`Rtc::hold_all_gpios`]
+    ///
+    /// This also releases the holds of single pins set up via
+    /// [`OutputPin::persist_through_reset`](crate::gpio::OutputPin::persist_through_reset),
+    /// call it after the drivers were initialized again, so they take over
+    /// the pins without a glitch.
+    #[cfg(any(esp32c2, esp32c3, esp32s2, esp32s3))]
+    pub fn unhold_all_gpios(&mut self) {
+        let rtc_cntl = unsafe { &*RTC_CNTL::ptr() };
+
+        rtc_cntl.dig_iso.modify(|_, w| w.dg_pad_force_hold().clear_bit());
+        rtc_cntl
+            .pad_hold
+            .modify(|r, w| unsafe { w.bits(r.bits() & !RTC_PAD_HOLD_MASK) });
+        rtc_cntl.dig_pad_hold.write(|w| unsafe { w.bits(0) });
+    }
+
+    /// Set the time between two runs of the ULP coprocessor
+    ///
+    /// The ULP timer is started again after the program executed `halt`, so
+    /// this is the time from the end of one run to the start of the next. The
+    /// period is converted to RTC SLOW_CLK cycles using the current slow
+    /// clock frequency.
+    #[cfg(esp32)]
+    pub fn set_ulp_wakeup_period(&mut self, period: MicrosDurationU64) {
+        let sens = unsafe { &*crate::peripherals::SENS::ptr() };
+
+        let slow_freq = RtcClock::get_slow_freq().frequency().to_Hz() as u64;
+        let cycles = (period.to_micros() * slow_freq / 1_000_000).min(u32::MAX as u64);
+
+        sens.ulp_cp_sleep_cyc0
+            .write(|w| unsafe { w.sleep_cycles_s0().bits(cycles as u32) });
+    }
+
+    /// Load a ULP coprocessor program and start running it periodically
+    ///
+    /// `program` is a flat binary image of the ULP FSM program (text followed
+    /// by data), linked to be loaded at the very start of the RTC slow memory
+    /// (0x5000_0000) with the entry point at offset 0. All addresses used by
+    /// the program are relative to this, in 32 bit words. Variables shared
+    /// with the main cores are accessed in the RTC slow memory at the same
+    /// offset, only the lower 16 bits of each word are written by the ULP.
+    ///
+    /// The program has to fit into the RTC slow memory reserved for the ULP
+    /// (4K with the `ulp` feature of `esp32-hal`, otherwise none), which the
+    /// linker keeps free of the HAL's `.rtc_slow` sections. The ULP timer
+    /// runs with the period set via [`Rtc::set_ulp_wakeup_period`].
+    #[cfg(esp32)]
+    pub fn start_ulp(&mut self, program: &[u8]) -> Result<(), UlpError> {
+        extern "C" {
+            static RESERVE_RTC_SLOW: u8;
+        }
+
+        let reserved = unsafe { &RESERVE_RTC_SLOW as *const u8 as usize };
+        if program.is_empty() || program.len() % 4 != 0 {
+            return Err(UlpError::InvalidProgram);
+        }
+        if program.len() > reserved {
+            return Err(UlpError::ProgramTooLarge);
+        }
+
+        let rtc_cntl = unsafe { &*RTC_CNTL::ptr() };
+        let sens = unsafe { &*crate::peripherals::SENS::ptr() };
+
+        // stop the ULP timer and wait for at least one RTC SLOW_CLK cycle
+        rtc_cntl
+            .state0
+            .modify(|_, w| w.ulp_cp_slp_timer_en().clear_bit());
+        unsafe { ets_delay_us(10) };
+
+        let mem = ULP_MEMORY_START as *mut u32;
+        for (i, word) in program.chunks_exact(4).enumerate() {
+            let word = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+            unsafe { mem.add(i).write_volatile(word) };
+        }
+
+        // start at the first word, by the timer instead of by software
+        sens.sar_start_force.modify(|_, w| unsafe {
+            w.pc_init()
+                .bits(0)
+                .ulp_cp_force_start_top()
+                .clear_bit()
+        });
+        // allow the shortest time until the next wakeup
+        rtc_cntl
+            .timer5
+            .modify(|_, w| unsafe { w.min_slp_val().bits(ULP_MIN_SLEEP_VAL) });
+        // raise the voltage when RC_FAST is enabled by the ULP
+        rtc_cntl.options0.modify(|_, w| {
+            w.bias_i2c_folw_8m()
+                .set_bit()
+                .bias_core_folw_8m()
+                .set_bit()
+                .bias_sleep_folw_8m()
+                .set_bit()
+        });
+        rtc_cntl
+            .state0
+            .modify(|_, w| w.ulp_cp_slp_timer_en().set_bit());
+
+        Ok(())
+    }
+}
+
+/// Pad hold bits of the RTC pads
+#[cfg(any(esp32c2, esp32c3))]
+const RTC_PAD_HOLD_MASK: u32 = (1 << 6) - 1;
+#[cfg(any(esp32s2, esp32s3))]
+const RTC_PAD_HOLD_MASK: u32 = (1 << 22) - 1;
+
+/// Start of the RTC slow memory, where the ULP program is loaded
+#[cfg(esp32)]
+const ULP_MEMORY_START: usize = 0x5000_0000;
+
+/// The smallest supported `RTC_CNTL_MIN_SLP_VAL`
+#[cfg(esp32)]
+const ULP_MIN_SLEEP_VAL: u8 = 1;
+
+/// Errors of [`Rtc::start_ulp`]
+#[cfg(esp32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UlpError {
+    /// The program is empty or its length isn't a multiple of 4 bytes
+    InvalidProgram,
+    /// The program doesn't fit into the RTC slow memory reserved for the ULP
+    ProgramTooLarge,
+}
+
+/// An instant of the RTC timer, in microseconds
+pub type RtcInstant = fugit::Instant<u64, 1, 1_000_000>;
+
+/// Read the 48 bit RTC timer
+///
+/// The counter is latched into the high and low registers by a single update
+/// request, the update and both reads happen in a critical section so another
+/// update can't be requested in between which would tear the value.
+fn read_time_raw() -> u64 {
+    #[cfg(not(any(esp32c6, esp32h2)))]
+    let rtc_cntl = unsafe { &*RTC_CNTL::ptr() };
+    #[cfg(any(esp32c6, esp32h2))]
+    let rtc_cntl = unsafe { &*LP_TIMER::ptr() };
+
+    critical_section::with(|_| {
         #[cfg(esp32)]
         let (l, h) = {
             rtc_cntl.time_update.write(|w| w.time_update().set_bit());
@@ -176,18 +383,13 @@ impl<'d> Rtc<'d> {
             let l = rtc_cntl.main_buf0_low.read().main_timer_buf0_low().bits();
             (l, h)
         };
-        ((h as u64) << 32) | (l as u64)
-    }
-
-    /// read the current value of the rtc time registers in microseconds.
-    pub fn get_time_us(&self) -> u64 {
-        self.get_time_raw() * 1_000_000 / RtcClock::get_slow_freq().frequency().to_Hz() as u64
-    }
+        (((h as u64) << 32) | (l as u64)) & 0xffff_ffff_ffff
+    })
+}
 
-    /// read the current value of the rtc time registers in milliseconds
-    pub fn get_time_ms(&self) -> u64 {
-        self.get_time_raw() * 1_000 / RtcClock::get_slow_freq().frequency().to_Hz() as u64
-    }
+/// The RTC timer converted to microseconds using the current RTC SLOW_CLK
+pub(crate) fn time_us() -> u64 {
+    read_time_raw() * 1_000_000 / RtcClock::get_slow_freq().frequency().to_Hz() as u64
 }
 
 #[cfg(not(any(esp32c6, esp32h2)))]
@@ -517,7 +719,7 @@ impl RtcClock {
 
     // TODO: implement for ESP32-C6
     #[cfg(not(any(esp32c6, esp32h2)))]
-    fn estimate_xtal_frequency() -> u32 {
+    pub(crate) fn estimate_xtal_frequency() -> u32 {
         // Number of 8M/256 clock cycles to use for XTAL frequency estimation.
         const XTAL_FREQ_EST_CYCLES: u32 = 10;
 
@@ -557,6 +759,8 @@ pub struct Rwdt {
     stg1_action: RwdtStageAction,
     stg2_action: RwdtStageAction,
     stg3_action: RwdtStageAction,
+    timeout: Option<MicrosDurationU64>,
+    last_feed: u64,
 }
 
 impl Default for Rwdt {
@@ -566,6 +770,8 @@ impl Default for Rwdt {
             stg1_action: RwdtStageAction::RwdtStageActionOff,
             stg2_action: RwdtStageAction::RwdtStageActionOff,
             stg3_action: RwdtStageAction::RwdtStageActionOff,
+            timeout: None,
+            last_feed: 0,
         }
     }
 }
@@ -653,6 +859,19 @@ impl Rwdt {
         }
     }
 
+    /// The time left until the watchdog expires, `None` if it wasn't started
+    /// via [`WatchdogEnable::start`] or is disabled
+    ///
+    /// The counter of the watchdog can't be read back, so this is derived
+    /// from the time of the last feed (or start) as measured by the RTC
+    /// timer. Useful to log how close a feed came to the timeout.
+    pub fn time_to_expiry(&self) -> Option<MicrosDurationU64> {
+        self.timeout.map(|timeout| {
+            let elapsed = time_us().saturating_sub(self.last_feed);
+            MicrosDurationU64::micros(timeout.to_micros().saturating_sub(elapsed))
+        })
+    }
+
     /// Enable/disable write protection for WDT registers
     fn set_write_protection(&mut self, enable: bool) {
         #[cfg(not(any(esp32c6, esp32h2)))]
@@ -680,6 +899,7 @@ impl WatchdogDisable for Rwdt {
             .modify(|_, w| w.wdt_en().clear_bit().wdt_flashboot_mod_en().clear_bit());
 
         self.set_write_protection(true);
+        self.timeout = None;
     }
 }
 
@@ -696,7 +916,8 @@ impl WatchdogEnable for Rwdt {
         #[cfg(any(esp32c6, esp32h2))]
         let rtc_cntl = unsafe { &*LP_WDT::PTR };
 
-        let timeout_raw = (period.into().to_millis() * (RtcClock::cycles_to_1ms() as u64)) as u32;
+        let period = period.into();
+        let timeout_raw = (period.to_millis() * (RtcClock::cycles_to_1ms() as u64)) as u32;
         self.set_write_protection(false);
 
         unsafe {
@@ -736,6 +957,8 @@ impl WatchdogEnable for Rwdt {
         }
 
         self.set_write_protection(true);
+        self.timeout = Some(period);
+        self.last_feed = time_us();
     }
 }
 
@@ -749,6 +972,7 @@ impl Watchdog for Rwdt {
         self.set_write_protection(false);
         rtc_cntl.wdtfeed.write(|w| unsafe { w.bits(1) });
         self.set_write_protection(true);
+        self.last_feed = time_us();
     }
 }
 
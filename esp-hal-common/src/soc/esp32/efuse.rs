@@ -87,6 +87,26 @@ impl Efuse {
         }
     }
 
+    /// Returns the major hardware revision of the chip.
+    pub fn get_major_chip_version() -> u8 {
+        let apb_ctrl = unsafe { &*crate::peripherals::APB_CTRL::PTR };
+        let eco_bit0 = Self::read_field_le::<u8>(CHIP_VER_REV1);
+        let eco_bit1 = Self::read_field_le::<u8>(CHIP_VER_REV2);
+        let eco_bit2 = (apb_ctrl.date.read().bits() >> 31) as u8;
+
+        match eco_bit2 << 2 | eco_bit1 << 1 | eco_bit0 {
+            1 => 1,
+            3 => 2,
+            7 => 3,
+            _ => 0,
+        }
+    }
+
+    /// Returns the minor hardware revision of the chip.
+    pub fn get_minor_chip_version() -> u8 {
+        Self::read_field_le(WAFER_VERSION_MINOR)
+    }
+
     /// Get status of SPI boot encryption.
     pub fn get_flash_encryption() -> bool {
         (Self::read_field_le::<u8>(FLASH_CRYPT_CNT).count_ones() % 2) != 0
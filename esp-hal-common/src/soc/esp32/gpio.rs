@@ -15,6 +15,9 @@ use crate::{
 
 pub const NUM_PINS: usize = 39;
 
+/// GPIOs sampled at reset to select the boot mode and other boot options
+pub const STRAPPING_PINS: &[u8] = &[0, 2, 5, 12, 15];
+
 pub type OutputSignalType = u16;
 pub const OUTPUT_SIGNAL_MAX: u16 = 548;
 pub const INPUT_SIGNAL_MAX: u16 = 539;
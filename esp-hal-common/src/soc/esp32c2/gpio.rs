@@ -13,6 +13,9 @@ use crate::{
 
 pub const NUM_PINS: usize = 20;
 
+/// GPIOs sampled at reset to select the boot mode and other boot options
+pub const STRAPPING_PINS: &[u8] = &[8, 9];
+
 pub type OutputSignalType = u8;
 pub const OUTPUT_SIGNAL_MAX: u8 = 128;
 pub const INPUT_SIGNAL_MAX: u8 = 100;
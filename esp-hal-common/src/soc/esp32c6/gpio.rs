@@ -13,6 +13,9 @@ use crate::{
 
 pub const NUM_PINS: usize = 30;
 
+/// GPIOs sampled at reset to select the boot mode and other boot options
+pub const STRAPPING_PINS: &[u8] = &[4, 5, 8, 9, 15];
+
 pub type OutputSignalType = u8;
 pub const OUTPUT_SIGNAL_MAX: u8 = 128;
 pub const INPUT_SIGNAL_MAX: u8 = 124;
@@ -14,6 +14,9 @@ use crate::{
 // https://github.com/espressif/esp-idf/blob/df9310a/components/soc/esp32h2/gpio_periph.c#L42
 pub const NUM_PINS: usize = 27;
 
+/// GPIOs sampled at reset to select the boot mode and other boot options
+pub const STRAPPING_PINS: &[u8] = &[8, 9, 25];
+
 pub type OutputSignalType = u8;
 pub const OUTPUT_SIGNAL_MAX: u8 = 128;
 pub const INPUT_SIGNAL_MAX: u8 = 124;
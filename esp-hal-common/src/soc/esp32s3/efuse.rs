@@ -27,6 +27,17 @@ impl Efuse {
         Self::read_field_be(MAC_FACTORY)
     }
 
+    /// Returns the major hardware revision of the chip.
+    pub fn get_major_chip_version() -> u8 {
+        Self::read_field_le(WAFER_VERSION_MAJOR)
+    }
+
+    /// Returns the minor hardware revision of the chip.
+    pub fn get_minor_chip_version() -> u8 {
+        Self::read_field_le::<u8>(WAFER_VERSION_MINOR_HI) << 3
+            | Self::read_field_le::<u8>(WAFER_VERSION_MINOR_LO)
+    }
+
     /// Get status of SPI boot encryption.
     pub fn get_flash_encryption() -> bool {
         (Self::read_field_le::<u8>(SPI_BOOT_CRYPT_CNT).count_ones() % 2) != 0
@@ -14,6 +14,9 @@ use crate::{
 
 pub const NUM_PINS: usize = 48;
 
+/// GPIOs sampled at reset to select the boot mode and other boot options
+pub const STRAPPING_PINS: &[u8] = &[0, 3, 45, 46];
+
 pub type OutputSignalType = u16;
 pub const OUTPUT_SIGNAL_MAX: u16 = 256;
 pub const INPUT_SIGNAL_MAX: u16 = 189;
@@ -20,6 +20,7 @@ type IntPri = crate::peripherals::INTPRI;
 #[cfg(not(any(esp32, esp32c6, esp32h2)))]
 type SystemPeripheral = crate::peripherals::SYSTEM;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SoftwareInterrupt {
     SoftwareInterrupt0,
     SoftwareInterrupt1,
@@ -91,12 +92,35 @@ pub struct SoftwareInterruptControl {
 
 impl SoftwareInterruptControl {
     pub fn raise(&mut self, interrupt: SoftwareInterrupt) {
+        interrupt.raise();
+    }
+
+    pub fn reset(&mut self, interrupt: SoftwareInterrupt) {
+        interrupt.reset();
+    }
+}
+
+impl SoftwareInterrupt {
+    /// The peripheral interrupt raised by this software interrupt
+    #[allow(unused)]
+    pub(crate) fn interrupt(self) -> crate::peripherals::Interrupt {
+        use crate::peripherals::Interrupt;
+
+        match self {
+            SoftwareInterrupt::SoftwareInterrupt0 => Interrupt::FROM_CPU_INTR0,
+            SoftwareInterrupt::SoftwareInterrupt1 => Interrupt::FROM_CPU_INTR1,
+            SoftwareInterrupt::SoftwareInterrupt2 => Interrupt::FROM_CPU_INTR2,
+            SoftwareInterrupt::SoftwareInterrupt3 => Interrupt::FROM_CPU_INTR3,
+        }
+    }
+
+    pub(crate) fn raise(self) {
         #[cfg(not(any(esp32c6, esp32h2)))]
         let system = unsafe { &*SystemPeripheral::PTR };
         #[cfg(any(esp32c6, esp32h2))]
         let system = unsafe { &*IntPri::PTR };
 
-        match interrupt {
+        match self {
             SoftwareInterrupt::SoftwareInterrupt0 => {
                 system
                     .cpu_intr_from_cpu_0
@@ -120,13 +144,13 @@ impl SoftwareInterruptControl {
         }
     }
 
-    pub fn reset(&mut self, interrupt: SoftwareInterrupt) {
+    pub(crate) fn reset(self) {
         #[cfg(not(any(esp32c6, esp32h2)))]
         let system = unsafe { &*SystemPeripheral::PTR };
         #[cfg(any(esp32c6, esp32h2))]
         let system = unsafe { &*IntPri::PTR };
 
-        match interrupt {
+        match self {
             SoftwareInterrupt::SoftwareInterrupt0 => {
                 system
                     .cpu_intr_from_cpu_0
@@ -352,6 +376,201 @@ impl PeripheralClockControl {
             }
         }
     }
+
+    /// Disables the clock of the given peripheral and holds it in reset
+    pub(crate) fn disable(&mut self, peripheral: Peripheral) {
+        let system = unsafe { &*SystemPeripheral::PTR };
+
+        #[cfg(not(esp32))]
+        let (perip_clk_en0, perip_rst_en0) = { (&system.perip_clk_en0, &system.perip_rst_en0) };
+        #[cfg(esp32)]
+        let (perip_clk_en0, perip_rst_en0, peri_clk_en, peri_rst_en) = {
+            (
+                &system.perip_clk_en,
+                &system.perip_rst_en,
+                &system.peri_clk_en,
+                &system.peri_rst_en,
+            )
+        };
+
+        #[cfg(any(esp32c2, esp32c3, esp32s2, esp32s3))]
+        let (perip_clk_en1, perip_rst_en1) = { (&system.perip_clk_en1, &system.perip_rst_en1) };
+
+        match peripheral {
+            #[cfg(spi2)]
+            Peripheral::Spi2 => {
+                perip_rst_en0.modify(|_, w| w.spi2_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.spi2_clk_en().clear_bit());
+            }
+            #[cfg(spi3)]
+            Peripheral::Spi3 => {
+                perip_rst_en0.modify(|_, w| w.spi3_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.spi3_clk_en().clear_bit());
+            }
+            #[cfg(esp32)]
+            Peripheral::I2cExt0 => {
+                perip_rst_en0.modify(|_, w| w.i2c0_ext0_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.i2c0_ext0_clk_en().clear_bit());
+            }
+            #[cfg(not(esp32))]
+            Peripheral::I2cExt0 => {
+                perip_rst_en0.modify(|_, w| w.i2c_ext0_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.i2c_ext0_clk_en().clear_bit());
+            }
+            #[cfg(i2c1)]
+            Peripheral::I2cExt1 => {
+                perip_rst_en0.modify(|_, w| w.i2c_ext1_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.i2c_ext1_clk_en().clear_bit());
+            }
+            #[cfg(rmt)]
+            Peripheral::Rmt => {
+                perip_rst_en0.modify(|_, w| w.rmt_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.rmt_clk_en().clear_bit());
+            }
+            Peripheral::Ledc => {
+                perip_rst_en0.modify(|_, w| w.ledc_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.ledc_clk_en().clear_bit());
+            }
+            #[cfg(mcpwm0)]
+            Peripheral::Mcpwm0 => {
+                perip_rst_en0.modify(|_, w| w.pwm0_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.pwm0_clk_en().clear_bit());
+            }
+            #[cfg(mcpwm1)]
+            Peripheral::Mcpwm1 => {
+                perip_rst_en0.modify(|_, w| w.pwm1_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.pwm1_clk_en().clear_bit());
+            }
+            #[cfg(pcnt)]
+            Peripheral::Pcnt => {
+                perip_rst_en0.modify(|_, w| w.pcnt_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.pcnt_clk_en().clear_bit());
+            }
+            #[cfg(apb_saradc)]
+            Peripheral::ApbSarAdc => {
+                perip_rst_en0.modify(|_, w| w.apb_saradc_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.apb_saradc_clk_en().clear_bit());
+            }
+            #[cfg(gdma)]
+            Peripheral::Gdma => {
+                perip_rst_en1.modify(|_, w| w.dma_rst().set_bit());
+                perip_clk_en1.modify(|_, w| w.dma_clk_en().clear_bit());
+            }
+            #[cfg(esp32)]
+            Peripheral::Dma => {
+                perip_rst_en0.modify(|_, w| w.spi_dma_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.spi_dma_clk_en().clear_bit());
+            }
+            #[cfg(esp32s2)]
+            Peripheral::Dma => {
+                perip_rst_en0.modify(|_, w| w.spi2_dma_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.spi2_dma_clk_en().clear_bit());
+                perip_rst_en0.modify(|_, w| w.spi3_dma_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.spi3_dma_clk_en().clear_bit());
+            }
+            #[cfg(esp32c3)]
+            Peripheral::I2s0 => {
+                // on ESP32-C3 note that i2s1_clk_en / rst is really I2s0
+                perip_rst_en0.modify(|_, w| w.i2s1_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.i2s1_clk_en().clear_bit());
+            }
+            #[cfg(any(esp32s3, esp32, esp32s2))]
+            Peripheral::I2s0 => {
+                perip_rst_en0.modify(|_, w| w.i2s0_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.i2s0_clk_en().clear_bit());
+            }
+            #[cfg(any(esp32s3, esp32))]
+            Peripheral::I2s1 => {
+                perip_rst_en0.modify(|_, w| w.i2s1_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.i2s1_clk_en().clear_bit());
+            }
+            #[cfg(usb0)]
+            Peripheral::Usb => {
+                perip_rst_en0.modify(|_, w| w.usb_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.usb_clk_en().clear_bit());
+            }
+            #[cfg(twai0)]
+            Peripheral::Twai0 => {
+                perip_rst_en0.modify(|_, w| w.twai_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.twai_clk_en().clear_bit());
+            }
+            #[cfg(esp32)]
+            Peripheral::Aes => {
+                peri_rst_en.modify(|r, w| unsafe { w.bits(r.bits() | 1) });
+                peri_clk_en.modify(|r, w| unsafe { w.bits(r.bits() & !1) });
+            }
+            #[cfg(any(esp32c3, esp32s2, esp32s3))]
+            Peripheral::Aes => {
+                perip_rst_en1.modify(|_, w| w.crypto_aes_rst().set_bit());
+                perip_clk_en1.modify(|_, w| w.crypto_aes_clk_en().clear_bit());
+            }
+            #[cfg(timg0)]
+            Peripheral::Timg0 => {
+                #[cfg(any(esp32c3, esp32s2, esp32s3))]
+                perip_clk_en0.modify(|_, w| w.timers_clk_en().clear_bit());
+                perip_clk_en0.modify(|_, w| w.timergroup_clk_en().clear_bit());
+
+                #[cfg(any(esp32c3, esp32s2, esp32s3))]
+                perip_rst_en0.modify(|_, w| w.timers_rst().set_bit());
+                perip_rst_en0.modify(|_, w| w.timergroup_rst().set_bit());
+            }
+            #[cfg(timg1)]
+            Peripheral::Timg1 => {
+                #[cfg(any(esp32c3, esp32s2, esp32s3))]
+                perip_clk_en0.modify(|_, w| w.timers_clk_en().clear_bit());
+                perip_clk_en0.modify(|_, w| w.timergroup1_clk_en().clear_bit());
+
+                #[cfg(any(esp32c3, esp32s2, esp32s3))]
+                perip_rst_en0.modify(|_, w| w.timers_rst().set_bit());
+                perip_rst_en0.modify(|_, w| w.timergroup1_rst().set_bit());
+            }
+            Peripheral::Sha => {
+                #[cfg(not(esp32))]
+                perip_rst_en1.modify(|_, w| w.crypto_sha_rst().set_bit());
+                #[cfg(not(esp32))]
+                perip_clk_en1.modify(|_, w| w.crypto_sha_clk_en().clear_bit());
+            }
+            #[cfg(esp32c3)]
+            Peripheral::UsbDevice => {
+                perip_rst_en0.modify(|_, w| w.usb_device_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.usb_device_clk_en().clear_bit());
+            }
+            #[cfg(esp32s3)]
+            Peripheral::UsbDevice => {
+                perip_rst_en1.modify(|_, w| w.usb_device_rst().set_bit());
+                perip_clk_en1.modify(|_, w| w.usb_device_clk_en().clear_bit());
+            }
+            Peripheral::Uart0 => {
+                perip_rst_en0.modify(|_, w| w.uart_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.uart_clk_en().clear_bit());
+            }
+            Peripheral::Uart1 => {
+                perip_rst_en0.modify(|_, w| w.uart1_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.uart1_clk_en().clear_bit());
+            }
+            #[cfg(all(uart2, esp32s3))]
+            Peripheral::Uart2 => {
+                perip_rst_en1.modify(|_, w| w.uart2_rst().set_bit());
+                perip_clk_en1.modify(|_, w| w.uart2_clk_en().clear_bit());
+            }
+            #[cfg(all(uart2, esp32))]
+            Peripheral::Uart2 => {
+                perip_rst_en0.modify(|_, w| w.uart2_rst().set_bit());
+                perip_clk_en0.modify(|_, w| w.uart2_clk_en().clear_bit());
+            }
+            #[cfg(esp32)]
+            Peripheral::Rsa => {
+                peri_rst_en.modify(|r, w| unsafe { w.bits(r.bits() | 1 << 2) });
+                peri_clk_en.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << 2)) });
+            }
+            #[cfg(any(esp32c3, esp32s2, esp32s3))]
+            Peripheral::Rsa => {
+                perip_rst_en1.modify(|_, w| w.crypto_rsa_rst().set_bit());
+                perip_clk_en1.modify(|_, w| w.crypto_rsa_clk_en().clear_bit());
+                system.rsa_pd_ctrl.modify(|_, w| w.rsa_mem_pd().set_bit());
+            }
+        }
+    }
 }
 
 #[cfg(any(esp32c6, esp32h2))]
@@ -508,6 +727,159 @@ impl PeripheralClockControl {
             }
         }
     }
+
+    /// Disables the clock of the given peripheral and holds it in reset
+    pub(crate) fn disable(&mut self, peripheral: Peripheral) {
+        let system = unsafe { &*SystemPeripheral::PTR };
+
+        match peripheral {
+            #[cfg(spi2)]
+            Peripheral::Spi2 => {
+                system.spi2_conf.modify(|_, w| w.spi2_rst_en().set_bit());
+                system.spi2_conf.modify(|_, w| w.spi2_clk_en().clear_bit());
+            }
+            #[cfg(i2c0)]
+            Peripheral::I2cExt0 => {
+                // TODO: align register names between C6 and H2 in the PACs
+                #[cfg(esp32c6)]
+                {
+                    system.i2c_conf.modify(|_, w| w.i2c_rst_en().set_bit());
+                    system.i2c_conf.modify(|_, w| w.i2c_clk_en().clear_bit());
+                }
+                #[cfg(esp32h2)]
+                {
+                    system.i2c0_conf.modify(|_, w| w.i2c0_rst_en().set_bit());
+                    system.i2c0_conf.modify(|_, w| w.i2c0_clk_en().clear_bit());
+                }
+            }
+            #[cfg(i2c1)]
+            Peripheral::I2cExt1 => {
+                #[cfg(esp32h2)]
+                {
+                    system.i2c1_conf.modify(|_, w| w.i2c1_rst_en().set_bit());
+                    system.i2c1_conf.modify(|_, w| w.i2c1_clk_en().clear_bit());
+                }
+            }
+            #[cfg(rmt)]
+            Peripheral::Rmt => {
+                system.rmt_conf.modify(|_, w| w.rmt_rst_en().set_bit());
+                system.rmt_conf.modify(|_, w| w.rmt_clk_en().clear_bit());
+            }
+            #[cfg(ledc)]
+            Peripheral::Ledc => {
+                system.ledc_conf.modify(|_, w| w.ledc_rst_en().set_bit());
+                system.ledc_conf.modify(|_, w| w.ledc_clk_en().clear_bit());
+            }
+            #[cfg(mcpwm0)]
+            Peripheral::Mcpwm0 => {
+                system.pwm_conf.modify(|_, w| w.pwm_rst_en().set_bit());
+                system.pwm_conf.modify(|_, w| w.pwm_clk_en().clear_bit());
+            }
+            #[cfg(mcpwm1)]
+            Peripheral::Mcpwm1 => {
+                system.pwm_conf.modify(|_, w| w.pwm_rst_en().set_bit());
+                system.pwm_conf.modify(|_, w| w.pwm_clk_en().clear_bit());
+            }
+            #[cfg(apb_saradc)]
+            Peripheral::ApbSarAdc => {
+                system
+                    .saradc_conf
+                    .modify(|_, w| w.saradc_reg_rst_en().set_bit());
+                system
+                    .saradc_conf
+                    .modify(|_, w| w.saradc_reg_clk_en().clear_bit());
+            }
+            #[cfg(gdma)]
+            Peripheral::Gdma => {
+                system.gdma_conf.modify(|_, w| w.gdma_rst_en().set_bit());
+                system.gdma_conf.modify(|_, w| w.gdma_clk_en().clear_bit());
+            }
+            #[cfg(i2s0)]
+            Peripheral::I2s0 => {
+                system.i2s_conf.modify(|_, w| w.i2s_rst_en().set_bit());
+                system.i2s_conf.modify(|_, w| w.i2s_clk_en().clear_bit());
+            }
+            #[cfg(twai0)]
+            Peripheral::Twai0 => {
+                system.twai0_conf.modify(|_, w| w.twai0_rst_en().set_bit());
+                system
+                    .twai0_conf
+                    .modify(|_, w| w.twai0_clk_en().clear_bit());
+            }
+            #[cfg(twai1)]
+            Peripheral::Twai1 => {
+                system.twai1_conf.modify(|_, w| w.twai1_rst_en().set_bit());
+                system
+                    .twai1_conf
+                    .modify(|_, w| w.twai1_clk_en().clear_bit());
+            }
+            #[cfg(aes)]
+            Peripheral::Aes => {
+                system.aes_conf.modify(|_, w| w.aes_rst_en().set_bit());
+                system.aes_conf.modify(|_, w| w.aes_clk_en().clear_bit());
+            }
+            #[cfg(pcnt)]
+            Peripheral::Pcnt => {
+                system.pcnt_conf.modify(|_, w| w.pcnt_rst_en().set_bit());
+                system.pcnt_conf.modify(|_, w| w.pcnt_clk_en().clear_bit());
+            }
+            #[cfg(timg0)]
+            Peripheral::Timg0 => {
+                system
+                    .timergroup0_timer_clk_conf
+                    .modify(|_, w| w.tg0_timer_clk_en().clear_bit());
+            }
+            #[cfg(timg1)]
+            Peripheral::Timg1 => {
+                system
+                    .timergroup1_timer_clk_conf
+                    .modify(|_, w| w.tg1_timer_clk_en().clear_bit());
+            }
+            #[cfg(lp_wdt)]
+            Peripheral::Wdt => {
+                system
+                    .timergroup0_wdt_clk_conf
+                    .modify(|_, w| w.tg0_wdt_clk_en().clear_bit());
+                system
+                    .timergroup1_timer_clk_conf
+                    .modify(|_, w| w.tg1_timer_clk_en().clear_bit());
+            }
+            #[cfg(sha)]
+            Peripheral::Sha => {
+                system.sha_conf.modify(|_, w| w.sha_rst_en().set_bit());
+                system.sha_conf.modify(|_, w| w.sha_clk_en().clear_bit());
+            }
+            #[cfg(usb_device)]
+            Peripheral::UsbDevice => {
+                system
+                    .usb_device_conf
+                    .modify(|_, w| w.usb_device_rst_en().set_bit());
+                system
+                    .usb_device_conf
+                    .modify(|_, w| w.usb_device_clk_en().clear_bit());
+            }
+            #[cfg(uart0)]
+            Peripheral::Uart0 => {
+                system.uart0_conf.modify(|_, w| w.uart0_rst_en().set_bit());
+                system
+                    .uart0_conf
+                    .modify(|_, w| w.uart0_clk_en().clear_bit());
+            }
+            #[cfg(uart1)]
+            Peripheral::Uart1 => {
+                system.uart1_conf.modify(|_, w| w.uart1_rst_en().set_bit());
+                system
+                    .uart1_conf
+                    .modify(|_, w| w.uart1_clk_en().clear_bit());
+            }
+            #[cfg(rsa)]
+            Peripheral::Rsa => {
+                system.rsa_conf.modify(|_, w| w.rsa_rst_en().set_bit());
+                system.rsa_conf.modify(|_, w| w.rsa_clk_en().clear_bit());
+                system.rsa_pd_ctrl.modify(|_, w| w.rsa_mem_pd().set_bit());
+            }
+        }
+    }
 }
 
 /// Controls the configuration of the chip's clocks.
@@ -610,6 +982,209 @@ impl crate::peripheral::Peripheral for SystemClockControl {
 
 impl crate::peripheral::sealed::Sealed for SystemClockControl {}
 
+/// The internal RAM left over for a heap, as `(start address, length)`.
+///
+/// This is the RAM after all statics (`.data`, `.bss`, ...) up to the area
+/// reserved for the stack(s): `STACK_SIZE` per core on Xtensa, and
+/// `_hart_stack_size` on RISC-V (2K unless overridden in the linker script).
+/// All of it is DMA capable. Only available with the linker scripts of this
+/// HAL for direct boot or the ESP-IDF bootloader.
+pub fn heap_region() -> (usize, usize) {
+    extern "C" {
+        static _heap_start: u8;
+        #[cfg(xtensa)]
+        static _heap_end: u8;
+        #[cfg(riscv)]
+        static _stack_start: u8;
+        #[cfg(riscv)]
+        static _hart_stack_size: u8;
+    }
+
+    let start = unsafe { &_heap_start as *const u8 as usize };
+    #[cfg(xtensa)]
+    let end = unsafe { &_heap_end as *const u8 as usize };
+    #[cfg(riscv)]
+    let end = unsafe {
+        &_stack_start as *const u8 as usize - &_hart_stack_size as *const u8 as usize
+    };
+
+    (start, end.saturating_sub(start))
+}
+
+/// Chip model
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ChipModel {
+    Esp32,
+    Esp32c2,
+    Esp32c3,
+    Esp32c6,
+    Esp32h2,
+    Esp32s2,
+    Esp32s3,
+}
+
+/// The model of the chip the code is running on
+pub fn chip_model() -> ChipModel {
+    cfg_if::cfg_if! {
+        if #[cfg(esp32)] {
+            ChipModel::Esp32
+        } else if #[cfg(esp32c2)] {
+            ChipModel::Esp32c2
+        } else if #[cfg(esp32c3)] {
+            ChipModel::Esp32c3
+        } else if #[cfg(esp32c6)] {
+            ChipModel::Esp32c6
+        } else if #[cfg(esp32h2)] {
+            ChipModel::Esp32h2
+        } else if #[cfg(esp32s2)] {
+            ChipModel::Esp32s2
+        } else if #[cfg(esp32s3)] {
+            ChipModel::Esp32s3
+        }
+    }
+}
+
+/// The silicon revision of the chip, as read from the eFuses.
+///
+/// Encoded like in ESP-IDF as `major * 100 + minor`, e.g. 301 for v3.1.
+#[cfg(efuse)]
+pub fn chip_revision() -> u16 {
+    use crate::efuse::Efuse;
+
+    Efuse::get_major_chip_version() as u16 * 100 + Efuse::get_minor_chip_version() as u16
+}
+
+#[cfg(multi_core)]
+const CORE_COUNT: usize = 2;
+#[cfg(single_core)]
+const CORE_COUNT: usize = 1;
+
+/// The last value returned by [`cycle_count`] on each core
+static LAST_CYCLE_COUNT: critical_section::Mutex<core::cell::Cell<[u64; CORE_COUNT]>> =
+    critical_section::Mutex::new(core::cell::Cell::new([0; CORE_COUNT]));
+
+/// The number of CPU cycles of the current core
+///
+/// Reads CCOUNT on Xtensa and the machine performance counter (MPCCR, set
+/// up to count cycles) on RISC-V. Both are only 32 bits wide, the upper
+/// bits are composed in software: this stays correct as long as it's called
+/// at least once per wrap of the counter (about 17s at 240MHz). The
+/// counters of the two cores on ESP32 and ESP32-S3 aren't synchronized, so
+/// only compare values read on the same core.
+pub fn cycle_count() -> u64 {
+    critical_section::with(|cs| {
+        let low = read_cycle_counter();
+        let core = crate::get_core() as usize;
+
+        let cell = LAST_CYCLE_COUNT.borrow(cs);
+        let mut last = cell.get();
+        let mut high = last[core] & !0xffff_ffff;
+        if low < last[core] as u32 {
+            high += 1 << 32;
+        }
+        last[core] = high | low as u64;
+        cell.set(last);
+
+        last[core]
+    })
+}
+
+/// Convert a number of CPU cycles to nanoseconds, according to
+/// [`Clocks::cpu_clock`](crate::clock::Clocks::cpu_clock)
+pub fn cycles_to_nanos(cycles: u64, clocks: &crate::clock::Clocks) -> u64 {
+    (cycles as u128 * 1_000_000_000 / clocks.cpu_clock.raw() as u128) as u64
+}
+
+#[cfg(xtensa)]
+fn read_cycle_counter() -> u32 {
+    xtensa_lx::timer::get_cycle_count()
+}
+
+#[cfg(riscv)]
+fn read_cycle_counter() -> u32 {
+    let count: u32;
+    unsafe {
+        // count cycles (MPCER) and enable the counter (MPCMR)
+        core::arch::asm!("csrwi 0x7e0, 1", "csrwi 0x7e1, 1");
+        core::arch::asm!("csrr {0}, 0x7e2", out(reg) count);
+    }
+    count
+}
+
+/// Power domains which can be switched off at runtime, see [`power_domain`]
+///
+/// Everything else, including the ADC and the other analog peripherals, is
+/// powered together with the digital core and can only be clock gated via
+/// [`PeripheralClockControl`].
+#[cfg(any(esp32c3, esp32s3))]
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Domain {
+    /// The Wi-Fi MAC and baseband, also used by ESP-NOW
+    Wifi,
+    /// The Bluetooth LE MAC and baseband
+    Bt,
+}
+
+/// A power domain, see [`power_domain`]
+#[cfg(any(esp32c3, esp32s3))]
+pub struct PowerDomain {
+    domain: Domain,
+}
+
+/// Control the power of the given domain
+///
+/// Powering down a domain loses the state of all peripherals in it, so the
+/// drivers using it (e.g. the Wi-Fi and BLE drivers) must not be running and
+/// have to be initialized again after powering it up. The RF frontend and the
+/// PHY are not affected.
+#[cfg(any(esp32c3, esp32s3))]
+pub fn power_domain(domain: Domain) -> PowerDomain {
+    PowerDomain { domain }
+}
+
+#[cfg(any(esp32c3, esp32s3))]
+impl PowerDomain {
+    /// Isolate the domain and then power it down
+    pub fn off(&self) {
+        let rtc_cntl = unsafe { &*crate::peripherals::RTC_CNTL::PTR };
+
+        match self.domain {
+            Domain::Wifi => {
+                rtc_cntl
+                    .dig_iso
+                    .modify(|_, w| w.wifi_force_iso().set_bit());
+                rtc_cntl
+                    .dig_pwc
+                    .modify(|_, w| w.wifi_force_pd().set_bit());
+            }
+            Domain::Bt => {
+                rtc_cntl.dig_iso.modify(|_, w| w.bt_force_iso().set_bit());
+                rtc_cntl.dig_pwc.modify(|_, w| w.bt_force_pd().set_bit());
+            }
+        }
+    }
+
+    /// Power the domain up and then remove the isolation
+    pub fn on(&self) {
+        let rtc_cntl = unsafe { &*crate::peripherals::RTC_CNTL::PTR };
+
+        match self.domain {
+            Domain::Wifi => {
+                rtc_cntl
+                    .dig_pwc
+                    .modify(|_, w| w.wifi_force_pd().clear_bit());
+                rtc_cntl
+                    .dig_iso
+                    .modify(|_, w| w.wifi_force_iso().clear_bit());
+            }
+            Domain::Bt => {
+                rtc_cntl.dig_pwc.modify(|_, w| w.bt_force_pd().clear_bit());
+                rtc_cntl.dig_iso.modify(|_, w| w.bt_force_iso().clear_bit());
+            }
+        }
+    }
+}
+
 #[cfg(pdma)]
 mod dma_peripheral {
     use super::Dma;
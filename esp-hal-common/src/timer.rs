@@ -1,4 +1,10 @@
 //! General-purpose timers
+//!
+//! The timer group timers have no input to latch their counter on an external
+//! event, their value can only be latched by software (see
+//! [`Instance::now`]). Timestamping external events needs a peripheral with a
+//! capture unit instead, such as the MCPWM capture channels (not yet supported
+//! by this HAL).
 
 use core::{
     marker::PhantomData,
@@ -29,6 +35,9 @@ pub enum Error {
     TimerActive,
     TimerInactive,
     AlarmInactive,
+    /// The tick rate can't be reached with the prescaler, see
+    /// [`Timer::set_tick_hz`]
+    UnsupportedTickRate,
 }
 
 // A timergroup consisting of up to 2 timers (chip dependent) and a watchdog
@@ -212,12 +221,44 @@ where
         Self { timg, apb_clk_freq }
     }
 
+    /// Set the prescaler for a tick rate as close as possible to `hz`,
+    /// returning the achieved rate
+    ///
+    /// The source clock can be divided by 2 to 65536. Fails with
+    /// [`Error::UnsupportedTickRate`] if `hz` is outside of this range, e.g.
+    /// 1MHz for a microsecond timer is fine with an 80MHz APB clock.
+    pub fn set_tick_hz(&mut self, hz: HertzU32) -> Result<HertzU32, Error> {
+        let source = self.apb_clk_freq.raw();
+        if hz.raw() == 0 {
+            return Err(Error::UnsupportedTickRate);
+        }
+
+        let divider = (source + hz.raw() / 2) / hz.raw();
+        if !(2..=65536).contains(&divider) {
+            return Err(Error::UnsupportedTickRate);
+        }
+
+        // a divider of 65536 is written as 0
+        self.timg.set_divider(divider as u16);
+
+        Ok(HertzU32::Hz(source / divider))
+    }
+
     /// Return the raw interface to the underlying timer instance
     pub fn free(self) -> T {
         self.timg
     }
 }
 
+impl<T> crate::interrupt::InterruptClearable for Timer<T>
+where
+    T: Instance,
+{
+    fn clear_interrupts(&mut self) {
+        self.timg.clear_interrupt();
+    }
+}
+
 impl<T> Deref for Timer<T>
 where
     T: Instance,
@@ -676,6 +717,8 @@ impl<T> Periodic for Timer<T> where T: Instance {}
 /// Watchdog timer
 pub struct Wdt<TG> {
     phantom: PhantomData<TG>,
+    timeout: Option<MicrosDurationU64>,
+    last_feed: u64,
 }
 
 /// Watchdog driver
@@ -690,9 +733,23 @@ where
         TG::configure_wdt_src_clk();
         Self {
             phantom: PhantomData::default(),
+            timeout: None,
+            last_feed: 0,
         }
     }
 
+    /// The time left until the watchdog expires, `None` if it isn't running
+    ///
+    /// The counter of the watchdog can't be read back, so this is derived
+    /// from the time of the last feed (or start) as measured by the RTC
+    /// timer. Useful to log how close a feed came to the timeout.
+    pub fn time_to_expiry(&self) -> Option<MicrosDurationU64> {
+        self.timeout.map(|timeout| {
+            let elapsed = crate::rtc_cntl::time_us().saturating_sub(self.last_feed);
+            MicrosDurationU64::micros(timeout.to_micros().saturating_sub(elapsed))
+        })
+    }
+
     fn set_wdt_enabled(&mut self, enabled: bool) {
         let reg_block = unsafe { &*TG::register_block() };
 
@@ -701,6 +758,7 @@ where
             .write(|w| unsafe { w.wdt_wkey().bits(0x50D8_3AA1u32) });
 
         if !enabled {
+            self.timeout = None;
             reg_block.wdtconfig0.write(|w| unsafe { w.bits(0) });
         } else {
             reg_block.wdtconfig0.write(|w| w.wdt_en().bit(true));
@@ -719,6 +777,7 @@ where
             .write(|w| unsafe { w.wdt_wkey().bits(0x50D8_3AA1u32) });
 
         reg_block.wdtfeed.write(|w| unsafe { w.bits(1) });
+        self.last_feed = crate::rtc_cntl::time_us();
 
         reg_block
             .wdtwprotect
@@ -765,6 +824,9 @@ where
             .wdtconfig0
             .modify(|_, w| w.wdt_conf_update_en().set_bit());
 
+        self.timeout = Some(timeout);
+        self.last_feed = crate::rtc_cntl::time_us();
+
         reg_block
             .wdtwprotect
             .write(|w| unsafe { w.wdt_wkey().bits(0u32) });
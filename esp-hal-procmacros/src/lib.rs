@@ -33,6 +33,7 @@ struct RamArgs {
     rtc_slow: bool,
     uninitialized: bool,
     zeroed: bool,
+    persistent: bool,
 }
 
 /// This attribute allows placing statics and functions into ram.
@@ -43,6 +44,15 @@ struct RamArgs {
 /// The uninitialized option will skip initialization of the memory
 /// (e.g. to persist it across resets or deep sleep mode for the RTC RAM)
 ///
+/// The persistent option (RTC RAM on Xtensa targets only) zero-initializes
+/// the memory, except when waking up from deep sleep, so e.g.
+/// `#[ram(rtc_fast, persistent)] static mut COUNT: [u32; 4] = [0; 4];`
+/// keeps its value across deep sleep but starts out zeroed after any other
+/// reset. As the memory is zeroed instead of initialized the initializer has
+/// to be visibly all zeros, i.e. built from `0`, `0.0` and `false` literals
+/// in arrays, tuples and struct expressions, or be
+/// `unsafe { core::mem::zeroed() }`; anything else is rejected.
+///
 /// Not all targets support RTC slow ram.
 #[proc_macro_attribute]
 #[proc_macro_error]
@@ -54,6 +64,7 @@ pub fn ram(args: TokenStream, input: TokenStream) -> TokenStream {
         rtc_slow,
         uninitialized,
         zeroed,
+        persistent,
     } = match FromMeta::from_list(&attr_args) {
         Ok(v) => v,
         Err(e) => {
@@ -71,21 +82,43 @@ pub fn ram(args: TokenStream, input: TokenStream) -> TokenStream {
         );
     }
 
+    #[cfg(not(feature = "xtensa"))]
+    if persistent {
+        abort!(
+            Span::call_site(),
+            "persistent is not available for this target"
+        );
+    }
+
+    if persistent {
+        if let syn::Item::Static(ref item) = item {
+            if !is_zero_expr(&item.expr) {
+                abort!(
+                    item.expr,
+                    "the initializer of a persistent static has to be all zeros, e.g. `0`, \
+                     `[0; N]` or `unsafe {{ core::mem::zeroed() }}`"
+                );
+            }
+        }
+    }
+
     let is_fn = matches!(item, syn::Item::Fn(_));
-    let section_name = match (is_fn, rtc_fast, rtc_slow, uninitialized, zeroed) {
-        (true, false, false, false, false) => Ok(".rwtext"),
-        (true, true, false, false, false) => Ok(".rtc_fast.text"),
-        (true, false, true, false, false) => Ok(".rtc_slow.text"),
+    let section_name = match (is_fn, rtc_fast, rtc_slow, uninitialized, zeroed, persistent) {
+        (true, false, false, false, false, false) => Ok(".rwtext"),
+        (true, true, false, false, false, false) => Ok(".rtc_fast.text"),
+        (true, false, true, false, false, false) => Ok(".rtc_slow.text"),
 
-        (false, false, false, false, false) => Ok(".data"),
+        (false, false, false, false, false, false) => Ok(".data"),
 
-        (false, true, false, false, false) => Ok(".rtc_fast.data"),
-        (false, true, false, true, false) => Ok(".rtc_fast.noinit"),
-        (false, true, false, false, true) => Ok(".rtc_fast.bss"),
+        (false, true, false, false, false, false) => Ok(".rtc_fast.data"),
+        (false, true, false, true, false, false) => Ok(".rtc_fast.noinit"),
+        (false, true, false, false, true, false) => Ok(".rtc_fast.bss"),
+        (false, true, false, false, false, true) => Ok(".rtc_fast.persistent"),
 
-        (false, false, true, false, false) => Ok(".rtc_slow.data"),
-        (false, false, true, true, false) => Ok(".rtc_slow.noinit"),
-        (false, false, true, false, true) => Ok(".rtc_slow.bss"),
+        (false, false, true, false, false, false) => Ok(".rtc_slow.data"),
+        (false, false, true, true, false, false) => Ok(".rtc_slow.noinit"),
+        (false, false, true, false, true, false) => Ok(".rtc_slow.bss"),
+        (false, false, true, false, false, true) => Ok(".rtc_slow.persistent"),
 
         _ => Err(()),
     };
@@ -110,6 +143,47 @@ pub fn ram(args: TokenStream, input: TokenStream) -> TokenStream {
     output.into()
 }
 
+/// Whether `expr` visibly evaluates to all zero bytes, see the persistent
+/// option of [`macro@ram`]
+fn is_zero_expr(expr: &syn::Expr) -> bool {
+    use syn::{Expr, Lit, Stmt};
+
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(int) => int.base10_digits().bytes().all(|digit| digit == b'0'),
+            Lit::Float(float) => float
+                .base10_digits()
+                .bytes()
+                .all(|digit| matches!(digit, b'0' | b'.')),
+            Lit::Bool(lit_bool) => !lit_bool.value,
+            _ => false,
+        },
+        Expr::Array(array) => array.elems.iter().all(is_zero_expr),
+        Expr::Repeat(repeat) => is_zero_expr(&repeat.expr),
+        Expr::Tuple(tuple) => tuple.elems.iter().all(is_zero_expr),
+        Expr::Struct(init) => {
+            init.rest.is_none() && init.fields.iter().all(|field| is_zero_expr(&field.expr))
+        }
+        Expr::Paren(paren) => is_zero_expr(&paren.expr),
+        Expr::Group(group) => is_zero_expr(&group.expr),
+        Expr::Unsafe(block) => match block.block.stmts.as_slice() {
+            [Stmt::Expr(Expr::Call(call))] => {
+                call.args.is_empty()
+                    && matches!(
+                        &*call.func,
+                        Expr::Path(path) if path
+                            .path
+                            .segments
+                            .last()
+                            .map_or(false, |segment| segment.ident == "zeroed")
+                    )
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 /// Marks a function as an interrupt handler
 ///
 /// Used to handle on of the [interrupts](enum.Interrupt.html).
@@ -135,6 +209,19 @@ pub fn ram(args: TokenStream, input: TokenStream) -> TokenStream {
 ///     // code
 /// }
 /// ```
+///
+/// Combined with [`macro@ram`] (in either order) the handler is placed in
+/// RAM, so it can run while the flash cache is disabled, e.g. during a flash
+/// write. Everything it calls has to be in RAM or ROM as well, and it must
+/// not use constants in flash (`.rodata`), e.g. for formatting or panics:
+///
+/// ```rust
+/// #[ram]
+/// #[interrupt]
+/// fn GPIO() {
+///     // code
+/// }
+/// ```
 #[cfg(feature = "interrupt")]
 #[proc_macro_attribute]
 pub fn interrupt(args: TokenStream, input: TokenStream) -> TokenStream {
@@ -266,6 +353,11 @@ pub fn interrupt(args: TokenStream, input: TokenStream) -> TokenStream {
 
     let (ref cfgs, ref attrs) = extract_cfgs(f.attrs.clone());
 
+    // the section and inlining attributes only apply to the trampoline, the
+    // handler itself is always inlined into it
+    f.attrs
+        .retain(|attr| !(eq(attr, "ram") || eq(attr, "link_section") || eq(attr, "inline")));
+
     let export_name = ident_s.to_string();
 
     let trap_frame_in_hal_crate = match hal_crate {
@@ -44,6 +44,12 @@ fn generate_memory_extras() -> Vec<u8> {
         "0x0"
     };
 
+    let reserve_rtc_slow = if cfg!(feature = "ulp") {
+        "0x1000"
+    } else {
+        "0"
+    };
+
     format!(
         "
     /* reserved at the start of DRAM for e.g. the BT stack */
@@ -51,7 +57,7 @@ fn generate_memory_extras() -> Vec<u8> {
     
     /* reserved at the start of the RTC memories for use by the ULP processor */
     RESERVE_RTC_FAST = 0;
-    RESERVE_RTC_SLOW = 0;
+    RESERVE_RTC_SLOW = {reserve_rtc_slow};
     
     /* define stack size for both cores */
     STACK_SIZE = 8k;"
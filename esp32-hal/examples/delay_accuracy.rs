@@ -0,0 +1,66 @@
+//! Checks the accuracy of `Delay` against a hardware timer
+//!
+//! The CPU runs at 240MHz here while the TIMG0 timer counts microseconds from
+//! the APB clock, so the delay has to pick up the configured CPU clock to
+//! match the timer.
+
+#![no_std]
+#![no_main]
+
+use esp32_hal::{
+    clock::{ClockControl, CpuClock},
+    peripherals::Peripherals,
+    prelude::*,
+    timer::TimerGroup,
+    Delay,
+    Rtc,
+};
+use esp_backtrace as _;
+use esp_println::println;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = Peripherals::take();
+    let mut system = peripherals.DPORT.split();
+    let clocks = ClockControl::configure(system.clock_control, CpuClock::Clock240MHz).freeze();
+
+    let timer_group0 = TimerGroup::new(
+        peripherals.TIMG0,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut timer0 = timer_group0.timer0;
+    let mut wdt = timer_group0.wdt;
+    let mut rtc = Rtc::new(peripherals.RTC_CNTL);
+
+    // Disable MWDT and RWDT (Watchdog) flash boot protection
+    wdt.disable();
+    rtc.rwdt.disable();
+
+    timer0.set_tick_hz(1u32.MHz()).unwrap();
+    timer0.set_counter_decrementing(false);
+    timer0.set_auto_reload(false);
+    timer0.reset_counter();
+    timer0.set_counter_active(true);
+
+    let delay = Delay::new(&clocks);
+
+    loop {
+        for us in [100u32, 1_000, 10_000, 100_000, 1_000_000] {
+            let start = timer0.now();
+            delay.delay(us);
+            let elapsed = timer0.now() - start;
+
+            // allow for 1% plus a few microseconds of overhead
+            let tolerance = us as u64 / 100 + 5;
+            assert!(
+                elapsed >= us as u64 && elapsed <= us as u64 + tolerance,
+                "Delay of {}us took {}us",
+                us,
+                elapsed
+            );
+
+            println!("Delay of {}us took {}us", us, elapsed);
+        }
+    }
+}
@@ -19,6 +19,7 @@ use embassy_time::{Duration, Timer};
 use esp32_hal::{
     clock::ClockControl,
     embassy,
+    gpio::{Gpio32, Gpio33, Unknown},
     i2c::I2C,
     peripherals::{Interrupt, Peripherals, I2C0},
     prelude::*,
@@ -32,7 +33,7 @@ use lis3dh_async::{Lis3dh, Range, SlaveAddr};
 use static_cell::StaticCell;
 
 #[embassy_executor::task]
-async fn run(i2c: I2C<'static, I2C0>) {
+async fn run(i2c: I2C<'static, I2C0, Gpio32<Unknown>, Gpio33<Unknown>>) {
     let mut lis3dh = Lis3dh::new_i2c(i2c, SlaveAddr::Alternate).await.unwrap();
     lis3dh.set_range(Range::G8).await.unwrap();
 
@@ -0,0 +1,82 @@
+//! Write and read back 64 bytes on an I2C EEPROM
+//!
+//! The ESP32 I2C FIFO holds 32 bytes, so both the 66 byte write (2 address
+//! bytes and a 64 byte page) and the 64 byte read are sent in segments. This
+//! example checks that the data read back matches what was written, using
+//! an AT24C256 (or another EEPROM with 64 byte pages) at address 0x50.
+//!
+//! The following wiring is assumed:
+//! - SDA => GPIO32
+//! - SCL => GPIO33
+
+#![no_std]
+#![no_main]
+
+use esp32_hal::{
+    clock::ClockControl,
+    gpio::IO,
+    i2c::I2C,
+    peripherals::Peripherals,
+    prelude::*,
+    timer::TimerGroup,
+    Delay,
+    Rtc,
+};
+use esp_backtrace as _;
+use esp_println::println;
+
+const EEPROM_ADDRESS: u8 = 0x50;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = Peripherals::take();
+    let mut system = peripherals.DPORT.split();
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let timer_group0 = TimerGroup::new(
+        peripherals.TIMG0,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut wdt = timer_group0.wdt;
+    let mut rtc = Rtc::new(peripherals.RTC_CNTL);
+
+    // Disable watchdog timer
+    wdt.disable();
+    rtc.rwdt.disable();
+
+    let io = IO::new(peripherals.GPIO, peripherals.IO_MUX);
+    let mut delay = Delay::new(&clocks);
+
+    let mut i2c = I2C::new(
+        peripherals.I2C0,
+        io.pins.gpio32,
+        io.pins.gpio33,
+        100u32.kHz(),
+        &mut system.peripheral_clock_control,
+        &clocks,
+    );
+
+    // memory address 0x0000 followed by one page of data
+    let mut page = [0u8; 2 + 64];
+    for (i, byte) in page[2..].iter_mut().enumerate() {
+        *byte = i as u8 ^ 0xa5;
+    }
+    i2c.write(EEPROM_ADDRESS, &page).unwrap();
+
+    // wait for the EEPROM's write cycle
+    delay.delay_ms(10u32);
+
+    let mut read_back = [0u8; 64];
+    i2c.write_read(EEPROM_ADDRESS, &[0, 0], &mut read_back)
+        .unwrap();
+
+    if read_back == page[2..] {
+        println!("64 byte write and read back OK");
+    } else {
+        println!("Mismatch: wrote {:02x?}", &page[2..]);
+        println!("          read  {:02x?}", read_back);
+    }
+
+    loop {}
+}
@@ -48,7 +48,7 @@ fn main() -> ! {
         &clocks,
         &mut system.peripheral_clock_control,
     );
-    let mut hstimer0 = ledc.get_timer::<HighSpeed>(timer::Number::Timer0);
+    let mut hstimer0 = ledc.get_timer::<HighSpeed>(timer::Number::Timer0).unwrap();
 
     hstimer0
         .configure(timer::config::Config {
@@ -58,7 +58,7 @@ fn main() -> ! {
         })
         .unwrap();
 
-    let mut channel0 = ledc.get_channel(channel::Number::Channel0, led);
+    let mut channel0 = ledc.get_channel(channel::Number::Channel0, led).unwrap();
     channel0
         .configure(channel::config::Config {
             timer: &hstimer0,
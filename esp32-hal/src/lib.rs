@@ -31,6 +31,12 @@ pub unsafe extern "C" fn ESP32Reset() -> ! {
         static mut _rtc_slow_bss_start: u32;
         static mut _rtc_slow_bss_end: u32;
 
+        static mut _rtc_fast_persistent_start: u32;
+        static mut _rtc_fast_persistent_end: u32;
+
+        static mut _rtc_slow_persistent_start: u32;
+        static mut _rtc_slow_persistent_end: u32;
+
         static mut _stack_end_cpu0: u32;
     }
 
@@ -44,6 +50,20 @@ pub unsafe extern "C" fn ESP32Reset() -> ! {
     esp_hal_common::xtensa_lx_rt::zero_bss(&mut _rtc_fast_bss_start, &mut _rtc_fast_bss_end);
     esp_hal_common::xtensa_lx_rt::zero_bss(&mut _rtc_slow_bss_start, &mut _rtc_slow_bss_end);
 
+    // `#[ram(persistent)]` statics are kept when waking up from deep sleep
+    if esp_hal_common::reset::get_reset_reason()
+        != Some(esp_hal_common::rtc_cntl::SocResetReason::CoreDeepSleep)
+    {
+        esp_hal_common::xtensa_lx_rt::zero_bss(
+            &mut _rtc_fast_persistent_start,
+            &mut _rtc_fast_persistent_end,
+        );
+        esp_hal_common::xtensa_lx_rt::zero_bss(
+            &mut _rtc_slow_persistent_start,
+            &mut _rtc_slow_persistent_end,
+        );
+    }
+
     // continue with default reset handler
     esp_hal_common::xtensa_lx_rt::Reset();
 }
@@ -51,7 +51,7 @@ fn main() -> ! {
         &mut system.peripheral_clock_control,
     );
     ledc.set_global_slow_clock(LSGlobalClkSource::APBClk);
-    let mut lstimer0 = ledc.get_timer::<LowSpeed>(timer::Number::Timer2);
+    let mut lstimer0 = ledc.get_timer::<LowSpeed>(timer::Number::Timer2).unwrap();
 
     lstimer0
         .configure(timer::config::Config {
@@ -61,7 +61,7 @@ fn main() -> ! {
         })
         .unwrap();
 
-    let mut channel0 = ledc.get_channel(channel::Number::Channel0, led);
+    let mut channel0 = ledc.get_channel(channel::Number::Channel0, led).unwrap();
     channel0
         .configure(channel::config::Config {
             timer: &lstimer0,
@@ -17,22 +17,19 @@
 use embassy_executor::Executor;
 use embassy_time::{Duration, Timer};
 use esp32c3_hal::{
-    clock::ClockControl,
     embassy,
+    gpio::{Gpio1, Gpio2, Unknown},
     i2c::I2C,
     peripherals::{Interrupt, Peripherals, I2C0},
     prelude::*,
-    timer::TimerGroup,
     Priority,
-    Rtc,
-    IO,
 };
 use esp_backtrace as _;
 use lis3dh_async::{Lis3dh, Range, SlaveAddr};
 use static_cell::StaticCell;
 
 #[embassy_executor::task]
-async fn run(i2c: I2C<'static, I2C0>) {
+async fn run(i2c: I2C<'static, I2C0, Gpio1<Unknown>, Gpio2<Unknown>>) {
     let mut lis3dh = Lis3dh::new_i2c(i2c, SlaveAddr::Alternate).await.unwrap();
     lis3dh.set_range(Range::G8).await.unwrap();
 
@@ -49,46 +46,19 @@ static EXECUTOR: StaticCell<Executor> = StaticCell::new();
 #[entry]
 fn main() -> ! {
     let peripherals = Peripherals::take();
-    let mut system = peripherals.SYSTEM.split();
-    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
-
-    let mut rtc = Rtc::new(peripherals.RTC_CNTL);
-    let timer_group0 = TimerGroup::new(
-        peripherals.TIMG0,
-        &clocks,
-        &mut system.peripheral_clock_control,
-    );
-    let mut wdt0 = timer_group0.wdt;
-    let timer_group1 = TimerGroup::new(
-        peripherals.TIMG1,
-        &clocks,
-        &mut system.peripheral_clock_control,
-    );
-    let mut wdt1 = timer_group1.wdt;
-
-    // Disable watchdog timers
-    rtc.swd.disable();
-    rtc.rwdt.disable();
-    wdt0.disable();
-    wdt1.disable();
-
-    #[cfg(feature = "embassy-time-systick")]
-    embassy::init(
-        &clocks,
-        esp32c3_hal::systimer::SystemTimer::new(peripherals.SYSTIMER),
-    );
-
-    #[cfg(feature = "embassy-time-timg0")]
-    embassy::init(&clocks, timer_group0.timer0);
-
-    let io = IO::new(peripherals.GPIO, peripherals.IO_MUX);
+    let embassy::DefaultParts {
+        clocks,
+        mut peripheral_clock_control,
+        io,
+        ..
+    } = embassy::init_default!(peripherals);
 
     let i2c0 = I2C::new(
         peripherals.I2C0,
         io.pins.gpio1,
         io.pins.gpio2,
         400u32.kHz(),
-        &mut system.peripheral_clock_control,
+        &mut peripheral_clock_control,
         &clocks,
     );
 
@@ -0,0 +1,88 @@
+//! Checks that a failed I2C transaction doesn't corrupt the next one
+//!
+//! This example reads the calibration data from a BMP180 sensor, then forces
+//! a NACK by addressing a device that isn't on the bus while the FIFOs still
+//! hold data, and reads the calibration data again. The second read has to
+//! return exactly the same bytes as the first one.
+//!
+//! The following wiring is assumed:
+//! - SDA => GPIO1
+//! - SCL => GPIO2
+//!
+//! Nothing may answer on address 0x10.
+
+#![no_std]
+#![no_main]
+
+use esp32c3_hal::{
+    clock::ClockControl,
+    gpio::IO,
+    i2c::{Error, I2C},
+    peripherals::Peripherals,
+    prelude::*,
+    timer::TimerGroup,
+    Rtc,
+};
+use esp_backtrace as _;
+use esp_println::println;
+
+const BMP180: u8 = 0x77;
+const NOBODY: u8 = 0x10;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = Peripherals::take();
+    let mut system = peripherals.SYSTEM.split();
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let mut rtc = Rtc::new(peripherals.RTC_CNTL);
+    let timer_group0 = TimerGroup::new(
+        peripherals.TIMG0,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut wdt0 = timer_group0.wdt;
+    let timer_group1 = TimerGroup::new(
+        peripherals.TIMG1,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut wdt1 = timer_group1.wdt;
+
+    // Disable watchdog timers
+    rtc.swd.disable();
+    rtc.rwdt.disable();
+    wdt0.disable();
+    wdt1.disable();
+
+    let io = IO::new(peripherals.GPIO, peripherals.IO_MUX);
+
+    let mut i2c = I2C::new(
+        peripherals.I2C0,
+        io.pins.gpio1,
+        io.pins.gpio2,
+        100u32.kHz(),
+        &mut system.peripheral_clock_control,
+        &clocks,
+    );
+
+    let mut expected = [0u8; 22];
+    i2c.write_read(BMP180, &[0xaa], &mut expected).unwrap();
+    println!("Calibration data: {:02x?}", expected);
+
+    let mut round = 0u32;
+    loop {
+        // leave bytes in the TX FIFO and commands in the command list which a
+        // NACK on the address stops from ever being sent
+        let mut junk = [0u8; 8];
+        let result = i2c.write_read(NOBODY, &[0x55; 16], &mut junk);
+        assert!(matches!(result, Err(Error::AckCheckFailed)));
+
+        let mut data = [0u8; 22];
+        i2c.write_read(BMP180, &[0xaa], &mut data).unwrap();
+        assert_eq!(data, expected);
+
+        round += 1;
+        println!("Round {}: clean read after NACK ok", round);
+    }
+}
@@ -0,0 +1,96 @@
+//! Continuously fades a LED up and down from the LEDC interrupt handler,
+//! without the `async` feature.
+//!
+//! This assumes that a LED is connected to the pin assigned to `led`. (GPIO4)
+
+#![no_std]
+#![no_main]
+
+use esp32c3_hal::{
+    clock::ClockControl,
+    gpio::IO,
+    interrupt,
+    ledc::{
+        channel::{self, ChannelIFace},
+        timer::{self, TimerIFace},
+        LSGlobalClkSource,
+        LowSpeed,
+        LEDC,
+    },
+    peripherals::{self, Peripherals},
+    prelude::*,
+    riscv,
+    timer::TimerGroup,
+    Rtc,
+};
+use esp_backtrace as _;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = Peripherals::take();
+    let mut system = peripherals.SYSTEM.split();
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let mut rtc = Rtc::new(peripherals.RTC_CNTL);
+    let timer_group0 = TimerGroup::new(
+        peripherals.TIMG0,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut wdt0 = timer_group0.wdt;
+    let timer_group1 = TimerGroup::new(
+        peripherals.TIMG1,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut wdt1 = timer_group1.wdt;
+
+    // Disable watchdog timers
+    rtc.swd.disable();
+    rtc.rwdt.disable();
+    wdt0.disable();
+    wdt1.disable();
+
+    let io = IO::new(peripherals.GPIO, peripherals.IO_MUX);
+    let led = io.pins.gpio4.into_push_pull_output();
+
+    let mut ledc = LEDC::new(peripherals.LEDC, &mut system.peripheral_clock_control);
+    ledc.set_global_slow_clock(LSGlobalClkSource::APBClk);
+    let mut lstimer0 = ledc.get_timer::<LowSpeed>(timer::Number::Timer0).unwrap();
+
+    lstimer0
+        .configure(
+            &clocks,
+            timer::config::Config {
+                duty: timer::config::Duty::Duty13Bit,
+                clock_source: timer::LSClockSource::APBClk,
+                frequency: 1u32.kHz(),
+            },
+        )
+        .unwrap();
+
+    let mut channel0 = ledc.get_channel(channel::Number::Channel0, led).unwrap();
+    channel0
+        .configure(channel::config::Config {
+            timer: &lstimer0,
+            duty_pct: 0,
+            pin_config: channel::config::PinConfig::PushPull,
+        })
+        .unwrap();
+
+    interrupt::enable(peripherals::Interrupt::LEDC, interrupt::Priority::Priority1).unwrap();
+    unsafe {
+        riscv::interrupt::enable();
+    }
+
+    // fade up and down once every two seconds, the fades are chained by
+    // `handle_breathe_interrupt` below
+    channel0.breathe(2000).unwrap();
+
+    loop {}
+}
+
+#[interrupt]
+fn LEDC() {
+    channel::handle_breathe_interrupt();
+}
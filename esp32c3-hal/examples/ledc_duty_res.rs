@@ -0,0 +1,82 @@
+//! Configures a LEDC timer with every duty resolution and checks that
+//! `read_duty_res_hw` reads the same resolution back from the hardware, and
+//! that `Duty::from_bits` and `Duty::bits` round-trip.
+
+#![no_std]
+#![no_main]
+
+use esp32c3_hal::{
+    clock::ClockControl,
+    ledc::{
+        timer::{self, TimerHW, TimerIFace},
+        LSGlobalClkSource,
+        LowSpeed,
+        LEDC,
+    },
+    peripherals::Peripherals,
+    prelude::*,
+    timer::TimerGroup,
+    Rtc,
+};
+use esp_backtrace as _;
+use esp_println::println;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = Peripherals::take();
+    let mut system = peripherals.SYSTEM.split();
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let mut rtc = Rtc::new(peripherals.RTC_CNTL);
+    let timer_group0 = TimerGroup::new(
+        peripherals.TIMG0,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut wdt0 = timer_group0.wdt;
+    let timer_group1 = TimerGroup::new(
+        peripherals.TIMG1,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut wdt1 = timer_group1.wdt;
+
+    // Disable watchdog timers
+    rtc.swd.disable();
+    rtc.rwdt.disable();
+    wdt0.disable();
+    wdt1.disable();
+
+    let mut ledc = LEDC::new(peripherals.LEDC, &mut system.peripheral_clock_control);
+    ledc.set_global_slow_clock(LSGlobalClkSource::APBClk);
+    let mut lstimer0 = ledc.get_timer::<LowSpeed>(timer::Number::Timer0).unwrap();
+
+    assert_eq!(timer::config::Duty::from_bits(0), None);
+    assert_eq!(timer::config::Duty::from_bits(15), None);
+
+    for bits in 1..=14 {
+        let duty = timer::config::Duty::from_bits(bits).unwrap();
+        assert_eq!(duty.bits(), bits);
+        assert_eq!(duty.max_count(), 1 << bits);
+
+        // a divisor of 4 from the 80MHz APB clock, in range for every resolution
+        let frequency = (clocks.apb_clock.raw() / 4) >> bits;
+        lstimer0
+            .configure(
+                &clocks,
+                timer::config::Config {
+                    duty,
+                    clock_source: timer::LSClockSource::APBClk,
+                    frequency: frequency.Hz(),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(lstimer0.read_duty_res_hw(), Some(duty));
+        println!("{} bit duty resolution read back ok", bits);
+    }
+
+    println!("Done");
+
+    loop {}
+}
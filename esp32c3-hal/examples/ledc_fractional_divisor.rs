@@ -0,0 +1,109 @@
+//! Configures a LEDC timer for frequencies the integer part of the divisor
+//! alone can't reach and checks that the fractional part programmed in the
+//! hardware brings the output closer to the requested frequency.
+
+#![no_std]
+#![no_main]
+
+use esp32c3_hal::{
+    clock::ClockControl,
+    ledc::{
+        timer::{self, TimerIFace},
+        LSGlobalClkSource,
+        LowSpeed,
+        LEDC,
+    },
+    peripherals::Peripherals,
+    prelude::*,
+    timer::TimerGroup,
+    Rtc,
+};
+use esp_backtrace as _;
+use esp_println::println;
+
+/// Relative error of the frequency output with `divisor` (in 1/256), in ppm
+fn error_ppm(src_freq: u32, max_count: u32, divisor: u32, frequency: u32) -> u64 {
+    // in mHz, `actual_frequency` truncates to whole Hz
+    let achieved = ((src_freq as u64) << 8) * 1000 / (divisor as u64 * max_count as u64);
+    achieved.abs_diff(frequency as u64 * 1000) * 1000 / frequency as u64
+}
+
+#[entry]
+fn main() -> ! {
+    let peripherals = Peripherals::take();
+    let mut system = peripherals.SYSTEM.split();
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let mut rtc = Rtc::new(peripherals.RTC_CNTL);
+    let timer_group0 = TimerGroup::new(
+        peripherals.TIMG0,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut wdt0 = timer_group0.wdt;
+    let timer_group1 = TimerGroup::new(
+        peripherals.TIMG1,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut wdt1 = timer_group1.wdt;
+
+    // Disable watchdog timers
+    rtc.swd.disable();
+    rtc.rwdt.disable();
+    wdt0.disable();
+    wdt1.disable();
+
+    let mut ledc = LEDC::new(peripherals.LEDC, &mut system.peripheral_clock_control);
+    ledc.set_global_slow_clock(LSGlobalClkSource::APBClk);
+    let mut lstimer0 = ledc.get_timer::<LowSpeed>(timer::Number::Timer0).unwrap();
+
+    let duty = timer::config::Duty::Duty13Bit;
+    let src_freq = clocks.apb_clock.raw();
+
+    for frequency in [440u32, 1_000, 3_000, 4_800] {
+        lstimer0
+            .configure(
+                &clocks,
+                timer::config::Config {
+                    duty,
+                    clock_source: timer::LSClockSource::APBClk,
+                    frequency: frequency.Hz(),
+                },
+            )
+            .unwrap();
+
+        let integer = (lstimer0.divisor_integer() as u32) << 8;
+        let divisor = integer | lstimer0.divisor_fraction() as u32;
+
+        let fractional_error = error_ppm(src_freq, duty.max_count(), divisor, frequency);
+        let integer_error = error_ppm(src_freq, duty.max_count(), integer, frequency);
+        assert!(fractional_error < integer_error || divisor == integer);
+        // off by at most half a step of the fractional part
+        assert!(fractional_error <= 1_000_000 / (2 * divisor as u64));
+
+        println!(
+            "{}Hz: divisor {}/256, {}ppm off, {}ppm with the integer part only",
+            frequency, divisor, fractional_error, integer_error
+        );
+    }
+
+    // 80MHz / (440Hz * 8192) = 22.19, the integer part alone gives 443.9Hz
+    lstimer0
+        .configure(
+            &clocks,
+            timer::config::Config {
+                duty,
+                clock_source: timer::LSClockSource::APBClk,
+                frequency: 440u32.Hz(),
+            },
+        )
+        .unwrap();
+    assert_eq!(lstimer0.divisor_integer(), 22);
+    assert_eq!(lstimer0.divisor_fraction(), 50);
+    assert_eq!(lstimer0.actual_frequency(&clocks), 439u32.Hz());
+
+    println!("Done");
+
+    loop {}
+}
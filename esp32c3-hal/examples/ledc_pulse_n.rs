@@ -0,0 +1,136 @@
+//! Checks that `Channel::pulse_n` outputs exactly the requested number of
+//! pulses
+//!
+//! The output of the channel is read back on a second GPIO and its rising
+//! edges are counted, both for pulse trains counted by the hardware and for
+//! the longer ones counted from the overflow interrupt, including one which
+//! needs an interrupt every period.
+//!
+//! The following wiring is assumed:
+//! - GPIO4 (LEDC output) => GPIO5 (input)
+
+#![no_std]
+#![no_main]
+
+use esp32c3_hal::{
+    clock::ClockControl,
+    gpio::IO,
+    interrupt,
+    ledc::{
+        channel::{self, ChannelIFace},
+        timer::{self, TimerIFace},
+        LSGlobalClkSource,
+        LowSpeed,
+        LEDC,
+    },
+    peripherals::{self, Peripherals},
+    prelude::*,
+    riscv,
+    timer::TimerGroup,
+    Rtc,
+};
+use esp_backtrace as _;
+use esp_println::println;
+
+/// PWM period in microseconds, at 1kHz
+const PERIOD_US: u64 = 1000;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = Peripherals::take();
+    let mut system = peripherals.SYSTEM.split();
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let mut rtc = Rtc::new(peripherals.RTC_CNTL);
+    let timer_group0 = TimerGroup::new(
+        peripherals.TIMG0,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut timer0 = timer_group0.timer0;
+    let mut wdt0 = timer_group0.wdt;
+    let timer_group1 = TimerGroup::new(
+        peripherals.TIMG1,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut wdt1 = timer_group1.wdt;
+
+    // Disable watchdog timers
+    rtc.swd.disable();
+    rtc.rwdt.disable();
+    wdt0.disable();
+    wdt1.disable();
+
+    timer0.set_tick_hz(1u32.MHz()).unwrap();
+    timer0.set_counter_decrementing(false);
+    timer0.set_auto_reload(false);
+    timer0.reset_counter();
+    timer0.set_counter_active(true);
+
+    let io = IO::new(peripherals.GPIO, peripherals.IO_MUX);
+    let output = io.pins.gpio4.into_push_pull_output();
+    let input = io.pins.gpio5.into_floating_input();
+
+    let mut ledc = LEDC::new(peripherals.LEDC, &mut system.peripheral_clock_control);
+    ledc.set_global_slow_clock(LSGlobalClkSource::APBClk);
+    let mut lstimer0 = ledc.get_timer::<LowSpeed>(timer::Number::Timer0).unwrap();
+
+    lstimer0
+        .configure(
+            &clocks,
+            timer::config::Config {
+                duty: timer::config::Duty::Duty10Bit,
+                clock_source: timer::LSClockSource::APBClk,
+                frequency: 1u32.kHz(),
+            },
+        )
+        .unwrap();
+
+    let mut channel0 = ledc.get_channel(channel::Number::Channel0, output).unwrap();
+    channel0
+        .configure(channel::config::Config {
+            timer: &lstimer0,
+            duty_pct: 25,
+            pin_config: channel::config::PinConfig::PushPull,
+        })
+        .unwrap();
+
+    interrupt::enable(peripherals::Interrupt::LEDC, interrupt::Priority::Priority1).unwrap();
+    unsafe {
+        riscv::interrupt::enable();
+    }
+
+    // counted by the hardware up to 1023 pulses, above from the overflow
+    // interrupt every 1024, 767 and (1031 being prime) 1 periods
+    for count in [10u32, 1023, 2049, 3069, 1032] {
+        // the hardware counted pulse trains end with a duty of 0
+        channel0.set_duty(25).unwrap();
+        channel0.pulse_n(count).unwrap();
+
+        // the current period is output before the pulses are counted
+        let mut was_high = true;
+        let mut edges = 0;
+        let mut last_edge = timer0.now();
+        while channel0.is_pulse_running() || timer0.now() - last_edge < 3 * PERIOD_US {
+            let high = input.is_high().unwrap();
+            if high && !was_high {
+                edges += 1;
+                last_edge = timer0.now();
+            }
+            was_high = high;
+        }
+
+        println!("{} pulses requested, {} output", count, edges);
+        assert_eq!(edges, count, "Wrong number of pulses");
+    }
+
+    println!("Done");
+
+    loop {}
+}
+
+#[interrupt]
+fn LEDC() {
+    channel::handle_pulse_interrupt();
+}
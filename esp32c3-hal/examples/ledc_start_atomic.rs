@@ -0,0 +1,144 @@
+//! Checks that `LEDC::start_output_atomic` starts the PWM output with a
+//! complete first period
+//!
+//! The output of the channel is read back on a second GPIO and its edges are
+//! timestamped with the TIMG0 timer, like a logic analyzer would capture them:
+//! the output has to stay low until the first rising edge, and the first
+//! pulse and period have to be as long as configured.
+//!
+//! The following wiring is assumed:
+//! - GPIO4 (LEDC output) => GPIO5 (input)
+
+#![no_std]
+#![no_main]
+
+use esp32c3_hal::{
+    clock::ClockControl,
+    gpio::IO,
+    ledc::{
+        channel::{self, ChannelIFace},
+        timer::{self, TimerIFace},
+        LSGlobalClkSource,
+        LowSpeed,
+        LEDC,
+    },
+    peripherals::Peripherals,
+    prelude::*,
+    timer::TimerGroup,
+    Rtc,
+};
+use esp_backtrace as _;
+use esp_println::println;
+
+/// PWM period in microseconds, at 1kHz
+const PERIOD_US: u64 = 1000;
+/// High time of a pulse in microseconds, at 25% duty
+const HIGH_US: u64 = 250;
+/// Allowed deviation of the captured timestamps in microseconds
+const TOLERANCE_US: u64 = 5;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = Peripherals::take();
+    let mut system = peripherals.SYSTEM.split();
+    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
+
+    let mut rtc = Rtc::new(peripherals.RTC_CNTL);
+    let timer_group0 = TimerGroup::new(
+        peripherals.TIMG0,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut timer0 = timer_group0.timer0;
+    let mut wdt0 = timer_group0.wdt;
+    let timer_group1 = TimerGroup::new(
+        peripherals.TIMG1,
+        &clocks,
+        &mut system.peripheral_clock_control,
+    );
+    let mut wdt1 = timer_group1.wdt;
+
+    // Disable watchdog timers
+    rtc.swd.disable();
+    rtc.rwdt.disable();
+    wdt0.disable();
+    wdt1.disable();
+
+    timer0.set_tick_hz(1u32.MHz()).unwrap();
+    timer0.set_counter_decrementing(false);
+    timer0.set_auto_reload(false);
+    timer0.reset_counter();
+    timer0.set_counter_active(true);
+
+    let io = IO::new(peripherals.GPIO, peripherals.IO_MUX);
+    let output = io.pins.gpio4.into_push_pull_output();
+    let input = io.pins.gpio5.into_floating_input();
+
+    let mut ledc = LEDC::new(peripherals.LEDC, &mut system.peripheral_clock_control);
+    ledc.set_global_slow_clock(LSGlobalClkSource::APBClk);
+    let mut lstimer0 = ledc.get_timer::<LowSpeed>(timer::Number::Timer0).unwrap();
+
+    lstimer0
+        .configure_deferred(
+            &clocks,
+            timer::config::Config {
+                duty: timer::config::Duty::Duty10Bit,
+                clock_source: timer::LSClockSource::APBClk,
+                frequency: 1u32.kHz(),
+            },
+        )
+        .unwrap();
+
+    let mut channel0 = ledc.get_channel(channel::Number::Channel0, output).unwrap();
+    channel0
+        .configure(channel::config::Config {
+            timer: &lstimer0,
+            duty_pct: 25,
+            pin_config: channel::config::PinConfig::PushPull,
+        })
+        .unwrap();
+
+    ledc.start_output_atomic(&lstimer0, &mut channel0).unwrap();
+    let started = timer0.now();
+
+    // the output is enabled at the end of the period in which this returned
+    assert!(input.is_low().unwrap(), "Output high right after the start");
+
+    let wait_for = |high: bool| loop {
+        let now = timer0.now();
+        if input.is_high().unwrap() == high {
+            return now;
+        }
+        assert!(
+            now - started < 3 * PERIOD_US,
+            "No edge within three periods of the start"
+        );
+    };
+
+    let first_rise = wait_for(true);
+    let first_fall = wait_for(false);
+    let second_rise = wait_for(true);
+
+    let high = first_fall - first_rise;
+    let period = second_rise - first_rise;
+    println!(
+        "First rising edge {}us after the start, high for {}us, period {}us",
+        first_rise - started,
+        high,
+        period
+    );
+
+    assert!(first_rise - started <= PERIOD_US + TOLERANCE_US);
+    assert!(
+        high.abs_diff(HIGH_US) <= TOLERANCE_US,
+        "Malformed first pulse"
+    );
+    assert!(
+        period.abs_diff(PERIOD_US) <= TOLERANCE_US,
+        "Malformed first period"
+    );
+
+    println!("Done");
+
+    loop {}
+}
@@ -13,6 +13,7 @@ use esp32h2_hal::{
     clock::ClockControl,
     gpio::{Event, Gpio9, Input, PullDown, IO},
     interrupt,
+    macros::ram,
     peripherals::{self, Peripherals},
     prelude::*,
     riscv,
@@ -75,15 +76,14 @@ fn main() -> ! {
     }
 }
 
+#[ram]
 #[interrupt]
 fn GPIO() {
+    // this runs from RAM, so nothing in here may need flash: no formatting
+    // (`println!`) and no panics (`unwrap`), their strings are in `.rodata`
     critical_section::with(|cs| {
-        esp_println::println!("Interrupt");
-
-        BUTTON
-            .borrow_ref_mut(cs)
-            .as_mut()
-            .unwrap()
-            .clear_interrupt();
+        if let Some(button) = BUTTON.borrow_ref_mut(cs).as_mut() {
+            button.clear_interrupt();
+        }
     });
 }
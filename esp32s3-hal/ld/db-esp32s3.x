@@ -232,6 +232,15 @@ SECTIONS {
     . = ALIGN (4);
   }
 
+ .rtc_fast.persistent ORIGIN(rtc_fast_dram_seg) + SIZEOF(.rtc_fast.text) + SIZEOF(.rtc_fast.data) + SIZEOF(.rtc_fast.bss) + SIZEOF(.rtc_fast.noinit) (NOLOAD) :
+  {
+    . = ALIGN(4);
+    _rtc_fast_persistent_start = ABSOLUTE(.);
+    *(.rtc_fast.persistent .rtc_fast.persistent.*)
+    . = ALIGN (4);
+    _rtc_fast_persistent_end = ABSOLUTE(.);
+  }
+
  .rtc_slow.text ORIGIN(rtc_slow_seg) : 
     AT(_text_size + SIZEOF(.header) + SIZEOF(.pre_header) + SIZEOF(.rodata) + SIZEOF(.rwtext) + 
       SIZEOF(.rtc_fast.text) + SIZEOF(.rtc_fast.data) + SIZEOF(.rtc_fast.bss)) 
@@ -276,6 +285,15 @@ SECTIONS {
     . = ALIGN (4);
   }
 
+ .rtc_slow.persistent ORIGIN(rtc_slow_seg) + SIZEOF(.rtc_slow.text) + SIZEOF(.rtc_slow.data) + SIZEOF(.rtc_slow.bss) + SIZEOF(.rtc_slow.noinit) (NOLOAD) :
+  {
+    . = ALIGN(4);
+    _rtc_slow_persistent_start = ABSOLUTE(.);
+    *(.rtc_slow.persistent .rtc_slow.persistent.*)
+    . = ALIGN (4);
+    _rtc_slow_persistent_end = ABSOLUTE(.);
+  }
+
  .external.data :
   {
     _external_data_start = ABSOLUTE(.);